@@ -3,11 +3,14 @@ use std::rc::Rc;
 use wasm_bindgen::JsCast;
 use web_sys::{HtmlCanvasElement, WebGl2RenderingContext, WebGlContextAttributes};
 
-use glow::HasContext;
 use nalgebra::{Point2, Vector2};
 
 use crate::input::EventHandlers;
-use crate::{error::InitError, geom::Screen, gl, util, Color4, Event};
+use crate::{
+    error::InitError,
+    geom::{Screen, Viewport},
+    gl, util, Color4, Event,
+};
 
 #[derive(Debug, Clone)]
 pub struct CanvasCaps {
@@ -53,7 +56,7 @@ impl Canvas {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn from_element(_: HtmlCanvasElement, _: CanvasSize) -> Result<Self, Error> {
+    pub fn from_element(_: HtmlCanvasElement, _: CanvasSizeConfig) -> Result<Self, InitError> {
         // This is only in here as a workaround for the fact that Visual Studio
         // Code ignores our target setting in .cargo/config.toml for some
         // reason. Then, `glow::Context::from_webgl1_context` is not defined
@@ -68,8 +71,6 @@ impl Canvas {
     ) -> Result<Self, InitError> {
         use web_sys::WebGlPowerPreference;
 
-        let event_handlers = EventHandlers::new(element.clone())?;
-
         let mut context_attributes = WebGlContextAttributes::new();
         context_attributes.alpha(false);
         context_attributes.depth(true);
@@ -88,6 +89,7 @@ impl Canvas {
         let glow_context = glow::Context::from_webgl2_context(webgl_context);
         let gl = Rc::new(gl::Context::new(glow_context));
         let caps = CanvasCaps::new(gl.clone());
+        let event_handlers = EventHandlers::new(element.clone(), gl.clone())?;
 
         util::make_canvas_focusable(&element);
 
@@ -137,6 +139,19 @@ impl Canvas {
         }
     }
 
+    /// Renders into a part of the canvas. `f` receives the screen of the
+    /// viewport, which can be used to set up a camera for it.
+    pub fn with_viewport<F, R>(&self, viewport: &Viewport, f: F) -> R
+    where
+        F: FnOnce(Screen) -> R,
+    {
+        let screen = self.screen();
+
+        gl::with_viewport(&self.gl, viewport.gl_rect(screen), || {
+            f(viewport.screen(screen))
+        })
+    }
+
     pub fn pop_event(&mut self) -> Option<Event> {
         self.adjust_sizes();
 
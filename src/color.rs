@@ -1,7 +1,8 @@
 use bytemuck_derive::{Pod, Zeroable};
 use nalgebra::{Vector3, Vector4};
+use serde::Serialize;
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Zeroable, Pod)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Zeroable, Pod, Serialize)]
 #[repr(C)]
 pub struct Color3 {
     pub r: f32,
@@ -9,7 +10,7 @@ pub struct Color3 {
     pub b: f32,
 }
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Zeroable, Pod)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Zeroable, Pod, Serialize)]
 #[repr(C)]
 pub struct Color4 {
     pub r: f32,
@@ -61,6 +62,15 @@ impl Color4 {
     pub fn to_linear(self) -> Color4 {
         Color4::new(self.r.powf(2.2), self.g.powf(2.2), self.b.powf(2.2), self.a)
     }
+
+    pub fn lerp(self, other: Color4, t: f32) -> Color4 {
+        Color4::new(
+            self.r + (other.r - self.r) * t,
+            self.g + (other.g - self.g) * t,
+            self.b + (other.b - self.b) * t,
+            self.a + (other.a - self.a) * t,
+        )
+    }
 }
 
 impl From<[f32; 3]> for Color3 {
@@ -7,10 +7,10 @@ use crate::{
     al,
     data::{Sprite, SpriteBatch},
     error::InitError,
-    geom::{Rect, Screen},
+    geom::{Rect, Screen, Viewport},
     gl::{self, DrawParams, Texture, Uniform},
     input::InputState,
-    pass::{ColorPass, InstancedColorPass, SpritePass, ViewMatrices},
+    pass::{ColorPass, InstancedColorPass, InstancedSpritePass, SpritePass, ViewMatrices},
     plot::PlotPass,
     Canvas, Color4, Config, Event, FrameError,
 };
@@ -23,6 +23,7 @@ pub struct Context {
     sprite_pass: Rc<SpritePass>,
     color_pass: Rc<ColorPass>,
     instanced_color_pass: Rc<InstancedColorPass>,
+    instanced_sprite_pass: Rc<InstancedSpritePass>,
     plot_pass: Rc<PlotPass>,
 
     debug_matrices: Option<Uniform<ViewMatrices>>,
@@ -54,6 +55,7 @@ impl Context {
         let sprite_pass = Rc::new(SpritePass::new(canvas.gl())?);
         let color_pass = Rc::new(ColorPass::new(canvas.gl())?);
         let instanced_color_pass = Rc::new(InstancedColorPass::new(canvas.gl())?);
+        let instanced_sprite_pass = Rc::new(InstancedSpritePass::new(canvas.gl())?);
         let plot_pass = Rc::new(PlotPass::new(color_pass.clone()));
 
         Ok(Context {
@@ -63,6 +65,7 @@ impl Context {
             sprite_pass,
             color_pass,
             instanced_color_pass,
+            instanced_sprite_pass,
             plot_pass,
             debug_matrices: None,
             debug_sprite_batch: None,
@@ -103,6 +106,21 @@ impl Context {
         self.canvas.borrow().screen()
     }
 
+    /// Renders into a part of the canvas, e.g. for split-screen. `f` receives
+    /// the screen of the viewport.
+    ///
+    /// The canvas is not borrowed while `f` runs.
+    pub fn with_viewport<F, R>(&self, viewport: &Viewport, f: F) -> R
+    where
+        F: FnOnce(Screen) -> R,
+    {
+        let screen = self.screen();
+
+        gl::with_viewport(&*self.gl(), viewport.gl_rect(screen), || {
+            f(viewport.screen(screen))
+        })
+    }
+
     pub fn clear_color_and_depth(&self, color: Color4, depth: f32) {
         gl::clear_color_and_depth(&*self.gl(), color, depth);
     }
@@ -127,6 +145,10 @@ impl Context {
         self.instanced_color_pass.clone()
     }
 
+    pub fn instanced_sprite_pass(&self) -> Rc<InstancedSpritePass> {
+        self.instanced_sprite_pass.clone()
+    }
+
     pub fn plot_pass(&self) -> Rc<PlotPass> {
         self.plot_pass.clone()
     }
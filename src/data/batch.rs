@@ -5,9 +5,16 @@ use crate::gl::{
 };
 
 use super::{
-    ColorVertex, Geometry, GeometryBuffer, LineTag, Mesh, PrimitiveTag, SpriteVertex, TriangleTag,
+    ColorVertex, Geometry, GeometryBuffer, LineTag, Mesh, PackedColorVertex, PackedSpriteVertex,
+    PrimitiveTag, SpriteVertex, TriangleTag,
 };
 
+/// Collects geometry and uploads it to the GPU on demand.
+///
+/// The elements are uploaded as `u16` while the batch has at most 65535
+/// vertices, halving the size of the element buffer, and as `u32` otherwise.
+///
+/// The geometry is uploaded again after the context has been restored.
 pub struct GeometryBatch<P, V>
 where
     V: Vertex,
@@ -15,6 +22,7 @@ where
     buffer: GeometryBuffer<P, V>,
     vertex_array: VertexArray<V>,
     dirty: bool,
+    generation: u64,
 }
 
 pub type TriangleBatch<V> = GeometryBatch<TriangleTag, V>;
@@ -24,11 +32,16 @@ pub type SpriteBatch = TriangleBatch<SpriteVertex>;
 pub type ColorTriangleBatch = TriangleBatch<ColorVertex>;
 pub type ColorLineBatch = LineBatch<ColorVertex>;
 
+pub type PackedSpriteBatch = TriangleBatch<PackedSpriteVertex>;
+pub type PackedColorTriangleBatch = TriangleBatch<PackedColorVertex>;
+pub type PackedColorLineBatch = LineBatch<PackedColorVertex>;
+
 impl<P, V> GeometryBatch<P, V>
 where
     V: Vertex,
 {
     pub fn new(gl: Rc<gl::Context>) -> Result<Self, gl::Error> {
+        let generation = gl.generation();
         let element_buffer = ElementBuffer::new(gl.clone())?;
         let vertex_buffer = VertexBuffer::new(gl)?;
         let vertex_array = VertexArray::new(Rc::new(element_buffer), Rc::new(vertex_buffer))?;
@@ -37,16 +50,20 @@ where
             buffer: GeometryBuffer::new(),
             vertex_array,
             dirty: false,
+            generation,
         })
     }
 
     pub fn flush(&mut self) {
-        if self.dirty {
+        let generation = self.vertex_array.gl().generation();
+
+        if self.dirty || self.generation != generation {
             self.buffer.upload(
-                &*self.vertex_array.element_buffer(),
-                &*self.vertex_array.vertex_buffers(),
+                &self.vertex_array.element_buffer(),
+                &self.vertex_array.vertex_buffers(),
             );
             self.dirty = false;
+            self.generation = generation;
         }
     }
 
@@ -54,6 +71,12 @@ where
         &self.vertex_array
     }
 
+    /// Returns the geometry that has been pushed, e.g. for inspecting it on
+    /// the CPU.
+    pub fn buffer(&self) -> &GeometryBuffer<P, V> {
+        &self.buffer
+    }
+
     pub fn num_elements(&self) -> usize {
         self.buffer.num_elements()
     }
@@ -94,8 +117,11 @@ where
         self.extend(iter);
     }
 
-    pub fn into_mesh(mut self) -> Mesh<V> {
-        self.flush();
+    pub fn into_mesh(self) -> Mesh<V> {
+        self.buffer.upload_static(
+            &self.vertex_array.element_buffer(),
+            &self.vertex_array.vertex_buffers(),
+        );
         let element_range = 0..self.vertex_array.element_buffer().len();
         Mesh::new(
             Rc::new(self.vertex_array),
@@ -149,6 +175,7 @@ where
     vertex_array: VertexArray<(V, I)>,
     instances: Vec<I>,
     dirty: bool,
+    generation: u64,
 }
 
 impl<V, I> InstanceBatch<V, I>
@@ -165,6 +192,7 @@ where
         )?;
 
         Ok(Self {
+            generation: mesh.gl().generation(),
             mesh,
             vertex_array,
             instances: Vec::new(),
@@ -178,9 +206,12 @@ where
     }
 
     pub fn flush(&mut self) {
-        if self.dirty {
+        let generation = self.mesh.gl().generation();
+
+        if self.dirty || self.generation != generation {
             self.vertex_array.vertex_buffers().1.set(&self.instances);
             self.dirty = false;
+            self.generation = generation;
         }
     }
 
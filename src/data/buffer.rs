@@ -32,6 +32,14 @@ impl<P, V> GeometryBuffer<P, V> {
     pub fn num_vertices(&self) -> usize {
         self.vertices.len()
     }
+
+    pub fn elements(&self) -> &[u32] {
+        &self.elements
+    }
+
+    pub fn vertices(&self) -> &[V] {
+        &self.vertices
+    }
 }
 
 impl<P, V> GeometryBuffer<P, V>
@@ -57,7 +65,8 @@ impl<P, V> GeometryBuffer<P, V>
 where
     V: Vertex,
 {
-    pub fn upload(&mut self, element_buffer: &ElementBuffer<u32>, vertex_buffer: &VertexBuffer<V>) {
+    /// Uploads the geometry, using `u16` elements if they are sufficient.
+    pub fn upload(&mut self, element_buffer: &ElementBuffer, vertex_buffer: &VertexBuffer<V>) {
         /*#[cfg(feature = "coarse-prof")]
         coarse_prof::profile_string_name!(format!(
             "<{}, {}>::upload",
@@ -65,7 +74,14 @@ where
             std::any::type_name::<V>().split("::").last().unwrap(),
         ));*/
 
-        element_buffer.set(&self.elements);
+        element_buffer.set_compact(&self.elements);
         vertex_buffer.set(&self.vertices);
     }
+
+    /// Uploads the geometry for a buffer that is not going to change, so that
+    /// it is kept for restoring after context loss.
+    pub fn upload_static(&self, element_buffer: &ElementBuffer, vertex_buffer: &VertexBuffer<V>) {
+        element_buffer.set_compact_static(&self.elements);
+        vertex_buffer.set_static(&self.vertices);
+    }
 }
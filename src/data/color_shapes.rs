@@ -0,0 +1,689 @@
+use nalgebra::{Point2, Point3, Vector2};
+
+use crate::{
+    geom::{Circle, Rect},
+    Color4,
+};
+
+use super::{
+    quad_line_indices, quad_triangle_indices, ColorVertex, Geometry, LineTag, TriangleTag,
+};
+
+/// A color gradient, given by stops at increasing positions in `[0, 1]`.
+///
+/// Positions outside of the stops are clamped to the color of the closest
+/// stop.
+#[derive(Debug, Clone)]
+pub struct Gradient {
+    pub stops: Vec<(f32, Color4)>,
+}
+
+/// A rectangle with one color per corner, in the order of `Rect::corners`.
+#[derive(Debug, Copy, Clone)]
+pub struct ColorCornersRect {
+    pub rect: Rect,
+    pub z: f32,
+    pub colors: [Color4; 4],
+}
+
+/// A rectangle filled with a linear gradient that goes from `start` to `end`.
+#[derive(Debug, Clone)]
+pub struct LinearGradientRect {
+    pub rect: Rect,
+    pub z: f32,
+    pub start: Point2<f32>,
+    pub end: Point2<f32>,
+    pub gradient: Gradient,
+}
+
+/// A circle filled with a radial gradient that goes from the center to the
+/// circle's radius.
+#[derive(Debug, Clone)]
+pub struct RadialGradientCircle {
+    pub circle: Circle,
+    pub z: f32,
+    pub angle: f32,
+    pub num_segments: usize,
+    pub gradient: Gradient,
+}
+
+/// A rectangle with rounded corners.
+///
+/// Radii are given per corner, in the order of `Rect::corners`, and are
+/// clamped to half of the rectangle's smaller side.
+#[derive(Debug, Copy, Clone)]
+pub struct ColorRoundedRect {
+    pub rect: Rect,
+    pub z: f32,
+    pub radii: [f32; 4],
+    pub num_segments_per_corner: usize,
+    pub color: Color4,
+}
+
+/// A segment of a circle's outline with a given thickness.
+#[derive(Debug, Copy, Clone)]
+pub struct ColorArc {
+    pub circle: Circle,
+    pub z: f32,
+    pub start_angle: f32,
+    pub angle_size: f32,
+    pub thickness: f32,
+    pub num_segments: usize,
+    pub color: Color4,
+}
+
+/// A slice of a circle, going from the center to the circle's outline.
+#[derive(Debug, Copy, Clone)]
+pub struct ColorPieSlice {
+    pub circle: Circle,
+    pub z: f32,
+    pub start_angle: f32,
+    pub angle_size: f32,
+    pub num_segments: usize,
+    pub color: Color4,
+}
+
+/// A segment of an annulus. Use an `angle_size` of `2.0 * PI` for a full
+/// ring.
+#[derive(Debug, Copy, Clone)]
+pub struct ColorRing {
+    pub center: Point2<f32>,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub z: f32,
+    pub start_angle: f32,
+    pub angle_size: f32,
+    pub num_segments: usize,
+    pub color: Color4,
+}
+
+impl Gradient {
+    pub fn new(start: Color4, end: Color4) -> Self {
+        Self {
+            stops: vec![(0.0, start), (1.0, end)],
+        }
+    }
+
+    pub fn color_at(&self, t: f32) -> Color4 {
+        let first = match self.stops.first() {
+            Some(first) => first,
+            None => return Color4::default(),
+        };
+
+        if t <= first.0 {
+            return first.1;
+        }
+
+        for (a, b) in self.stops.iter().zip(self.stops.iter().skip(1)) {
+            if t <= b.0 {
+                let span = b.0 - a.0;
+                let s = if span > 0.0 { (t - a.0) / span } else { 1.0 };
+                return a.1.lerp(b.1, s);
+            }
+        }
+
+        self.stops.last().unwrap().1
+    }
+}
+
+impl Geometry<TriangleTag> for ColorCornersRect {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        elements.extend_from_slice(&quad_triangle_indices(vertices.len() as u32));
+
+        for (p, color) in self.rect.corners().iter().zip(self.colors) {
+            vertices.push(ColorVertex {
+                position: Point3::new(p.x, p.y, self.z),
+                color,
+            });
+        }
+    }
+}
+
+impl Geometry<LineTag> for ColorCornersRect {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        elements.extend_from_slice(&quad_line_indices(vertices.len() as u32));
+
+        for (p, color) in self.rect.corners().iter().zip(self.colors) {
+            vertices.push(ColorVertex {
+                position: Point3::new(p.x, p.y, self.z),
+                color,
+            });
+        }
+    }
+}
+
+impl LinearGradientRect {
+    fn t(&self, p: Point2<f32>) -> f32 {
+        let axis = self.end - self.start;
+        let norm_sq = axis.norm_squared();
+
+        if norm_sq > 0.0 {
+            (p - self.start).dot(&axis) / norm_sq
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the positions at which the gradient's color is not affine.
+    fn cuts(&self) -> Vec<f32> {
+        self.gradient.stops.iter().map(|(t, _)| *t).collect()
+    }
+}
+
+impl Geometry<TriangleTag> for LinearGradientRect {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        // Within each band between two stops, the color is an affine function
+        // of the position, so it is interpolated exactly by the rasterizer.
+        // Thus, we split the rectangle into one convex polygon per band.
+        let cuts = self.cuts();
+        let corners = self.rect.corners().to_vec();
+
+        for i in 0..=cuts.len() {
+            let lo = if i > 0 { Some(cuts[i - 1]) } else { None };
+            let hi = cuts.get(i).copied();
+
+            let mut band = corners.clone();
+            if let Some(lo) = lo {
+                band = clip_polygon(&band, |p| self.t(p) - lo);
+            }
+            if let Some(hi) = hi {
+                band = clip_polygon(&band, |p| hi - self.t(p));
+            }
+
+            if band.len() < 3 {
+                continue;
+            }
+
+            let start_index = vertices.len() as u32;
+            for p in band.iter() {
+                vertices.push(ColorVertex {
+                    position: Point3::new(p.x, p.y, self.z),
+                    color: self.gradient.color_at(self.t(*p)),
+                });
+            }
+            for j in 1..band.len() as u32 - 1 {
+                elements.extend_from_slice(&[start_index, start_index + j, start_index + j + 1]);
+            }
+        }
+    }
+}
+
+impl Geometry<LineTag> for LinearGradientRect {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let cuts = self.cuts();
+
+        for edge in self.rect.edges() {
+            let (t0, t1) = (self.t(edge.0), self.t(edge.1));
+
+            // Split the edge at each stop that it crosses.
+            let mut ss = vec![0.0, 1.0];
+            if (t1 - t0).abs() > 0.0 {
+                for cut in cuts.iter() {
+                    let s = (cut - t0) / (t1 - t0);
+                    if s > 0.0 && s < 1.0 {
+                        ss.push(s);
+                    }
+                }
+            }
+            ss.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let points = ss
+                .iter()
+                .map(|s| edge.0 + edge.delta() * *s)
+                .collect::<Vec<_>>();
+            let start_index = vertices.len() as u32;
+
+            for p in points.iter() {
+                vertices.push(ColorVertex {
+                    position: Point3::new(p.x, p.y, self.z),
+                    color: self.gradient.color_at(self.t(*p)),
+                });
+            }
+            for j in 0..points.len() as u32 - 1 {
+                elements.extend_from_slice(&[start_index + j, start_index + j + 1]);
+            }
+        }
+    }
+}
+
+impl Geometry<TriangleTag> for RadialGradientCircle {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let n = self.num_segments;
+
+        // Every stop inside of the circle gets its own ring of vertices.
+        let mut radii = vec![0.0];
+        for (t, _) in self.gradient.stops.iter() {
+            if *t > 0.0 && *t < 1.0 {
+                radii.push(*t);
+            }
+        }
+        radii.push(1.0);
+
+        let start_index = vertices.len() as u32;
+
+        vertices.push(ColorVertex {
+            position: Point3::new(self.circle.center.x, self.circle.center.y, self.z),
+            color: self.gradient.color_at(0.0),
+        });
+
+        for t in radii.iter().skip(1) {
+            let circle = Circle {
+                center: self.circle.center,
+                radius: self.circle.radius * t,
+            };
+            let color = self.gradient.color_at(*t);
+
+            for p in circle.points(self.angle, n) {
+                vertices.push(ColorVertex {
+                    position: Point3::new(p.x, p.y, self.z),
+                    color,
+                });
+            }
+        }
+
+        let ring_start = |ring: usize| start_index + 1 + (ring * n) as u32;
+
+        for i in 0..n {
+            let next = (i + 1) % n;
+
+            elements.extend_from_slice(&[
+                start_index,
+                ring_start(0) + next as u32,
+                ring_start(0) + i as u32,
+            ]);
+
+            for ring in 0..radii.len() - 2 {
+                let inner = ring_start(ring);
+                let outer = ring_start(ring + 1);
+
+                elements.extend_from_slice(&[
+                    inner + i as u32,
+                    outer + i as u32,
+                    outer + next as u32,
+                    outer + next as u32,
+                    inner + next as u32,
+                    inner + i as u32,
+                ]);
+            }
+        }
+    }
+}
+
+impl Geometry<LineTag> for RadialGradientCircle {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let points = self
+            .circle
+            .points(self.angle, self.num_segments)
+            .collect::<Vec<_>>();
+
+        write_polyline(
+            &points,
+            true,
+            self.z,
+            self.gradient.color_at(1.0),
+            elements,
+            vertices,
+        );
+    }
+}
+
+impl ColorRoundedRect {
+    fn outline(&self) -> Vec<Point2<f32>> {
+        let max_radius = self.rect.size.x.min(self.rect.size.y).max(0.0) / 2.0;
+        let corners = self.rect.corners();
+
+        // Directions from each corner towards the center of its arc, and the
+        // angle at which the arc starts, both in the order of `Rect::corners`.
+        let inward = [
+            Vector2::new(1.0, 1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+        ];
+        let start_angles = [
+            std::f32::consts::PI,
+            1.5 * std::f32::consts::PI,
+            0.0,
+            0.5 * std::f32::consts::PI,
+        ];
+
+        let mut points = Vec::new();
+
+        for i in 0..4 {
+            let radius = self.radii[i].max(0.0).min(max_radius);
+
+            if radius == 0.0 || self.num_segments_per_corner == 0 {
+                points.push(corners[i]);
+            } else {
+                points.extend(arc_points(
+                    corners[i] + inward[i] * radius,
+                    radius,
+                    start_angles[i],
+                    0.5 * std::f32::consts::PI,
+                    self.num_segments_per_corner,
+                ));
+            }
+        }
+
+        points
+    }
+}
+
+impl Geometry<TriangleTag> for ColorRoundedRect {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        write_fan(
+            self.rect.center,
+            &self.outline(),
+            true,
+            self.z,
+            self.color,
+            elements,
+            vertices,
+        );
+    }
+}
+
+impl Geometry<LineTag> for ColorRoundedRect {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        write_polyline(
+            &self.outline(),
+            true,
+            self.z,
+            self.color,
+            elements,
+            vertices,
+        );
+    }
+}
+
+impl ColorArc {
+    fn ring(&self) -> ColorRing {
+        ColorRing {
+            center: self.circle.center,
+            inner_radius: (self.circle.radius - self.thickness / 2.0).max(0.0),
+            outer_radius: self.circle.radius + self.thickness / 2.0,
+            z: self.z,
+            start_angle: self.start_angle,
+            angle_size: self.angle_size,
+            num_segments: self.num_segments,
+            color: self.color,
+        }
+    }
+}
+
+impl Geometry<TriangleTag> for ColorArc {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        Geometry::<TriangleTag>::write(&self.ring(), elements, vertices);
+    }
+}
+
+impl Geometry<LineTag> for ColorArc {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let full = is_full_circle(self.angle_size);
+        let mut points = arc_points(
+            self.circle.center,
+            self.circle.radius,
+            self.start_angle,
+            self.angle_size,
+            self.num_segments,
+        )
+        .collect::<Vec<_>>();
+
+        if full {
+            // The last point coincides with the first one.
+            points.pop();
+        }
+
+        write_polyline(&points, full, self.z, self.color, elements, vertices);
+    }
+}
+
+impl Geometry<TriangleTag> for ColorPieSlice {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let points = arc_points(
+            self.circle.center,
+            self.circle.radius,
+            self.start_angle,
+            self.angle_size,
+            self.num_segments,
+        )
+        .collect::<Vec<_>>();
+
+        write_fan(
+            self.circle.center,
+            &points,
+            false,
+            self.z,
+            self.color,
+            elements,
+            vertices,
+        );
+    }
+}
+
+impl Geometry<LineTag> for ColorPieSlice {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let arc = arc_points(
+            self.circle.center,
+            self.circle.radius,
+            self.start_angle,
+            self.angle_size,
+            self.num_segments,
+        );
+
+        if is_full_circle(self.angle_size) {
+            let mut points = arc.collect::<Vec<_>>();
+            points.pop();
+
+            write_polyline(&points, true, self.z, self.color, elements, vertices);
+        } else {
+            let points = std::iter::once(self.circle.center)
+                .chain(arc)
+                .collect::<Vec<_>>();
+
+            write_polyline(&points, true, self.z, self.color, elements, vertices);
+        }
+    }
+}
+
+impl ColorRing {
+    fn arcs(&self) -> (Vec<Point2<f32>>, Vec<Point2<f32>>) {
+        let arc = |radius| {
+            arc_points(
+                self.center,
+                radius,
+                self.start_angle,
+                self.angle_size,
+                self.num_segments,
+            )
+            .collect::<Vec<_>>()
+        };
+
+        (arc(self.inner_radius), arc(self.outer_radius))
+    }
+}
+
+impl Geometry<TriangleTag> for ColorRing {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let (inner, outer) = self.arcs();
+        let start_index = vertices.len() as u32;
+
+        for (p, q) in inner.iter().zip(outer.iter()) {
+            vertices.push(ColorVertex {
+                position: Point3::new(p.x, p.y, self.z),
+                color: self.color,
+            });
+            vertices.push(ColorVertex {
+                position: Point3::new(q.x, q.y, self.z),
+                color: self.color,
+            });
+        }
+
+        for i in 0..self.num_segments as u32 {
+            let a = start_index + 2 * i;
+
+            elements.extend_from_slice(&[a, a + 1, a + 3, a + 3, a + 2, a]);
+        }
+    }
+}
+
+impl Geometry<LineTag> for ColorRing {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let (mut inner, mut outer) = self.arcs();
+
+        if is_full_circle(self.angle_size) {
+            inner.pop();
+            outer.pop();
+
+            write_polyline(&inner, true, self.z, self.color, elements, vertices);
+            write_polyline(&outer, true, self.z, self.color, elements, vertices);
+        } else {
+            // Walk along the outer arc and then back along the inner arc, so
+            // that the closing segments form the caps.
+            let points = outer
+                .into_iter()
+                .chain(inner.into_iter().rev())
+                .collect::<Vec<_>>();
+
+            write_polyline(&points, true, self.z, self.color, elements, vertices);
+        }
+    }
+}
+
+fn is_full_circle(angle_size: f32) -> bool {
+    angle_size.abs() >= 2.0 * std::f32::consts::PI - 0.0001
+}
+
+/// Returns `num_segments + 1` points on the given arc, including both ends.
+fn arc_points(
+    center: Point2<f32>,
+    radius: f32,
+    start_angle: f32,
+    angle_size: f32,
+    num_segments: usize,
+) -> impl Iterator<Item = Point2<f32>> {
+    (0..=num_segments).map(move |i| {
+        let phi = start_angle + i as f32 / num_segments.max(1) as f32 * angle_size;
+
+        center + radius * Vector2::new(phi.cos(), phi.sin())
+    })
+}
+
+fn write_fan(
+    center: Point2<f32>,
+    points: &[Point2<f32>],
+    closed: bool,
+    z: f32,
+    color: Color4,
+    elements: &mut Vec<u32>,
+    vertices: &mut Vec<ColorVertex>,
+) {
+    if points.len() < 2 {
+        return;
+    }
+
+    let start_index = vertices.len() as u32;
+
+    vertices.push(ColorVertex {
+        position: Point3::new(center.x, center.y, z),
+        color,
+    });
+    for p in points {
+        vertices.push(ColorVertex {
+            position: Point3::new(p.x, p.y, z),
+            color,
+        });
+    }
+
+    let n = points.len() as u32;
+    let num_triangles = if closed { n } else { n - 1 };
+
+    for i in 0..num_triangles {
+        elements.extend_from_slice(&[
+            start_index,
+            start_index + 1 + i,
+            start_index + 1 + (i + 1) % n,
+        ]);
+    }
+}
+
+fn write_polyline(
+    points: &[Point2<f32>],
+    closed: bool,
+    z: f32,
+    color: Color4,
+    elements: &mut Vec<u32>,
+    vertices: &mut Vec<ColorVertex>,
+) {
+    if points.len() < 2 {
+        return;
+    }
+
+    let start_index = vertices.len() as u32;
+
+    for p in points {
+        vertices.push(ColorVertex {
+            position: Point3::new(p.x, p.y, z),
+            color,
+        });
+    }
+
+    let n = points.len() as u32;
+    let num_lines = if closed { n } else { n - 1 };
+
+    for i in 0..num_lines {
+        elements.extend_from_slice(&[start_index + i, start_index + (i + 1) % n]);
+    }
+}
+
+/// Clips a convex polygon to the half-plane where `f` is nonnegative, assuming
+/// that `f` is affine.
+fn clip_polygon<F>(polygon: &[Point2<f32>], f: F) -> Vec<Point2<f32>>
+where
+    F: Fn(Point2<f32>) -> f32,
+{
+    let mut output = Vec::new();
+
+    for (i, p) in polygon.iter().enumerate() {
+        let q = polygon[(i + 1) % polygon.len()];
+        let (fp, fq) = (f(*p), f(q));
+
+        if fp >= 0.0 {
+            output.push(*p);
+        }
+        if (fp >= 0.0) != (fq >= 0.0) {
+            let s = fp / (fp - fq);
+            output.push(p + (q - p) * s);
+        }
+    }
+
+    output
+}
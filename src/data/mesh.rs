@@ -17,6 +17,17 @@ impl<V> Mesh<V>
 where
     V: Vertex,
 {
+    pub fn from_geometry<P, G>(gl: Rc<gl::Context>, geometry: G) -> Result<Self, gl::Error>
+    where
+        P: PrimitiveTag,
+        G: Geometry<P, Vertex = V>,
+    {
+        let mut batch = GeometryBatch::<P, V>::new(gl)?;
+        batch.push(geometry);
+
+        Ok(batch.into_mesh())
+    }
+
     pub fn new(
         vertex_array: Rc<VertexArray<V>>,
         primitive_mode: PrimitiveMode,
@@ -32,17 +43,6 @@ where
         }
     }
 
-    pub fn from_geometry<P, G>(gl: Rc<gl::Context>, geometry: G) -> Result<Self, gl::Error>
-    where
-        P: PrimitiveTag,
-        G: Geometry<P, Vertex = V>,
-    {
-        let mut batch = GeometryBatch::<P, V>::new(gl)?;
-        batch.push(geometry);
-
-        Ok(batch.into_mesh())
-    }
-
     pub fn gl(&self) -> Rc<gl::Context> {
         self.vertex_array.gl()
     }
@@ -67,7 +67,7 @@ where
         self.element_range.clone()
     }
 
-    pub fn draw_unit(&self) -> DrawUnit<V, u32> {
+    pub fn draw_unit(&self) -> DrawUnit<V> {
         DrawUnit::new(
             &*self.vertex_array,
             self.primitive_mode,
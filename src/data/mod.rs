@@ -1,17 +1,32 @@
 mod batch;
 mod buffer;
+mod color_shapes;
 mod geometry;
 mod mesh;
+mod nine_slice;
+mod packed;
+mod pivot_sprite;
+mod tiling_background;
+mod transformed;
 mod vertex;
 
 pub use batch::{
-    ColorLineBatch, ColorTriangleBatch, GeometryBatch, InstanceBatch, LineBatch, SpriteBatch,
-    TriangleBatch,
+    ColorLineBatch, ColorTriangleBatch, GeometryBatch, InstanceBatch, LineBatch,
+    PackedColorLineBatch, PackedColorTriangleBatch, PackedSpriteBatch, SpriteBatch, TriangleBatch,
 };
 pub use buffer::GeometryBuffer;
+pub use color_shapes::{
+    ColorArc, ColorCornersRect, ColorPieSlice, ColorRing, ColorRoundedRect, Gradient,
+    LinearGradientRect, RadialGradientCircle,
+};
 pub use geometry::{
     quad_line_indices, quad_triangle_indices, ColorCircle, ColorLine, ColorRect, ColorRotatedRect,
     Geometry, LineTag, PrimitiveTag, RotatedSprite, Sprite, TriangleTag,
 };
 pub use mesh::Mesh;
-pub use vertex::{ColorVertex, SpriteVertex};
+pub use nine_slice::{NineSliceInsets, NineSliceMode, NineSliceSprite};
+pub use packed::{Packed, PackedSprites};
+pub use pivot_sprite::{PivotSprite, PixelSnap, SpriteFlip};
+pub use tiling_background::TilingBackground;
+pub use transformed::{HasPosition, Transformed};
+pub use vertex::{ColorVertex, PackedColorVertex, PackedSpriteVertex, SpriteVertex};
@@ -0,0 +1,218 @@
+use nalgebra::{Point2, Vector2};
+
+use crate::{geom::Rect, Color4};
+
+use super::{Geometry, Sprite, SpriteVertex, TriangleTag};
+
+/// How the edges and the center of a `NineSliceSprite` fill their area.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NineSliceMode {
+    Stretch,
+    Tile,
+}
+
+/// Border sizes of a `NineSliceSprite`, measured in texels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NineSliceInsets {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+/// A sprite that is split into a 3x3 grid, so that it can be scaled to any
+/// size without distorting its corners.
+///
+/// The corners are drawn at their texel size (multiplied by `scale`), the
+/// edges are extended in one direction, and the center in both directions.
+#[derive(Debug, Copy, Clone)]
+pub struct NineSliceSprite {
+    /// Destination rectangle.
+    pub rect: Rect,
+    pub depth: f32,
+
+    /// Source rectangle in texels, with the origin at the top-left of the
+    /// texture data and Y pointing down.
+    pub tex_rect: Rect,
+
+    /// Size of the texture that `tex_rect` refers to. This is needed to fix up
+    /// texture coordinates for the OpenGL Y flip, in the same way that
+    /// `text::Atlas` does.
+    pub texture_size: Vector2<f32>,
+
+    pub insets: NineSliceInsets,
+
+    /// Factor from texels to units of the destination rectangle for the
+    /// border sizes.
+    pub scale: f32,
+
+    pub edge_mode: NineSliceMode,
+    pub center_mode: NineSliceMode,
+    pub color: Color4,
+}
+
+impl NineSliceInsets {
+    pub fn uniform(inset: f32) -> Self {
+        Self {
+            left: inset,
+            right: inset,
+            top: inset,
+            bottom: inset,
+        }
+    }
+}
+
+impl NineSliceSprite {
+    fn flip_tex_rect(&self, tex_rect: Rect) -> Rect {
+        // Fix up for OpenGL Y flip (necessary since we draw with positive Y down.)
+        let top_left = tex_rect.top_left();
+
+        Rect::from_top_left(
+            Point2::new(top_left.x, self.texture_size.y - top_left.y),
+            Vector2::new(tex_rect.size.x, -tex_rect.size.y),
+        )
+    }
+
+    fn write_slice(
+        &self,
+        tile: (bool, bool),
+        rect: Rect,
+        tex_rect: Rect,
+        elements: &mut Vec<u32>,
+        vertices: &mut Vec<SpriteVertex>,
+    ) {
+        if rect.size.x <= 0.0 || rect.size.y <= 0.0 {
+            return;
+        }
+
+        // Along axes that are not tiled, a single tile covers the whole slice.
+        let tile_size = Vector2::new(
+            if tile.0 {
+                tex_rect.size.x * self.scale
+            } else {
+                rect.size.x
+            },
+            if tile.1 {
+                tex_rect.size.y * self.scale
+            } else {
+                rect.size.y
+            },
+        );
+        if tile_size.x <= 0.0 || tile_size.y <= 0.0 {
+            return;
+        }
+
+        let mut y = 0.0;
+        while y < rect.size.y {
+            let h = tile_size.y.min(rect.size.y - y);
+
+            let mut x = 0.0;
+            while x < rect.size.x {
+                let w = tile_size.x.min(rect.size.x - x);
+
+                // The last tile in each direction is cut off, so we only take
+                // the corresponding part of the texture.
+                let part = Rect::from_top_left(
+                    tex_rect.top_left(),
+                    Vector2::new(
+                        tex_rect.size.x * w / tile_size.x,
+                        tex_rect.size.y * h / tile_size.y,
+                    ),
+                );
+
+                Sprite {
+                    rect: Rect::from_top_left(
+                        rect.top_left() + Vector2::new(x, y),
+                        Vector2::new(w, h),
+                    ),
+                    depth: self.depth,
+                    tex_rect: self.flip_tex_rect(part),
+                    color: self.color,
+                }
+                .write(elements, vertices);
+
+                x += tile_size.x;
+            }
+
+            y += tile_size.y;
+        }
+    }
+}
+
+impl Geometry<TriangleTag> for NineSliceSprite {
+    type Vertex = SpriteVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let insets = self.insets;
+
+        // Column and row boundaries in the source texture.
+        let tex_xs = [
+            self.tex_rect.left_x(),
+            self.tex_rect.left_x() + insets.left,
+            self.tex_rect.right_x() - insets.right,
+            self.tex_rect.right_x(),
+        ];
+        let tex_ys = [
+            self.tex_rect.top_y(),
+            self.tex_rect.top_y() + insets.top,
+            self.tex_rect.bottom_y() - insets.bottom,
+            self.tex_rect.bottom_y(),
+        ];
+
+        // If the destination is smaller than the borders, shrink the borders
+        // proportionally so that they do not overlap.
+        let border = Vector2::new(
+            (insets.left + insets.right) * self.scale,
+            (insets.top + insets.bottom) * self.scale,
+        );
+        let shrink = Vector2::new(
+            if border.x > self.rect.size.x && border.x > 0.0 {
+                self.rect.size.x / border.x
+            } else {
+                1.0
+            },
+            if border.y > self.rect.size.y && border.y > 0.0 {
+                self.rect.size.y / border.y
+            } else {
+                1.0
+            },
+        );
+
+        let xs = [
+            self.rect.left_x(),
+            self.rect.left_x() + insets.left * self.scale * shrink.x,
+            self.rect.right_x() - insets.right * self.scale * shrink.x,
+            self.rect.right_x(),
+        ];
+        let ys = [
+            self.rect.top_y(),
+            self.rect.top_y() + insets.top * self.scale * shrink.y,
+            self.rect.bottom_y() - insets.bottom * self.scale * shrink.y,
+            self.rect.bottom_y(),
+        ];
+
+        for row in 0..3 {
+            for col in 0..3 {
+                let rect = Rect::from_top_left(
+                    Point2::new(xs[col], ys[row]),
+                    Vector2::new(xs[col + 1] - xs[col], ys[row + 1] - ys[row]),
+                );
+                let tex_rect = Rect::from_top_left(
+                    Point2::new(tex_xs[col], tex_ys[row]),
+                    Vector2::new(tex_xs[col + 1] - tex_xs[col], tex_ys[row + 1] - tex_ys[row]),
+                );
+
+                let edge_tile = self.edge_mode == NineSliceMode::Tile;
+                let center_tile = self.center_mode == NineSliceMode::Tile;
+                let tile = match (row, col) {
+                    (1, 1) => (center_tile, center_tile),
+                    (_, 1) => (edge_tile, false),
+                    (1, _) => (false, edge_tile),
+                    _ => (false, false),
+                };
+
+                self.write_slice(tile, rect, tex_rect, elements, vertices);
+            }
+        }
+    }
+}
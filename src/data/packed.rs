@@ -0,0 +1,81 @@
+use std::marker::PhantomData;
+
+use nalgebra::Vector2;
+
+use crate::gl::Vertex;
+
+use super::{Geometry, PackedSpriteVertex, PrimitiveTag, SpriteVertex};
+
+/// Converts the vertices written by a `Geometry` into a more compact vertex
+/// type, e.g. `PackedColorVertex`.
+#[derive(Debug, Clone)]
+pub struct Packed<G, V> {
+    pub geometry: G,
+    _phantom: PhantomData<V>,
+}
+
+impl<G, V> Packed<G, V> {
+    pub fn new(geometry: G) -> Self {
+        Self {
+            geometry,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<P, G, V> Geometry<P> for Packed<G, V>
+where
+    P: PrimitiveTag,
+    G: Geometry<P>,
+    V: Vertex + From<G::Vertex>,
+{
+    type Vertex = V;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        write_converted(&self.geometry, elements, vertices, V::from);
+    }
+}
+
+/// Converts the vertices written by a sprite `Geometry` into
+/// `PackedSpriteVertex`, normalizing the texture coordinates.
+#[derive(Debug, Clone)]
+pub struct PackedSprites<G> {
+    pub geometry: G,
+
+    /// Size of the texture that the sprites are drawn with.
+    pub texture_size: Vector2<u32>,
+}
+
+impl<P, G> Geometry<P> for PackedSprites<G>
+where
+    P: PrimitiveTag,
+    G: Geometry<P, Vertex = SpriteVertex>,
+{
+    type Vertex = PackedSpriteVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        write_converted(&self.geometry, elements, vertices, |vertex| {
+            PackedSpriteVertex::from_sprite_vertex(vertex, self.texture_size)
+        });
+    }
+}
+
+fn write_converted<P, G, V>(
+    geometry: &G,
+    elements: &mut Vec<u32>,
+    vertices: &mut Vec<V>,
+    convert: impl Fn(G::Vertex) -> V,
+) where
+    P: PrimitiveTag,
+    G: Geometry<P>,
+{
+    let mut unpacked_elements = Vec::new();
+    let mut unpacked_vertices = Vec::new();
+
+    geometry.write(&mut unpacked_elements, &mut unpacked_vertices);
+
+    let offset = vertices.len() as u32;
+
+    elements.extend(unpacked_elements.into_iter().map(|index| index + offset));
+    vertices.extend(unpacked_vertices.into_iter().map(convert));
+}
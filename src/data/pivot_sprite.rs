@@ -0,0 +1,155 @@
+use nalgebra::{Matrix3, Point2, Point3, Rotation2, Vector2};
+
+use crate::{
+    geom::{Camera, Rect, Screen},
+    Color4,
+};
+
+use super::{quad_triangle_indices, Geometry, SpriteVertex, TriangleTag};
+
+/// Mirroring of a sprite's texture.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SpriteFlip {
+    pub horizontal: bool,
+    pub vertical: bool,
+}
+
+/// Snaps positions to the physical pixels of the screen.
+///
+/// Snapping gives crisp results for pixel art as long as the camera is not
+/// rotated.
+#[derive(Debug, Copy, Clone)]
+pub struct PixelSnap {
+    world_to_pixel: Matrix3<f32>,
+    pixel_to_world: Matrix3<f32>,
+}
+
+/// A sprite that is placed by a pivot point, rotated around it, and optionally
+/// mirrored and snapped to physical pixels.
+#[derive(Debug, Copy, Clone)]
+pub struct PivotSprite {
+    /// World position of the pivot.
+    pub position: Point2<f32>,
+    pub size: Vector2<f32>,
+
+    /// Pivot relative to the sprite, where `(0, 0)` is the top-left and
+    /// `(1, 1)` the bottom-right corner.
+    pub pivot: Vector2<f32>,
+    pub angle: f32,
+    pub depth: f32,
+    pub tex_rect: Rect,
+    pub flip: SpriteFlip,
+    pub snap: Option<PixelSnap>,
+    pub color: Color4,
+}
+
+impl SpriteFlip {
+    pub fn horizontal() -> Self {
+        Self {
+            horizontal: true,
+            vertical: false,
+        }
+    }
+
+    pub fn vertical() -> Self {
+        Self {
+            horizontal: false,
+            vertical: true,
+        }
+    }
+
+    /// Mirrors a texture rectangle, so that it can also be used with `Sprite`
+    /// and `RotatedSprite`.
+    pub fn apply(self, mut tex_rect: Rect) -> Rect {
+        if self.horizontal {
+            tex_rect.size.x = -tex_rect.size.x;
+        }
+        if self.vertical {
+            tex_rect.size.y = -tex_rect.size.y;
+        }
+
+        tex_rect
+    }
+}
+
+impl PixelSnap {
+    pub fn new(camera: &Camera, screen: Screen) -> Self {
+        let dpr = screen.device_pixel_ratio as f32;
+        let world_to_pixel =
+            Matrix3::new_nonuniform_scaling(&Vector2::new(dpr, dpr)) * camera.matrix(screen);
+        let pixel_to_world = world_to_pixel
+            .try_inverse()
+            .unwrap_or_else(Matrix3::identity);
+
+        Self {
+            world_to_pixel,
+            pixel_to_world,
+        }
+    }
+
+    /// Snaps for drawing in screen coordinates, i.e. without a camera.
+    pub fn screen(screen: Screen) -> Self {
+        Self::new(
+            &Camera {
+                center: Point2::from(screen.logical_size / 2.0),
+                zoom: 1.0,
+                angle: 0.0,
+            },
+            screen,
+        )
+    }
+
+    /// Moves a world position to the closest physical pixel corner.
+    pub fn snap(&self, p: Point2<f32>) -> Point2<f32> {
+        let pixel = self.world_to_pixel.transform_point(&p);
+
+        self.pixel_to_world
+            .transform_point(&Point2::new(pixel.x.round(), pixel.y.round()))
+    }
+}
+
+impl PivotSprite {
+    pub fn corners(&self) -> [Point2<f32>; 4] {
+        let rotation = Rotation2::new(self.angle);
+        let offset = self.pivot.component_mul(&self.size);
+        let corner = |x: f32, y: f32| {
+            self.position + rotation * (Vector2::new(x * self.size.x, y * self.size.y) - offset)
+        };
+
+        let mut corners = [
+            corner(0.0, 0.0),
+            corner(1.0, 0.0),
+            corner(1.0, 1.0),
+            corner(0.0, 1.0),
+        ];
+
+        // Move all corners by the same amount, so that the size is kept.
+        if let Some(snap) = self.snap {
+            let delta = snap.snap(corners[0]) - corners[0];
+
+            for corner in corners.iter_mut() {
+                *corner += delta;
+            }
+        }
+
+        corners
+    }
+}
+
+impl Geometry<TriangleTag> for PivotSprite {
+    type Vertex = SpriteVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        elements.extend_from_slice(&quad_triangle_indices(vertices.len() as u32));
+
+        let tex_rect = self.flip.apply(self.tex_rect);
+
+        for (p, tex_coords) in self.corners().iter().zip(tex_rect.corners()) {
+            vertices.push(SpriteVertex {
+                position: Point3::new(p.x, p.y, self.depth),
+                tex_coords,
+                color: self.color,
+            });
+        }
+    }
+}
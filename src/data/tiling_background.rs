@@ -0,0 +1,85 @@
+use nalgebra::{Point2, Point3, Vector2};
+
+use crate::{
+    geom::{Camera, Screen},
+    Color4,
+};
+
+use super::{quad_triangle_indices, Geometry, SpriteVertex, TriangleTag};
+
+/// A texture that repeats infinitely across the world.
+///
+/// The geometry is a single quad that covers the camera's view, with texture
+/// coordinates outside of the texture. It needs to be drawn with a texture
+/// whose wrap mode is `TextureWrap::Repeat` or `TextureWrap::MirroredRepeat`.
+#[derive(Debug, Copy, Clone)]
+pub struct TilingBackground {
+    pub camera: Camera,
+    pub screen: Screen,
+    pub texture_size: Vector2<f32>,
+
+    /// Size of one repetition of the texture in world units.
+    pub tile_size: Vector2<f32>,
+
+    /// Scrolling speed relative to the camera. With a value of one, the
+    /// background moves along with the world, while with zero it stays fixed
+    /// on the screen.
+    pub scroll: Vector2<f32>,
+
+    /// Offset of the texture in world units.
+    pub offset: Vector2<f32>,
+    pub depth: f32,
+    pub color: Color4,
+}
+
+impl TilingBackground {
+    /// Returns the texture coordinates in texels, with the origin at the
+    /// top-left of the texture data and Y pointing down, for a world position.
+    pub fn texel(&self, p: Point2<f32>) -> Point2<f32> {
+        let fixed = self
+            .camera
+            .center
+            .coords
+            .component_mul(&(Vector2::new(1.0, 1.0) - self.scroll));
+        let p = p - fixed - self.offset;
+
+        Point2::new(
+            p.x / self.tile_size.x * self.texture_size.x,
+            p.y / self.tile_size.y * self.texture_size.y,
+        )
+    }
+}
+
+impl Geometry<TriangleTag> for TilingBackground {
+    type Vertex = SpriteVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        elements.extend_from_slice(&quad_triangle_indices(vertices.len() as u32));
+
+        let corners = self
+            .camera
+            .visible_world_rotated_rect(self.screen)
+            .corners();
+        let texels = corners.map(|p| self.texel(p));
+
+        // Move the texture coordinates close to the origin by whole
+        // repetitions, so that the shader does not lose precision far away
+        // from the world origin.
+        let shift = Vector2::new(
+            (texels[0].x / self.texture_size.x).floor() * self.texture_size.x,
+            (texels[0].y / self.texture_size.y).floor() * self.texture_size.y,
+        );
+
+        for (p, texel) in corners.iter().zip(texels) {
+            let texel = texel - shift;
+
+            vertices.push(SpriteVertex {
+                position: Point3::new(p.x, p.y, self.depth),
+                // Fix up for OpenGL Y flip (necessary since we draw with
+                // positive Y down.)
+                tex_coords: Point2::new(texel.x, self.texture_size.y - texel.y),
+                color: self.color,
+            });
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use nalgebra::{Isometry2, Matrix3, Point2};
+
+use super::{ColorVertex, Geometry, PrimitiveTag, SpriteVertex};
+
+/// Vertices whose positions can be transformed in the plane.
+pub trait HasPosition {
+    fn position(&self) -> Point2<f32>;
+
+    fn set_position(&mut self, position: Point2<f32>);
+
+    /// Applies `f` to every position stored in the vertex.
+    ///
+    /// Vertex types that store more than one position need to override this.
+    fn map_positions<F>(&mut self, f: F)
+    where
+        F: Fn(Point2<f32>) -> Point2<f32>,
+    {
+        self.set_position(f(self.position()));
+    }
+
+    /// Adds `offset` to the depth of the vertex. Does nothing for vertex types
+    /// without a depth.
+    fn offset_depth(&mut self, _offset: f32) {}
+}
+
+/// Applies a transform to the positions written by a `Geometry`.
+#[derive(Debug, Clone)]
+pub struct Transformed<G> {
+    pub geometry: G,
+
+    /// Transformation in homogeneous coordinates.
+    pub transform: Matrix3<f32>,
+}
+
+impl<G> Transformed<G> {
+    pub fn new(geometry: G, transform: Matrix3<f32>) -> Self {
+        Self {
+            geometry,
+            transform,
+        }
+    }
+
+    pub fn from_isometry(geometry: G, isometry: Isometry2<f32>) -> Self {
+        Self::new(geometry, isometry.to_homogeneous())
+    }
+}
+
+impl<P, G> Geometry<P> for Transformed<G>
+where
+    P: PrimitiveTag,
+    G: Geometry<P>,
+    G::Vertex: HasPosition,
+{
+    type Vertex = G::Vertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let start_index = vertices.len();
+
+        self.geometry.write(elements, vertices);
+
+        for vertex in vertices[start_index..].iter_mut() {
+            vertex.map_positions(|p| self.transform.transform_point(&p));
+        }
+    }
+}
+
+impl HasPosition for ColorVertex {
+    fn position(&self) -> Point2<f32> {
+        self.position.xy()
+    }
+
+    fn set_position(&mut self, position: Point2<f32>) {
+        self.position.x = position.x;
+        self.position.y = position.y;
+    }
+
+    fn offset_depth(&mut self, offset: f32) {
+        self.position.z += offset;
+    }
+}
+
+impl HasPosition for SpriteVertex {
+    fn position(&self) -> Point2<f32> {
+        self.position.xy()
+    }
+
+    fn set_position(&mut self, position: Point2<f32>) {
+        self.position.x = position.x;
+        self.position.y = position.y;
+    }
+
+    fn offset_depth(&mut self, offset: f32) {
+        self.position.z += offset;
+    }
+}
@@ -1,10 +1,10 @@
-use nalgebra::{Point2, Point3};
+use nalgebra::{Point2, Point3, Vector2};
 
 use bytemuck::{Pod, Zeroable};
 
 use crate::{
     attributes,
-    gl::{Attribute, Vertex},
+    gl::{Attribute, Unorm16x2, Unorm8x4, Vertex},
     Color4,
 };
 
@@ -23,6 +23,27 @@ pub struct ColorVertex {
     pub color: Color4,
 }
 
+/// A `SpriteVertex` with 16-bit texture coordinates and 8-bit colors.
+///
+/// Unlike in `SpriteVertex`, texture coordinates are normalized to the size of
+/// the texture, which keeps them precise for large textures. Both texture
+/// coordinates and colors are clamped to `[0, 1]`.
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+pub struct PackedSpriteVertex {
+    pub position: Point3<f32>,
+    pub tex_coords: Unorm16x2,
+    pub color: Unorm8x4,
+}
+
+/// A `ColorVertex` with 8-bit colors.
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+pub struct PackedColorVertex {
+    pub position: Point3<f32>,
+    pub color: Unorm8x4,
+}
+
 impl Vertex for SpriteVertex {
     fn attributes() -> Vec<Attribute> {
         attributes![position, tex_coords, color]
@@ -34,3 +55,39 @@ impl Vertex for ColorVertex {
         attributes![position, color]
     }
 }
+
+impl Vertex for PackedSpriteVertex {
+    fn attributes() -> Vec<Attribute> {
+        attributes![position, tex_coords, color]
+    }
+}
+
+impl Vertex for PackedColorVertex {
+    fn attributes() -> Vec<Attribute> {
+        attributes![position, color]
+    }
+}
+
+impl PackedSpriteVertex {
+    /// Packs a vertex whose texture coordinates are given in texels of a
+    /// texture of size `texture_size`.
+    pub fn from_sprite_vertex(vertex: SpriteVertex, texture_size: Vector2<u32>) -> Self {
+        Self {
+            position: vertex.position,
+            tex_coords: Unorm16x2::new([
+                vertex.tex_coords.x / texture_size.x as f32,
+                vertex.tex_coords.y / texture_size.y as f32,
+            ]),
+            color: vertex.color.into(),
+        }
+    }
+}
+
+impl From<ColorVertex> for PackedColorVertex {
+    fn from(vertex: ColorVertex) -> Self {
+        Self {
+            position: vertex.position,
+            color: vertex.color.into(),
+        }
+    }
+}
@@ -1,12 +1,19 @@
 use thiserror::Error;
 
+#[cfg(feature = "web")]
 use crate::{
     al::{self, LoadSoundError},
-    gl::{self, LoadTextureError, NewFramebufferError, NewTextureError},
+    gl::LoadTextureError,
     light::NewLightPipelineError,
-    text::{LoadFontError, WriteTextError},
+    text::LoadFontError,
+    tilemap::LoadTilemapError,
+};
+use crate::{
+    gl::{self, NewFramebufferError, NewTextureError},
+    text::WriteTextError,
 };
 
+#[cfg(feature = "web")]
 #[derive(Error, Debug)]
 pub enum InitError {
     #[error("no window")]
@@ -39,6 +46,9 @@ pub enum InitError {
     #[error("load font error: {0}")]
     LoadFont(#[from] LoadFontError),
 
+    #[error("load tilemap error: {0}")]
+    LoadTilemap(#[from] LoadTilemapError),
+
     #[error("load sound error: {0}")]
     LoadSound(#[from] LoadSoundError),
 
@@ -69,6 +79,7 @@ pub enum FrameError {
     #[error("new framebuffer error: {0}")]
     NewFramebuffer(#[from] NewFramebufferError),
 
+    #[cfg(feature = "web")]
     #[error("audio error: {0}")]
     Audio(#[from] al::PlayError),
 }
@@ -70,6 +70,18 @@ impl Camera {
             .unwrap_or_else(Matrix3::identity)
     }
 
+    /// Returns the camera of a layer that scrolls `factor` times as fast as
+    /// this camera.
+    ///
+    /// A factor of zero keeps the layer fixed on the screen, while a factor of
+    /// one moves it along with the world. Factors can differ per axis.
+    pub fn parallax(&self, factor: Vector2<f32>) -> Self {
+        Self {
+            center: self.center.coords.component_mul(&factor).into(),
+            ..*self
+        }
+    }
+
     pub fn visible_world_rotated_rect(&self, screen: Screen) -> RotatedRect {
         // TODO: Double check that this handles rotation correctly
         RotatedRect {
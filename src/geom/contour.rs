@@ -0,0 +1,308 @@
+use std::collections::{BTreeMap, HashSet};
+
+use nalgebra::{Point2, Vector2};
+use thiserror::Error;
+
+use super::Line;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ContourError {
+    #[error("expected RGBA data for an image of size {0}x{1}, but got {2} bytes")]
+    InvalidDataLength(u32, u32, usize),
+}
+
+/// Identifies a point on the edge between two neighbouring samples: either
+/// between `(x, y)` and `(x + 1, y)`, or between `(x, y)` and `(x, y + 1)`.
+///
+/// Points are ordered row by row, which makes tracing deterministic.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct EdgePoint {
+    y: i64,
+    x: i64,
+    vertical: bool,
+}
+
+/// Traces the outlines of the regions in an RGBA image whose alpha is larger
+/// than `threshold`, using marching squares.
+///
+/// The image is given row by row, starting at the top. Returns closed
+/// contours in pixel coordinates, with the origin at the top-left corner of
+/// the image and Y pointing down. Pixels outside of the image are considered
+/// transparent, so all contours are closed.
+///
+/// Contours are returned in the order of their top-most, left-most point,
+/// and each one starts at that point.
+pub fn alpha_contours(
+    data: &[u8],
+    size: Vector2<u32>,
+    threshold: u8,
+) -> Result<Vec<Vec<Point2<f32>>>, ContourError> {
+    let expected_len = (size.x as usize)
+        .checked_mul(size.y as usize)
+        .and_then(|len| len.checked_mul(4));
+    if expected_len != Some(data.len()) {
+        return Err(ContourError::InvalidDataLength(size.x, size.y, data.len()));
+    }
+
+    let (w, h) = (size.x as i64, size.y as i64);
+    let alpha = |x: i64, y: i64| {
+        if x >= 0 && y >= 0 && x < w && y < h {
+            data[((y * w + x) * 4 + 3) as usize] as f32
+        } else {
+            0.0
+        }
+    };
+    let threshold_f = threshold as f32;
+    let inside = |x: i64, y: i64| alpha(x, y) > threshold_f;
+
+    // Samples are located at pixel centers. Interpolate the crossing along the
+    // edge between two samples.
+    let position = |p: EdgePoint| {
+        let (x1, y1) = if p.vertical {
+            (p.x, p.y + 1)
+        } else {
+            (p.x + 1, p.y)
+        };
+        let (a0, a1) = (alpha(p.x, p.y), alpha(x1, y1));
+        let t = if a0 != a1 {
+            ((threshold_f - a0) / (a1 - a0)).clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+
+        Point2::new(
+            p.x as f32 + 0.5 + t * (x1 - p.x) as f32,
+            p.y as f32 + 0.5 + t * (y1 - p.y) as f32,
+        )
+    };
+
+    let mut neighbours: BTreeMap<EdgePoint, Vec<EdgePoint>> = BTreeMap::new();
+    let mut connect = |a: EdgePoint, b: EdgePoint| {
+        neighbours.entry(a).or_default().push(b);
+        neighbours.entry(b).or_default().push(a);
+    };
+
+    // Each cell lies between four samples, starting with the top-left sample
+    // at `(x, y)`. The padding of one sample closes contours at the border.
+    for y in -1..h {
+        for x in -1..w {
+            let corners = [
+                inside(x, y),
+                inside(x + 1, y),
+                inside(x + 1, y + 1),
+                inside(x, y + 1),
+            ];
+            let edges = [
+                EdgePoint {
+                    x,
+                    y,
+                    vertical: false,
+                },
+                EdgePoint {
+                    x: x + 1,
+                    y,
+                    vertical: true,
+                },
+                EdgePoint {
+                    x,
+                    y: y + 1,
+                    vertical: false,
+                },
+                EdgePoint {
+                    x,
+                    y,
+                    vertical: true,
+                },
+            ];
+
+            // Edge `i` goes from corner `i` to corner `i + 1`.
+            let crossings = (0..4)
+                .filter(|i| corners[*i] != corners[(i + 1) % 4])
+                .collect::<Vec<_>>();
+
+            match crossings.len() {
+                2 => connect(edges[crossings[0]], edges[crossings[1]]),
+                4 => {
+                    // Saddle: decide by the average of the four samples whether
+                    // the diagonal inside corners are connected. If they are, cut
+                    // off the outside corners, and vice versa.
+                    let center =
+                        (alpha(x, y) + alpha(x + 1, y) + alpha(x + 1, y + 1) + alpha(x, y + 1))
+                            / 4.0;
+                    let cut_inside = center <= threshold_f;
+
+                    // Corner `i` is adjacent to the edges `i - 1` and `i`.
+                    for i in 0..4 {
+                        if corners[i] == cut_inside {
+                            connect(edges[(i + 3) % 4], edges[i]);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // Every edge point has exactly two neighbours, so the segments form closed
+    // loops.
+    let mut contours = Vec::new();
+    let mut visited = HashSet::new();
+
+    for start in neighbours.keys() {
+        if visited.contains(start) {
+            continue;
+        }
+
+        let mut contour = Vec::new();
+        let mut prev = *start;
+        let mut current = *start;
+
+        loop {
+            visited.insert(current);
+            contour.push(position(current));
+
+            let next = neighbours[&current]
+                .iter()
+                .copied()
+                .find(|p| *p != prev && !visited.contains(p));
+
+            match next {
+                Some(next) => {
+                    prev = current;
+                    current = next;
+                }
+                None => break,
+            }
+        }
+
+        contours.push(contour);
+    }
+
+    Ok(contours)
+}
+
+/// Simplifies a polyline with the Douglas-Peucker algorithm, so that no
+/// removed point is farther than `tolerance` from the result.
+pub fn simplify_polyline(points: &[Point2<f32>], tolerance: f32) -> Vec<Point2<f32>> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+
+    while let Some((first, last)) = stack.pop() {
+        let line = Line(points[first], points[last]);
+        let farthest = (first + 1..last)
+            .map(|i| (i, point_line_distance(points[i], line)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((i, distance)) = farthest {
+            if distance > tolerance {
+                keep[i] = true;
+                stack.push((first, i));
+                stack.push((i, last));
+            }
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(p, _)| *p)
+        .collect()
+}
+
+/// Simplifies a closed polygon with the Douglas-Peucker algorithm.
+pub fn simplify_polygon(points: &[Point2<f32>], tolerance: f32) -> Vec<Point2<f32>> {
+    if points.len() < 4 {
+        return points.to_vec();
+    }
+
+    // Split the polygon at the point that is farthest from the first point,
+    // and simplify both halves as polylines.
+    let split = (1..points.len())
+        .max_by(|a, b| {
+            let da = (points[*a] - points[0]).norm_squared();
+            let db = (points[*b] - points[0]).norm_squared();
+            da.total_cmp(&db)
+        })
+        .unwrap();
+
+    let mut second_half = points[split..].to_vec();
+    second_half.push(points[0]);
+
+    let mut result = simplify_polyline(&points[..=split], tolerance);
+    result.pop();
+    result.extend(simplify_polyline(&second_half, tolerance));
+    result.pop();
+
+    result
+}
+
+fn point_line_distance(p: Point2<f32>, line: Line) -> f32 {
+    let delta = line.delta();
+    let norm = delta.norm();
+
+    if norm == 0.0 {
+        (p - line.0).norm()
+    } else {
+        (delta.x * (line.0.y - p.y) - delta.y * (line.0.x - p.x)).abs() / norm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Vector2};
+
+    use super::{alpha_contours, ContourError};
+
+    fn image(size: Vector2<u32>, opaque: &[(u32, u32)]) -> Vec<u8> {
+        let mut data = vec![0; (size.x * size.y * 4) as usize];
+        for (x, y) in opaque {
+            data[((y * size.x + x) * 4 + 3) as usize] = 254;
+        }
+        data
+    }
+
+    #[test]
+    fn single_pixel() {
+        let size = Vector2::new(3, 3);
+        let contours = alpha_contours(&image(size, &[(1, 1)]), size, 127).unwrap();
+
+        // The crossings lie halfway between the pixel centers.
+        assert_eq!(
+            contours,
+            vec![vec![
+                Point2::new(1.5, 1.0),
+                Point2::new(1.0, 1.5),
+                Point2::new(1.5, 2.0),
+                Point2::new(2.0, 1.5),
+            ]]
+        );
+    }
+
+    #[test]
+    fn contours_are_ordered() {
+        let size = Vector2::new(6, 4);
+        let data = image(size, &[(4, 2), (1, 0), (1, 1)]);
+        let contours = alpha_contours(&data, size, 127).unwrap();
+
+        assert_eq!(contours.len(), 2);
+        assert_eq!(contours[0][0], Point2::new(1.5, 0.0));
+        assert_eq!(contours[1][0], Point2::new(4.5, 2.0));
+        assert_eq!(contours, alpha_contours(&data, size, 127).unwrap());
+    }
+
+    #[test]
+    fn invalid_data_length() {
+        assert_eq!(
+            alpha_contours(&[0; 12], Vector2::new(2, 2), 127),
+            Err(ContourError::InvalidDataLength(2, 2, 12)),
+        );
+    }
+}
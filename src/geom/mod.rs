@@ -1,18 +1,23 @@
 mod camera;
 mod circle;
+mod contour;
 mod grid;
 mod line;
+mod outline;
 mod overlap;
 mod rect;
 mod rotated_rect;
 mod screen;
 mod shape;
 mod transforms;
+mod viewport;
 
 pub use camera::Camera;
 pub use circle::Circle;
+pub use contour::{alpha_contours, simplify_polygon, simplify_polyline, ContourError};
 pub use grid::Grid;
 pub use line::Line;
+pub use outline::{grid_outline, rects_outline};
 pub use overlap::{
     circle_circle_overlap, rect_circle_overlap, rect_rect_overlap, rotated_rect_circle_overlap,
     rotated_rect_rotated_rect_overlap, shape_shape_overlap, Overlap,
@@ -25,3 +30,4 @@ pub use transforms::{
     matrix3_to_array, scale_rotate_translate, scale_translate, scale_translate3,
     translate_rotate_scale,
 };
+pub use viewport::Viewport;
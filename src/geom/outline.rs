@@ -0,0 +1,247 @@
+use nalgebra::{Point2, Vector2};
+
+use super::{Line, Rect};
+
+/// Returns the boundary of the union of axis-aligned rectangles.
+///
+/// Edges that are shared between overlapping or touching rectangles are
+/// dropped, and collinear edges are merged, so that each straight piece of the
+/// boundary is a single line. Rectangles with non-finite coordinates are
+/// ignored.
+pub fn rects_outline(rects: &[Rect]) -> Vec<Line> {
+    let rects = rects
+        .iter()
+        .filter(|r| {
+            [r.left_x(), r.right_x(), r.top_y(), r.bottom_y()]
+                .iter()
+                .all(|c| c.is_finite())
+        })
+        .collect::<Vec<_>>();
+
+    // Compress the coordinates of all rectangle edges into a non-uniform grid,
+    // in which each cell is either completely inside or outside of the union.
+    let xs = sorted_coords(rects.iter().flat_map(|r| [r.left_x(), r.right_x()]));
+    let ys = sorted_coords(rects.iter().flat_map(|r| [r.top_y(), r.bottom_y()]));
+
+    if xs.len() < 2 || ys.len() < 2 {
+        return Vec::new();
+    }
+
+    let num_cells = Vector2::new(xs.len() - 1, ys.len() - 1);
+    let mut occupied = vec![false; num_cells.x * num_cells.y];
+
+    for rect in rects {
+        let range_x = coord_index(&xs, rect.left_x())..coord_index(&xs, rect.right_x());
+        let range_y = coord_index(&ys, rect.top_y())..coord_index(&ys, rect.bottom_y());
+
+        for y in range_y {
+            for x in range_x.clone() {
+                occupied[y * num_cells.x + x] = true;
+            }
+        }
+    }
+
+    cells_outline(&xs, &ys, |x, y| occupied[y * num_cells.x + x])
+}
+
+/// Returns the boundary of the occupied cells of a grid that covers
+/// `grid_rect` with `num_cells` cells.
+///
+/// As with `rects_outline`, shared edges are dropped and collinear edges are
+/// merged.
+pub fn grid_outline<F>(grid_rect: Rect, num_cells: Vector2<u32>, occupied: F) -> Vec<Line>
+where
+    F: Fn(Point2<u32>) -> bool,
+{
+    let top_left = grid_rect.top_left();
+    let cell_size = grid_rect.size.component_div(&Vector2::new(
+        num_cells.x.max(1) as f32,
+        num_cells.y.max(1) as f32,
+    ));
+
+    let xs = (0..=num_cells.x)
+        .map(|i| top_left.x + i as f32 * cell_size.x)
+        .collect::<Vec<_>>();
+    let ys = (0..=num_cells.y)
+        .map(|i| top_left.y + i as f32 * cell_size.y)
+        .collect::<Vec<_>>();
+
+    cells_outline(&xs, &ys, |x, y| occupied(Point2::new(x as u32, y as u32)))
+}
+
+fn sorted_coords(coords: impl Iterator<Item = f32>) -> Vec<f32> {
+    let mut coords = coords.collect::<Vec<_>>();
+    coords.sort_by(f32::total_cmp);
+    coords.dedup();
+    coords
+}
+
+fn coord_index(coords: &[f32], value: f32) -> usize {
+    coords.binary_search_by(|c| c.total_cmp(&value)).unwrap()
+}
+
+/// Traces the outline of a grid whose cell `(x, y)` spans from `xs[x]` to
+/// `xs[x + 1]` and from `ys[y]` to `ys[y + 1]`.
+fn cells_outline<F>(xs: &[f32], ys: &[f32], occupied: F) -> Vec<Line>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let num_cells = Vector2::new(xs.len() - 1, ys.len() - 1);
+    let is_occupied = |x: usize, y: usize, dx: bool, dy: bool| {
+        // Looks at cell (x - dx, y - dy), treating cells outside as empty.
+        if (dx && x == 0) || (dy && y == 0) {
+            return false;
+        }
+
+        let (x, y) = (x - dx as usize, y - dy as usize);
+        x < num_cells.x && y < num_cells.y && occupied(x, y)
+    };
+
+    let mut lines = Vec::new();
+
+    // Horizontal edges lie between the rows `y - 1` and `y`. Consecutive edges
+    // along the same grid line are merged into runs.
+    for (y, &line_y) in ys.iter().enumerate() {
+        let mut x = 0;
+
+        while x < num_cells.x {
+            if is_occupied(x, y, false, true) == is_occupied(x, y, false, false) {
+                x += 1;
+                continue;
+            }
+
+            let start = x;
+            while x < num_cells.x
+                && is_occupied(x, y, false, true) != is_occupied(x, y, false, false)
+            {
+                x += 1;
+            }
+
+            lines.push(Line(
+                Point2::new(xs[start], line_y),
+                Point2::new(xs[x], line_y),
+            ));
+        }
+    }
+
+    // Vertical edges lie between the columns `x - 1` and `x`.
+    for (x, &line_x) in xs.iter().enumerate() {
+        let mut y = 0;
+
+        while y < num_cells.y {
+            if is_occupied(x, y, true, false) == is_occupied(x, y, false, false) {
+                y += 1;
+                continue;
+            }
+
+            let start = y;
+            while y < num_cells.y
+                && is_occupied(x, y, true, false) != is_occupied(x, y, false, false)
+            {
+                y += 1;
+            }
+
+            lines.push(Line(
+                Point2::new(line_x, ys[start]),
+                Point2::new(line_x, ys[y]),
+            ));
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Vector2};
+
+    use crate::geom::{Line, Rect};
+
+    use super::{grid_outline, rects_outline};
+
+    fn sorted(lines: Vec<Line>) -> Vec<[f32; 4]> {
+        let mut lines = lines
+            .into_iter()
+            .map(|Line(a, b)| [a.x, a.y, b.x, b.y])
+            .collect::<Vec<_>>();
+        lines.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        lines
+    }
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
+        Rect::from_top_left(Point2::new(x, y), Vector2::new(w, h))
+    }
+
+    #[test]
+    fn touching_rects_are_merged() {
+        let lines = rects_outline(&[rect(0.0, 0.0, 1.0, 1.0), rect(1.0, 0.0, 2.0, 1.0)]);
+
+        assert_eq!(
+            sorted(lines),
+            vec![
+                [0.0, 0.0, 0.0, 1.0],
+                [0.0, 0.0, 3.0, 0.0],
+                [0.0, 1.0, 3.0, 1.0],
+                [3.0, 0.0, 3.0, 1.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_rects() {
+        // An L shape made from two overlapping rectangles.
+        let lines = rects_outline(&[rect(0.0, 0.0, 2.0, 1.0), rect(0.0, 0.0, 1.0, 2.0)]);
+
+        assert_eq!(
+            sorted(lines),
+            vec![
+                [0.0, 0.0, 0.0, 2.0],
+                [0.0, 0.0, 2.0, 0.0],
+                [0.0, 2.0, 1.0, 2.0],
+                [1.0, 1.0, 1.0, 2.0],
+                [1.0, 1.0, 2.0, 1.0],
+                [2.0, 0.0, 2.0, 1.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn non_finite_rects_are_ignored() {
+        let lines = rects_outline(&[
+            rect(f32::NAN, 0.0, 1.0, 1.0),
+            rect(0.0, 0.0, f32::INFINITY, 1.0),
+            rect(0.0, 0.0, 1.0, 1.0),
+        ]);
+
+        assert_eq!(sorted(lines).len(), 4);
+        assert!(rects_outline(&[rect(0.0, f32::NAN, 1.0, 1.0)]).is_empty());
+    }
+
+    #[test]
+    fn grid_with_hole() {
+        // A 3x3 ring of cells, each of size 2.
+        let lines = grid_outline(rect(0.0, 0.0, 6.0, 6.0), Vector2::new(3, 3), |p| {
+            p != Point2::new(1, 1)
+        });
+
+        assert_eq!(
+            sorted(lines),
+            vec![
+                [0.0, 0.0, 0.0, 6.0],
+                [0.0, 0.0, 6.0, 0.0],
+                [0.0, 6.0, 6.0, 6.0],
+                [2.0, 2.0, 2.0, 4.0],
+                [2.0, 2.0, 4.0, 2.0],
+                [2.0, 4.0, 4.0, 4.0],
+                [4.0, 2.0, 4.0, 4.0],
+                [6.0, 0.0, 6.0, 6.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_grid() {
+        assert!(grid_outline(rect(0.0, 0.0, 1.0, 1.0), Vector2::new(0, 0), |_| true).is_empty());
+        assert!(grid_outline(rect(0.0, 0.0, 1.0, 1.0), Vector2::new(2, 2), |_| false).is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use nalgebra::{Point2, Vector2};
+
+use super::{Rect, Screen};
+
+/// A part of the screen that is rendered with its own camera, e.g. for
+/// split-screen multiplayer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Rectangle in logical screen coordinates, with `(0, 0)` at the top-left.
+    pub rect: Rect,
+}
+
+impl Viewport {
+    pub fn full(screen: Screen) -> Self {
+        Self {
+            rect: screen.logical_rect(),
+        }
+    }
+
+    /// Splits the screen into `n` viewports of equal size.
+    ///
+    /// Two viewports are placed side by side, three or four in a 2x2 grid. In
+    /// general, the grid has as many columns as needed to stay square-ish.
+    /// Viewports are ordered row by row, starting at the top-left.
+    pub fn split(screen: Screen, n: usize) -> Vec<Self> {
+        assert!(n > 0, "need at least one viewport");
+
+        let columns = (n as f32).sqrt().ceil() as usize;
+        let rows = n.div_ceil(columns);
+        let size = Vector2::new(
+            screen.logical_size.x / columns as f32,
+            screen.logical_size.y / rows as f32,
+        );
+
+        (0..n)
+            .map(|i| {
+                let top_left =
+                    Point2::new((i % columns) as f32 * size.x, (i / columns) as f32 * size.y);
+
+                Self {
+                    rect: Rect::from_top_left(top_left, size),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a screen that describes only this viewport. It can be used
+    /// like the screen of the whole canvas, e.g. for `Camera::matrix`.
+    pub fn screen(&self, screen: Screen) -> Screen {
+        Screen {
+            logical_size: self.rect.size,
+            physical_size: self.physical_size(screen),
+            device_pixel_ratio: screen.device_pixel_ratio,
+        }
+    }
+
+    /// Returns `[x, y, width, height]` in physical pixels, with the origin at
+    /// the lower-left as expected by OpenGL.
+    ///
+    /// Position and size are rounded separately, so that viewports of equal
+    /// logical size also have equal physical size.
+    pub fn gl_rect(&self, screen: Screen) -> [i32; 4] {
+        let scale = physical_scale(screen);
+        let size = self.physical_size(screen);
+        let left = (self.rect.left_x() * scale.x).round() as i32;
+        let bottom = screen.physical_size.y as i32
+            - (self.rect.top_y() * scale.y).round() as i32
+            - size.y as i32;
+
+        [left, bottom, size.x as i32, size.y as i32]
+    }
+
+    pub fn contains_point(&self, logical_p: Point2<f32>) -> bool {
+        self.rect.contains_point(logical_p)
+    }
+
+    /// Maps a logical position on the canvas, e.g. of the mouse, to a logical
+    /// position in the viewport.
+    pub fn canvas_to_viewport(&self, logical_p: Point2<f32>) -> Point2<f32> {
+        logical_p - self.rect.top_left().coords
+    }
+
+    fn physical_size(&self, screen: Screen) -> Vector2<u32> {
+        let size = self.rect.size.component_mul(&physical_scale(screen));
+
+        Vector2::new(
+            size.x.round().max(0.0) as u32,
+            size.y.round().max(0.0) as u32,
+        )
+    }
+}
+
+fn physical_scale(screen: Screen) -> Vector2<f32> {
+    Vector2::new(
+        screen.physical_size.x as f32 / screen.logical_size.x,
+        screen.physical_size.y as f32 / screen.logical_size.y,
+    )
+}
@@ -0,0 +1,243 @@
+use std::{any::Any, collections::HashSet, fmt::Debug};
+
+use glow::{PixelPackData, PixelUnpackData};
+use serde::{Serialize, Serializer};
+
+/// Handle of an object that has been created by a backend.
+///
+/// Handles are only valid for the backend that created them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(super) enum Handle<T> {
+    Glow(T),
+    Mock(u32),
+}
+
+macro_rules! handle {
+    ($name:ident, $glow:ty) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+        pub struct $name(pub(super) Handle<$glow>);
+    };
+}
+
+handle!(BufferId, glow::Buffer);
+handle!(FramebufferId, glow::Framebuffer);
+handle!(ProgramId, glow::Program);
+handle!(QueryId, glow::Query);
+handle!(ShaderId, glow::Shader);
+handle!(TextureId, glow::Texture);
+handle!(VertexArrayId, glow::VertexArray);
+
+#[derive(Clone, Debug)]
+pub struct UniformLocation(pub(super) Handle<glow::UniformLocation>);
+
+/// The subset of OpenGL that is used by `malen`.
+///
+/// Methods have the same names and signatures as in `glow::HasContext`, except
+/// that objects are referred to by handles that do not depend on the backend.
+/// This allows rendering code to run against `MockBackend` in native tests.
+///
+/// As in `glow`, the methods are unsafe since they call into OpenGL directly.
+#[allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
+pub trait Backend: Any {
+    fn as_any(&self) -> &dyn Any;
+
+    fn supported_extensions(&self) -> &HashSet<String>;
+
+    unsafe fn create_buffer(&self) -> Result<BufferId, String>;
+    unsafe fn create_framebuffer(&self) -> Result<FramebufferId, String>;
+    unsafe fn create_program(&self) -> Result<ProgramId, String>;
+    unsafe fn create_query(&self) -> Result<QueryId, String>;
+    unsafe fn create_shader(&self, shader_type: u32) -> Result<ShaderId, String>;
+    unsafe fn create_texture(&self) -> Result<TextureId, String>;
+    unsafe fn create_vertex_array(&self) -> Result<VertexArrayId, String>;
+
+    unsafe fn delete_buffer(&self, buffer: BufferId);
+    unsafe fn delete_framebuffer(&self, framebuffer: FramebufferId);
+    unsafe fn delete_program(&self, program: ProgramId);
+    unsafe fn delete_query(&self, query: QueryId);
+    unsafe fn delete_shader(&self, shader: ShaderId);
+    unsafe fn delete_texture(&self, texture: TextureId);
+    unsafe fn delete_vertex_array(&self, vertex_array: VertexArrayId);
+
+    unsafe fn active_texture(&self, unit: u32);
+    unsafe fn attach_shader(&self, program: ProgramId, shader: ShaderId);
+    unsafe fn detach_shader(&self, program: ProgramId, shader: ShaderId);
+    unsafe fn begin_query(&self, target: u32, query: QueryId);
+    unsafe fn end_query(&self, target: u32);
+    unsafe fn bind_attrib_location(&self, program: ProgramId, index: u32, name: &str);
+    unsafe fn bind_buffer(&self, target: u32, buffer: Option<BufferId>);
+    unsafe fn bind_buffer_base(&self, target: u32, index: u32, buffer: Option<BufferId>);
+    unsafe fn bind_framebuffer(&self, target: u32, framebuffer: Option<FramebufferId>);
+    unsafe fn bind_texture(&self, target: u32, texture: Option<TextureId>);
+    unsafe fn bind_vertex_array(&self, vertex_array: Option<VertexArrayId>);
+    unsafe fn blend_color(&self, red: f32, green: f32, blue: f32, alpha: f32);
+    unsafe fn blend_equation(&self, mode: u32);
+    unsafe fn blend_equation_separate(&self, mode_rgb: u32, mode_alpha: u32);
+    unsafe fn blend_func(&self, src: u32, dst: u32);
+    unsafe fn blend_func_separate(
+        &self,
+        src_rgb: u32,
+        dst_rgb: u32,
+        src_alpha: u32,
+        dst_alpha: u32,
+    );
+    unsafe fn buffer_data_u8_slice(&self, target: u32, data: &[u8], usage: u32);
+    unsafe fn clear(&self, mask: u32);
+    unsafe fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32);
+    unsafe fn clear_depth_f32(&self, depth: f32);
+    unsafe fn color_mask(&self, red: bool, green: bool, blue: bool, alpha: bool);
+    unsafe fn compile_shader(&self, shader: ShaderId);
+    unsafe fn depth_func(&self, func: u32);
+    unsafe fn depth_mask(&self, value: bool);
+    unsafe fn depth_range_f32(&self, near: f32, far: f32);
+    unsafe fn disable(&self, parameter: u32);
+    unsafe fn enable(&self, parameter: u32);
+    unsafe fn draw_buffers(&self, buffers: &[u32]);
+    unsafe fn draw_elements(&self, mode: u32, count: i32, element_type: u32, offset: i32);
+    unsafe fn draw_elements_instanced(
+        &self,
+        mode: u32,
+        count: i32,
+        element_type: u32,
+        offset: i32,
+        instance_count: i32,
+    );
+    unsafe fn enable_vertex_attrib_array(&self, index: u32);
+    unsafe fn framebuffer_texture_2d(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture_target: u32,
+        texture: Option<TextureId>,
+        level: i32,
+    );
+    unsafe fn generate_mipmap(&self, target: u32);
+    unsafe fn get_parameter_i32(&self, parameter: u32) -> i32;
+    unsafe fn get_parameter_i32_slice(&self, parameter: u32, out: &mut [i32]);
+    unsafe fn get_program_info_log(&self, program: ProgramId) -> String;
+    unsafe fn get_program_link_status(&self, program: ProgramId) -> bool;
+    unsafe fn get_query_parameter_u32(&self, query: QueryId, parameter: u32) -> u32;
+    unsafe fn get_shader_compile_status(&self, shader: ShaderId) -> bool;
+    unsafe fn get_shader_info_log(&self, shader: ShaderId) -> String;
+    unsafe fn get_uniform_block_index(&self, program: ProgramId, name: &str) -> Option<u32>;
+    unsafe fn get_uniform_location(
+        &self,
+        program: ProgramId,
+        name: &str,
+    ) -> Option<UniformLocation>;
+    unsafe fn invalidate_framebuffer(&self, target: u32, attachments: &[u32]);
+    unsafe fn line_width(&self, width: f32);
+    unsafe fn link_program(&self, program: ProgramId);
+    unsafe fn pixel_store_i32(&self, parameter: u32, value: i32);
+    unsafe fn read_buffer(&self, src: u32);
+    unsafe fn read_pixels(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        gltype: u32,
+        pixels: PixelPackData,
+    );
+    unsafe fn scissor(&self, x: i32, y: i32, width: i32, height: i32);
+    unsafe fn shader_source(&self, shader: ShaderId, source: &str);
+    unsafe fn tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        ty: u32,
+        pixels: Option<&[u8]>,
+    );
+    #[cfg(feature = "web")]
+    unsafe fn tex_image_2d_with_image_bitmap(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        format: u32,
+        ty: u32,
+        pixels: &web_sys::ImageBitmap,
+    );
+    unsafe fn tex_parameter_i32(&self, target: u32, parameter: u32, value: i32);
+    unsafe fn tex_storage_2d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    );
+    unsafe fn tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        ty: u32,
+        pixels: PixelUnpackData,
+    );
+    unsafe fn uniform_1_i32(&self, location: Option<&UniformLocation>, x: i32);
+    unsafe fn uniform_block_binding(&self, program: ProgramId, index: u32, binding: u32);
+    unsafe fn use_program(&self, program: Option<ProgramId>);
+    unsafe fn vertex_attrib_divisor(&self, index: u32, divisor: u32);
+    unsafe fn vertex_attrib_pointer_f32(
+        &self,
+        index: u32,
+        size: i32,
+        data_type: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i32,
+    );
+    unsafe fn vertex_attrib_pointer_i32(
+        &self,
+        index: u32,
+        size: i32,
+        data_type: u32,
+        stride: i32,
+        offset: i32,
+    );
+    unsafe fn viewport(&self, x: i32, y: i32, width: i32, height: i32);
+}
+
+impl<T> Handle<T> {
+    /// Returns the `glow` object behind the handle.
+    ///
+    /// Panics if the handle has been created by a different backend.
+    pub(super) fn glow(self) -> T {
+        match self {
+            Handle::Glow(object) => object,
+            Handle::Mock(_) => panic!("Handle was not created by the glow backend"),
+        }
+    }
+
+    /// Returns the id of a handle that has been created by `MockBackend`.
+    ///
+    /// Panics if the handle has been created by a different backend.
+    pub(super) fn mock(&self) -> u32 {
+        match self {
+            Handle::Glow(_) => panic!("Handle was not created by the mock backend"),
+            Handle::Mock(id) => *id,
+        }
+    }
+}
+
+/// Mock handles are serialized as their id, which is deterministic. `glow`
+/// objects are opaque, so only their debug representation can be serialized.
+impl<T: Debug> Serialize for Handle<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Handle::Glow(object) => serializer.serialize_str(&format!("{:?}", object)),
+            Handle::Mock(id) => serializer.serialize_u32(*id),
+        }
+    }
+}
@@ -1,19 +1,21 @@
+use serde::Serialize;
+
 use crate::Color4;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
 pub struct Blend {
     pub equation: BlendEquation,
     pub func: BlendFunc,
     pub constant_color: Color4,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub struct BlendEquation {
     pub color: BlendOp,
     pub alpha: BlendOp,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub struct BlendFunc {
     pub src_color: BlendFactor,
     pub src_alpha: BlendFactor,
@@ -21,7 +23,7 @@ pub struct BlendFunc {
     pub dst_alpha: BlendFactor,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum BlendOp {
     Add,
     Subtract,
@@ -30,7 +32,7 @@ pub enum BlendOp {
     Max,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum BlendFactor {
     Zero,
     One,
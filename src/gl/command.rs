@@ -0,0 +1,271 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::Color4;
+
+use super::{
+    draw_params::set_draw_params, BufferId, Context, DrawParams, FramebufferId, PrimitiveMode,
+    ProgramId, TextureId, VertexArrayId,
+};
+
+/// A single step of rendering.
+///
+/// Commands refer to GL objects by their ids, so the objects need to outlive
+/// the commands. Buffer uploads are commands of their own, which carry a copy
+/// of the data, so that replaying draws with the same vertices and uniform
+/// blocks as when they were recorded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum RenderCommand {
+    /// Binds a framebuffer, or the main framebuffer if `None`, and sets the
+    /// viewport and scissor rectangle.
+    BindFramebuffer {
+        framebuffer: Option<FramebufferId>,
+        viewport: [i32; 4],
+        scissor: Option<[i32; 4]>,
+    },
+    InvalidateFramebuffer {
+        attachments: Vec<u32>,
+    },
+    Clear {
+        color: Option<Color4>,
+        depth: Option<f32>,
+    },
+    SetDrawParams(DrawParams),
+    BindProgram(ProgramId),
+    BindUniformBuffer {
+        binding: u32,
+        buffer: BufferId,
+    },
+    /// Replaces the contents of a vertex, element or uniform buffer.
+    SetBufferData {
+        target: u32,
+        buffer: BufferId,
+        data: Vec<u8>,
+        usage: u32,
+    },
+    BindTexture {
+        unit: u32,
+        texture: TextureId,
+    },
+    Draw {
+        vertex_array: VertexArrayId,
+        primitive_mode: PrimitiveMode,
+        element_type: u32,
+        count: usize,
+        offset: usize,
+        num_instances: Option<usize>,
+    },
+}
+
+/// A list of recorded commands, see `gl::record`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CommandList {
+    commands: Vec<RenderCommand>,
+}
+
+impl RenderCommand {
+    pub fn execute(&self, gl: &Context) {
+        use RenderCommand::*;
+
+        match self {
+            BindFramebuffer {
+                framebuffer,
+                viewport,
+                scissor,
+            } => {
+                gl.set_framebuffer(*framebuffer);
+                gl.set_viewport(*viewport);
+
+                if let Some(scissor) = scissor {
+                    gl.set_capability(glow::SCISSOR_TEST, true);
+                    gl.set_scissor(*scissor);
+                } else {
+                    gl.set_capability(glow::SCISSOR_TEST, false);
+                }
+            }
+            InvalidateFramebuffer { attachments } => unsafe {
+                gl.invalidate_framebuffer(glow::FRAMEBUFFER, attachments);
+            },
+            Clear { color, depth } => {
+                let mut mask = 0;
+
+                if let Some(color) = color {
+                    gl.set_color_mask((true, true, true, true));
+                    unsafe {
+                        gl.clear_color(color.r, color.g, color.b, color.a);
+                    }
+                    mask |= glow::COLOR_BUFFER_BIT;
+                }
+                if let Some(depth) = depth {
+                    gl.set_depth_mask(true);
+                    unsafe {
+                        gl.clear_depth_f32(*depth);
+                    }
+                    mask |= glow::DEPTH_BUFFER_BIT;
+                }
+
+                unsafe {
+                    gl.clear(mask);
+                }
+            }
+            SetDrawParams(draw_params) => set_draw_params(gl, draw_params),
+            BindProgram(program) => gl.set_program(Some(*program)),
+            BindUniformBuffer { binding, buffer } => gl.set_uniform_buffer(*binding, *buffer),
+            SetBufferData {
+                target,
+                buffer,
+                data,
+                usage,
+            } => upload_buffer_data(gl, *target, *buffer, data, *usage),
+            BindTexture { unit, texture } => gl.set_texture(*unit, Some(*texture)),
+            Draw {
+                vertex_array,
+                primitive_mode,
+                element_type,
+                count,
+                offset,
+                num_instances,
+            } => {
+                gl.set_vertex_array(Some(*vertex_array));
+
+                unsafe {
+                    if let Some(num_instances) = num_instances {
+                        gl.draw_elements_instanced(
+                            primitive_mode.to_gl(),
+                            i32::try_from(*count).unwrap(),
+                            *element_type,
+                            i32::try_from(*offset).unwrap(),
+                            i32::try_from(*num_instances).unwrap(),
+                        );
+                    } else {
+                        gl.draw_elements(
+                            primitive_mode.to_gl(),
+                            i32::try_from(*count).unwrap(),
+                            *element_type,
+                            i32::try_from(*offset).unwrap(),
+                        );
+                    }
+                }
+
+                gl.count_frame_stats(|stats| {
+                    stats.draw_calls += 1;
+                    stats.elements += count * num_instances.unwrap_or(1);
+                    if num_instances.is_some() {
+                        stats.instanced_draw_calls += 1;
+                    }
+                });
+            }
+        }
+    }
+}
+
+impl CommandList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: RenderCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn commands(&self) -> &[RenderCommand] {
+        &self.commands
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Returns the number of draw commands.
+    pub fn num_draws(&self) -> usize {
+        self.commands
+            .iter()
+            .filter(|command| matches!(command, RenderCommand::Draw { .. }))
+            .count()
+    }
+
+    /// Executes the commands in order. This may also be called from within
+    /// `gl::record`, in which case the commands are appended to the outer
+    /// recording.
+    pub fn execute(&self, gl: &Context) {
+        for command in &self.commands {
+            gl.submit(command.clone());
+        }
+    }
+}
+
+/// Prints one command per line, which is convenient for diffing frames.
+impl fmt::Display for CommandList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for command in &self.commands {
+            writeln!(f, "{:?}", command)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl IntoIterator for CommandList {
+    type Item = RenderCommand;
+    type IntoIter = std::vec::IntoIter<RenderCommand>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.into_iter()
+    }
+}
+
+/// Records the commands that are issued by `f` instead of executing them.
+///
+/// Only the functions in `gl` that draw, clear, switch framebuffers or upload
+/// buffer contents are recorded. Everything else, such as creating objects or
+/// uploading textures, still happens immediately.
+pub fn record<F, R>(gl: &Context, f: F) -> (CommandList, R)
+where
+    F: FnOnce() -> R,
+{
+    let outer = gl.recording.replace(Some(CommandList::new()));
+    let result = f();
+    let commands = gl.recording.replace(outer).unwrap();
+
+    (commands, result)
+}
+
+impl Context {
+    /// Uploads the contents of a buffer, or copies them into the current
+    /// recording if called within `gl::record`.
+    pub(super) fn set_buffer_data(&self, target: u32, buffer: BufferId, data: &[u8], usage: u32) {
+        if self.is_recording() {
+            self.submit(RenderCommand::SetBufferData {
+                target,
+                buffer,
+                data: data.to_vec(),
+                usage,
+            });
+        } else if !self.is_lost() {
+            upload_buffer_data(self, target, buffer, data, usage);
+        }
+    }
+}
+
+fn upload_buffer_data(gl: &Context, target: u32, buffer: BufferId, data: &[u8], usage: u32) {
+    if target == glow::ELEMENT_ARRAY_BUFFER {
+        // Binding the element buffer would otherwise change the element
+        // buffer of whichever vertex array is currently bound.
+        gl.set_vertex_array(None);
+    }
+
+    unsafe {
+        gl.bind_buffer(target, Some(buffer));
+        gl.buffer_data_u8_slice(target, data, usage);
+    }
+
+    gl.count_frame_stats(|stats| {
+        stats.buffer_uploads += 1;
+        stats.buffer_bytes_uploaded += data.len();
+    });
+}
@@ -1,39 +1,132 @@
-use std::{cell::Cell, ops::Deref};
+use std::{
+    cell::{Cell, RefCell},
+    ops::Deref,
+};
 
-use glow::HasContext;
+use super::{
+    resources::ResourceRegistry, state_cache::StateCache, Backend, CommandList, FrameStats,
+    GlowBackend, MockBackend, RenderCommand,
+};
 
 pub struct Context {
-    context: glow::Context,
+    backend: Box<dyn Backend>,
     pub(super) main_viewport: Cell<[i32; 4]>,
+    pub(super) main_scissor: Cell<Option<[i32; 4]>>,
+    pub(super) recording: RefCell<Option<CommandList>>,
+    pub(super) state: RefCell<StateCache>,
+    pub(super) frame_stats: Cell<FrameStats>,
+    pub(super) last_frame_stats: Cell<FrameStats>,
+    pub(super) resources: RefCell<ResourceRegistry>,
+    pub(super) lost: Cell<bool>,
+    pub(super) generation: Cell<u64>,
+    color_buffer_float: bool,
 }
 
 impl Context {
     pub fn new(context: glow::Context) -> Self {
+        Self::with_backend(Box::new(GlowBackend::new(context)))
+    }
+
+    pub fn with_backend(backend: Box<dyn Backend>) -> Self {
         let mut main_viewport = [0, 0, 0, 0];
 
         unsafe {
-            context.get_parameter_i32_slice(glow::VIEWPORT, &mut main_viewport);
+            backend.get_parameter_i32_slice(glow::VIEWPORT, &mut main_viewport);
         }
 
+        let color_buffer_float = backend
+            .supported_extensions()
+            .contains("EXT_color_buffer_float");
+
         Context {
-            context,
+            backend,
             main_viewport: Cell::new(main_viewport),
+            main_scissor: Cell::new(None),
+            recording: RefCell::new(None),
+            state: RefCell::new(StateCache::new()),
+            frame_stats: Cell::new(FrameStats::default()),
+            last_frame_stats: Cell::new(FrameStats::default()),
+            resources: RefCell::new(ResourceRegistry::default()),
+            lost: Cell::new(false),
+            generation: Cell::new(0),
+            color_buffer_float,
+        }
+    }
+
+    /// Creates a context that does not render anything, for testing.
+    pub fn new_mock() -> Self {
+        Self::with_backend(Box::new(MockBackend::new()))
+    }
+
+    pub fn backend(&self) -> &dyn Backend {
+        &*self.backend
+    }
+
+    /// Returns the mock backend if the context has been created with
+    /// `new_mock`.
+    pub fn mock(&self) -> Option<&MockBackend> {
+        self.backend.as_any().downcast_ref()
+    }
+
+    /// Returns true if float textures can be rendered to, which WebGL 2
+    /// only allows with `EXT_color_buffer_float`.
+    pub fn supports_color_buffer_float(&self) -> bool {
+        self.color_buffer_float
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.borrow().is_some()
+    }
+
+    /// Executes a command, or appends it to the current recording if called
+    /// within `gl::record`. Commands are dropped while the context is lost.
+    pub fn submit(&self, command: RenderCommand) {
+        if let Some(recording) = self.recording.borrow_mut().as_mut() {
+            recording.push(command);
+            return;
+        }
+
+        if self.is_lost() {
+            return;
         }
+
+        command.execute(self);
+    }
+
+    pub fn main_viewport(&self) -> [i32; 4] {
+        self.main_viewport.get()
     }
 
     pub fn set_main_viewport(&self, viewport: [i32; 4]) {
         self.main_viewport.set(viewport);
-        unsafe {
-            self.context
-                .viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        self.set_viewport(viewport);
+    }
+
+    pub fn main_scissor(&self) -> Option<[i32; 4]> {
+        self.main_scissor.get()
+    }
+
+    /// Restricts drawing and clearing in the main framebuffer to a rectangle.
+    /// Rendering into a `Framebuffer` is not affected.
+    pub fn set_main_scissor(&self, scissor: Option<[i32; 4]>) {
+        self.main_scissor.set(scissor);
+        self.apply_main_scissor();
+    }
+
+    pub(super) fn apply_main_scissor(&self) {
+        if let Some(scissor) = self.main_scissor.get() {
+            self.set_capability(glow::SCISSOR_TEST, true);
+            self.set_scissor(scissor);
+        } else {
+            self.set_capability(glow::SCISSOR_TEST, false);
         }
     }
 }
 
 impl Deref for Context {
-    type Target = glow::Context;
+    type Target = dyn Backend;
 
-    fn deref(&self) -> &glow::Context {
-        &self.context
+    fn deref(&self) -> &dyn Backend {
+        &*self.backend
     }
 }
@@ -1,4 +1,6 @@
-#[derive(Copy, Clone, Debug, PartialEq)]
+use serde::Serialize;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
 pub struct DepthTest {
     pub func: DepthFunc,
     pub range_near: f32,
@@ -6,7 +8,7 @@ pub struct DepthTest {
     pub write: bool,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
 pub enum DepthFunc {
     Never,
     Less,
@@ -1,24 +1,21 @@
 use std::rc::Rc;
 
-use glow::HasContext;
-
 use crate::Color4;
 
 use super::{
-    draw_params::set_draw_params, uniform_block::UniformBuffers, Context, DrawParams, DrawUnit,
-    Element, Framebuffer, InstancedDrawUnit, Program, Texture, Vertex, VertexDecls,
+    uniform_block::UniformBuffers, Context, DrawParams, DrawUnit, Framebuffer, InstancedDrawUnit,
+    Program, RenderCommand, Texture, Vertex, VertexDecls,
 };
 
-pub fn draw<U, V, E, const S: usize>(
+pub fn draw<U, V, const S: usize>(
     program: &Program<U::UniformBlockDecls, V, S>,
     uniforms: U,
     samplers: [&Texture; S],
-    draw_unit: DrawUnit<V, E>,
+    draw_unit: DrawUnit<V>,
     draw_params: &DrawParams,
 ) where
     U: UniformBuffers,
     V: Vertex,
-    E: Element,
 {
     assert!(Rc::ptr_eq(&program.gl(), &draw_unit.gl()));
 
@@ -29,44 +26,32 @@ pub fn draw<U, V, E, const S: usize>(
 
     let gl = program.gl();
 
-    set_draw_params(&*gl, draw_params);
-
-    program.bind();
-    uniforms.bind(program.uniform_block_bindings());
-    bind_samplers(gl.clone(), samplers);
-    draw_unit.bind();
-
-    let count = range.end - range.start;
-    let offset = range.start * std::mem::size_of::<E>();
+    submit_bindings(&gl, program, uniforms, samplers, draw_params);
 
     // FIXME: We need to re-verify the element range here, since the buffers
     //        references by the DrawUnit could have changed since its creation.
 
-    unsafe {
-        gl.draw_elements(
-            draw_unit.primitive_mode().to_gl(),
-            i32::try_from(count).unwrap(),
-            E::to_gl(),
-            i32::try_from(offset).unwrap(),
-        );
-
-        // It is important to unbind the vertex array here, so that buffer
-        // bindings later on (e.g. for mutating buffer contents) do not change
-        // the vertex array bindings.
-        gl.bind_vertex_array(None);
-    }
+    let element_buffer = draw_unit.element_buffer();
+
+    gl.submit(RenderCommand::Draw {
+        vertex_array: draw_unit.vertex_array().id(),
+        primitive_mode: draw_unit.primitive_mode(),
+        element_type: element_buffer.element_type(),
+        count: range.end - range.start,
+        offset: range.start * element_buffer.element_size(),
+        num_instances: None,
+    });
 }
 
-pub fn draw_instanced<U, V, E, const S: usize>(
+pub fn draw_instanced<U, V, const S: usize>(
     program: &Program<U::UniformBlockDecls, V, S>,
     uniforms: U,
     samplers: [&Texture; S],
-    draw_unit: InstancedDrawUnit<V, E>,
+    draw_unit: InstancedDrawUnit<V>,
     draw_params: &DrawParams,
 ) where
     U: UniformBuffers,
     V: VertexDecls,
-    E: Element,
 {
     assert!(Rc::ptr_eq(&program.gl(), &draw_unit.gl()));
 
@@ -77,33 +62,21 @@ pub fn draw_instanced<U, V, E, const S: usize>(
 
     let gl = program.gl();
 
-    set_draw_params(&*gl, draw_params);
-
-    program.bind();
-    uniforms.bind(program.uniform_block_bindings());
-    bind_samplers(gl.clone(), samplers);
-    draw_unit.bind();
-
-    let count = range.end - range.start;
-    let offset = range.start * std::mem::size_of::<E>();
+    submit_bindings(&gl, program, uniforms, samplers, draw_params);
 
     // FIXME: We need to re-verify the element range here, since the buffers
     //        references by the DrawUnit could have changed since its creation.
 
-    unsafe {
-        gl.draw_elements_instanced(
-            draw_unit.primitive_mode().to_gl(),
-            i32::try_from(count).unwrap(),
-            E::to_gl(),
-            i32::try_from(offset).unwrap(),
-            i32::try_from(draw_unit.num_instances()).unwrap(),
-        );
-
-        // It is important to unbind the vertex array here, so that buffer
-        // bindings later on (e.g. for mutating buffer contents) do not change
-        // the vertex array bindings.
-        gl.bind_vertex_array(None);
-    }
+    let element_buffer = draw_unit.element_buffer();
+
+    gl.submit(RenderCommand::Draw {
+        vertex_array: draw_unit.vertex_array().id(),
+        primitive_mode: draw_unit.primitive_mode(),
+        element_type: element_buffer.element_type(),
+        count: range.end - range.start,
+        offset: range.start * element_buffer.element_size(),
+        num_instances: Some(draw_unit.num_instances()),
+    });
 }
 
 pub fn with_framebuffer<F, R>(framebuffer: &Framebuffer, f: F) -> R
@@ -112,28 +85,57 @@ where
 {
     let gl = framebuffer.gl();
 
-    unsafe {
-        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer.id()));
-        gl.viewport(
+    gl.submit(RenderCommand::BindFramebuffer {
+        framebuffer: Some(framebuffer.id()),
+        viewport: [
             0,
             0,
             i32::try_from(framebuffer.sizes()[0].x).unwrap(),
             i32::try_from(framebuffer.sizes()[0].y).unwrap(),
-        );
-    }
+        ],
+        scissor: None,
+    });
 
     let result = f();
 
     // TODO: We should be able to reduce state changes by delaying the unbind.
-    unsafe {
-        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
-        gl.viewport(
-            gl.main_viewport.get()[0],
-            gl.main_viewport.get()[1],
-            gl.main_viewport.get()[2],
-            gl.main_viewport.get()[3],
-        );
-    }
+    gl.submit(RenderCommand::BindFramebuffer {
+        framebuffer: None,
+        viewport: gl.main_viewport(),
+        scissor: gl.main_scissor(),
+    });
+
+    result
+}
+
+/// Renders into a part of the main framebuffer. Drawing and clearing are
+/// restricted to `viewport`, which is given as `[x, y, width, height]` with
+/// the origin at the lower-left.
+///
+/// The previous viewport is restored afterwards.
+pub fn with_viewport<F, R>(gl: &Context, viewport: [i32; 4], f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let prev_viewport = gl.main_viewport.replace(viewport);
+    let prev_scissor = gl.main_scissor.replace(Some(viewport));
+
+    gl.submit(RenderCommand::BindFramebuffer {
+        framebuffer: None,
+        viewport,
+        scissor: Some(viewport),
+    });
+
+    let result = f();
+
+    gl.main_viewport.set(prev_viewport);
+    gl.main_scissor.set(prev_scissor);
+
+    gl.submit(RenderCommand::BindFramebuffer {
+        framebuffer: None,
+        viewport: prev_viewport,
+        scissor: prev_scissor,
+    });
 
     result
 }
@@ -145,53 +147,62 @@ where
     let gl = framebuffer.gl();
 
     with_framebuffer(framebuffer, || {
-        unsafe {
-            gl.invalidate_framebuffer(glow::FRAMEBUFFER, framebuffer.attachments());
-        }
+        gl.submit(RenderCommand::InvalidateFramebuffer {
+            attachments: framebuffer.attachments().to_vec(),
+        });
         f()
     })
 }
 
 pub fn clear_color_and_depth(gl: &Context, color: Color4, depth: f32) {
-    unsafe {
-        gl.color_mask(true, true, true, true);
-        gl.depth_mask(true);
-        gl.clear_color(color.r, color.g, color.b, color.a);
-        gl.clear_depth_f32(depth);
-        gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
-    }
+    gl.submit(RenderCommand::Clear {
+        color: Some(color),
+        depth: Some(depth),
+    });
 }
 
 pub fn clear_color(gl: &Context, color: Color4) {
-    unsafe {
-        gl.color_mask(true, true, true, true);
-        gl.clear_color(color.r, color.g, color.b, color.a);
-        gl.clear(glow::COLOR_BUFFER_BIT);
-    }
+    gl.submit(RenderCommand::Clear {
+        color: Some(color),
+        depth: None,
+    });
 }
 
 pub fn clear_depth(gl: &Context, depth: f32) {
-    unsafe {
-        gl.depth_mask(true);
-        gl.clear_depth_f32(depth);
-        gl.clear(glow::DEPTH_BUFFER_BIT);
-    }
+    gl.submit(RenderCommand::Clear {
+        color: None,
+        depth: Some(depth),
+    });
 }
 
-fn bind_samplers<const S: usize>(gl: Rc<Context>, samplers: [&Texture; S]) {
+fn submit_bindings<U, V, const S: usize>(
+    gl: &Context,
+    program: &Program<U::UniformBlockDecls, V, S>,
+    uniforms: U,
+    samplers: [&Texture; S],
+    draw_params: &DrawParams,
+) where
+    U: UniformBuffers,
+{
+    gl.submit(RenderCommand::SetDrawParams(draw_params.clone()));
+    gl.submit(RenderCommand::BindProgram(program.id()));
+
+    let buffer_ids = uniforms.buffer_ids();
+    assert!(buffer_ids.len() == program.uniform_block_bindings().len());
+
+    for (&binding, buffer) in program.uniform_block_bindings().iter().zip(buffer_ids) {
+        gl.submit(RenderCommand::BindUniformBuffer { binding, buffer });
+    }
+
     // FIXME: We need to verify that we are not sampling from the current
     //        framebuffer.
 
     for (i, sampler) in samplers.iter().enumerate() {
-        assert!(Rc::ptr_eq(&sampler.gl(), &gl));
-
-        unsafe {
-            gl.active_texture(glow::TEXTURE0 + i as u32);
-            gl.bind_texture(glow::TEXTURE_2D, Some(sampler.id()));
-        }
-    }
+        assert!(std::ptr::eq(&*sampler.gl(), gl));
 
-    unsafe {
-        gl.active_texture(glow::TEXTURE0);
+        gl.submit(RenderCommand::BindTexture {
+            unit: i as u32,
+            texture: sampler.id(),
+        });
     }
 }
@@ -1,8 +1,8 @@
-use glow::HasContext;
+use serde::Serialize;
 
 use super::{Blend, Context, DepthTest};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct DrawParams {
     pub blend: Option<Blend>,
     pub depth_test: Option<DepthTest>,
@@ -22,87 +22,41 @@ impl Default for DrawParams {
 }
 
 pub fn set_draw_params(gl: &Context, draw_params: &DrawParams) {
-    // TODO: We may eventually need to implement some caching here.
-
     set_blend(gl, draw_params.blend);
     set_depth_test(gl, draw_params.depth_test);
-    unsafe {
-        gl.line_width(draw_params.line_width);
-        gl.color_mask(
-            draw_params.color_mask.0,
-            draw_params.color_mask.1,
-            draw_params.color_mask.2,
-            draw_params.color_mask.3,
-        );
-    }
+    gl.set_line_width(draw_params.line_width);
+    gl.set_color_mask(draw_params.color_mask);
 }
 
 fn set_blend(gl: &Context, blend: Option<Blend>) {
     match blend {
-        None => unsafe {
-            gl.disable(glow::BLEND);
-        },
+        None => gl.set_capability(glow::BLEND, false),
         Some(Blend {
             equation,
             func,
             constant_color,
         }) => {
-            unsafe {
-                gl.enable(glow::BLEND);
-            }
-
-            if equation.is_same() {
-                unsafe {
-                    gl.blend_equation(equation.color.to_gl());
-                }
-            } else {
-                unsafe {
-                    gl.blend_equation_separate(equation.color.to_gl(), equation.alpha.to_gl());
-                }
-            }
-
-            if func.is_same() {
-                unsafe {
-                    gl.blend_func(func.src_color.to_gl(), func.dst_color.to_gl());
-                }
-            } else {
-                unsafe {
-                    gl.blend_func_separate(
-                        func.src_color.to_gl(),
-                        func.dst_color.to_gl(),
-                        func.src_alpha.to_gl(),
-                        func.dst_alpha.to_gl(),
-                    )
-                }
-            }
-
-            unsafe {
-                gl.blend_color(
-                    constant_color.r,
-                    constant_color.g,
-                    constant_color.b,
-                    constant_color.a,
-                );
-            }
+            gl.set_capability(glow::BLEND, true);
+            gl.set_blend_equation(equation);
+            gl.set_blend_func(func);
+            gl.set_blend_color(constant_color);
         }
     }
 }
 
 fn set_depth_test(gl: &Context, depth_test: Option<DepthTest>) {
     match depth_test {
-        None => unsafe {
-            gl.disable(glow::DEPTH_TEST);
-        },
+        None => gl.set_capability(glow::DEPTH_TEST, false),
         Some(DepthTest {
             func,
             range_near,
             range_far,
             write,
-        }) => unsafe {
-            gl.enable(glow::DEPTH_TEST);
-            gl.depth_func(func.to_gl());
-            gl.depth_range_f32(range_near, range_far);
-            gl.depth_mask(write);
-        },
+        }) => {
+            gl.set_capability(glow::DEPTH_TEST, true);
+            gl.set_depth_func(func);
+            gl.set_depth_range(range_near, range_far);
+            gl.set_depth_mask(write);
+        }
     }
 }
@@ -1,9 +1,8 @@
 use std::{collections::VecDeque, rc::Rc, time::Duration};
 
-use glow::HasContext;
 use instant::Instant;
 
-use super::Context;
+use super::{Context, QueryId};
 
 const MAX_POLL_QUERIES: usize = 100;
 
@@ -11,9 +10,10 @@ pub struct DrawTimer {
     gl: Rc<Context>,
     max_age: Duration,
     is_supported: bool,
+    generation: u64,
 
-    last_query: Option<(Instant, glow::Query)>,
-    poll_queries: VecDeque<(Instant, glow::Query)>,
+    last_query: Option<(Instant, QueryId)>,
+    poll_queries: VecDeque<(Instant, QueryId)>,
 
     samples: VecDeque<(Instant, Duration)>,
 }
@@ -36,10 +36,13 @@ impl DrawTimer {
 
         log::info!("{:?}", gl.supported_extensions());
 
+        let generation = gl.generation();
+
         Self {
             gl,
             max_age,
             is_supported,
+            generation,
             last_query: None,
             poll_queries: VecDeque::new(),
             samples: VecDeque::new(),
@@ -56,6 +59,13 @@ impl DrawTimer {
             "end_draw must be called after start_draw"
         );
 
+        if self.gl.generation() != self.generation {
+            // Pending queries died with the lost context, so there is no
+            // point in polling or deleting them.
+            self.poll_queries.clear();
+            self.generation = self.gl.generation();
+        }
+
         if !self.poll_queries.is_empty() {
             return;
         }
@@ -1,28 +1,30 @@
 use std::{ops::Range, rc::Rc};
 
+use serde::Serialize;
+
 use super::{Context, ElementBuffer, Vertex, VertexArray, VertexBuffer, VertexDecls};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum PrimitiveMode {
     Triangle,
     Line,
 }
 
-pub struct DrawUnit<'a, V, E = u32>
+pub struct DrawUnit<'a, V>
 where
     V: Vertex,
 {
-    vertex_array: &'a VertexArray<V, E>,
+    vertex_array: &'a VertexArray<V>,
     primitive_mode: PrimitiveMode,
     element_range: Range<usize>,
 }
 
-impl<'a, V, E> DrawUnit<'a, V, E>
+impl<'a, V> DrawUnit<'a, V>
 where
     V: Vertex,
 {
     pub fn new(
-        vertex_array: &'a VertexArray<V, E>,
+        vertex_array: &'a VertexArray<V>,
         primitive_mode: PrimitiveMode,
         element_range: Range<usize>,
     ) -> Self {
@@ -40,7 +42,7 @@ where
         self.vertex_array.gl()
     }
 
-    pub fn vertex_array(&self) -> &'a VertexArray<V, E> {
+    pub fn vertex_array(&self) -> &'a VertexArray<V> {
         self.vertex_array
     }
 
@@ -48,7 +50,7 @@ where
         self.vertex_array.vertex_buffers()
     }
 
-    pub fn element_buffer(&self) -> Rc<ElementBuffer<E>> {
+    pub fn element_buffer(&self) -> Rc<ElementBuffer> {
         self.vertex_array.element_buffer()
     }
 
@@ -65,22 +67,22 @@ where
     }
 }
 
-pub struct InstancedDrawUnit<'a, V, E = u32>
+pub struct InstancedDrawUnit<'a, V>
 where
     V: VertexDecls,
 {
-    vertex_array: &'a VertexArray<V, E>,
+    vertex_array: &'a VertexArray<V>,
     primitive_mode: PrimitiveMode,
     element_range: Range<usize>,
     num_instances: usize,
 }
 
-impl<'a, V, E> InstancedDrawUnit<'a, V, E>
+impl<'a, V> InstancedDrawUnit<'a, V>
 where
     V: VertexDecls,
 {
     pub fn new(
-        vertex_array: &'a VertexArray<V, E>,
+        vertex_array: &'a VertexArray<V>,
         primitive_mode: PrimitiveMode,
         element_range: Range<usize>,
         num_instances: usize,
@@ -100,7 +102,7 @@ where
         self.vertex_array.gl()
     }
 
-    pub fn vertex_array(&self) -> &'a VertexArray<V, E> {
+    pub fn vertex_array(&self) -> &'a VertexArray<V> {
         self.vertex_array
     }
 
@@ -108,7 +110,7 @@ where
         self.vertex_array.vertex_buffers()
     }
 
-    pub fn element_buffer(&self) -> Rc<ElementBuffer<E>> {
+    pub fn element_buffer(&self) -> Rc<ElementBuffer> {
         self.vertex_array.element_buffer()
     }
 
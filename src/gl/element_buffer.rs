@@ -1,95 +1,202 @@
-use std::{cell::Cell, marker::PhantomData, rc::Rc};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use bytemuck::Pod;
-use glow::HasContext;
 
-use super::{Context, Error};
+use super::{
+    restore::{restore_buffer, RestorableId},
+    BufferId, Context, Error,
+};
 
 pub trait Element: Pod {
+    /// The largest vertex index that can be stored.
+    ///
+    /// WebGL 2 always enables primitive restart, which reserves the maximum
+    /// value of the type, so this is one less than that.
+    const MAX_INDEX: u32;
+
     fn to_gl() -> u32;
+
+    /// Converts `u32` indices, which must not be larger than `MAX_INDEX`.
+    fn from_u32_slice(indices: &[u32]) -> Cow<[Self]>;
 }
 
 impl Element for u32 {
+    const MAX_INDEX: u32 = u32::MAX - 1;
+
     fn to_gl() -> u32 {
         glow::UNSIGNED_INT
     }
+
+    fn from_u32_slice(indices: &[u32]) -> Cow<[Self]> {
+        Cow::Borrowed(indices)
+    }
 }
 
 impl Element for u16 {
+    const MAX_INDEX: u32 = u16::MAX as u32 - 1;
+
     fn to_gl() -> u32 {
         glow::UNSIGNED_SHORT
     }
+
+    fn from_u32_slice(indices: &[u32]) -> Cow<[Self]> {
+        Cow::Owned(
+            indices
+                .iter()
+                .map(|&index| u16::try_from(index).unwrap())
+                .collect(),
+        )
+    }
 }
 
-pub struct ElementBuffer<E = u32> {
+/// Buffer of vertex indices.
+///
+/// The indices are stored as either `u16` or `u32`, which can change with
+/// every upload. Like `VertexBuffer`, only static buffers keep a copy of their
+/// data for restoring them after context loss.
+pub struct ElementBuffer {
     gl: Rc<Context>,
-    id: glow::Buffer,
+    id: RestorableId<BufferId>,
     len: Cell<usize>,
-    _phantom: PhantomData<E>,
+
+    // Type of the elements that have been uploaded last.
+    element_type: Cell<u32>,
+    element_size: Cell<usize>,
+
+    // Kept for restoring the buffer after context loss. Empty unless the
+    // buffer is static.
+    data: RefCell<Vec<u8>>,
+    usage: Cell<u32>,
 }
 
-impl<E> ElementBuffer<E>
-where
-    E: Element,
-{
+impl ElementBuffer {
     pub fn new(gl: Rc<Context>) -> Result<Self, Error> {
         let id = unsafe { gl.create_buffer() }.map_err(Error::Glow)?;
+        gl.register_resource(id, "element buffer".into(), 0);
 
         Ok(Self {
+            id: RestorableId::new(&gl, id),
             gl,
-            id,
             len: Cell::new(0),
-            _phantom: PhantomData,
+            element_type: Cell::new(u32::to_gl()),
+            element_size: Cell::new(std::mem::size_of::<u32>()),
+            data: RefCell::new(Vec::new()),
+            usage: Cell::new(glow::STREAM_DRAW),
         })
     }
 
-    pub fn new_static(gl: Rc<Context>, data: &[E]) -> Result<Self, Error> {
+    pub fn new_static<E: Element>(gl: Rc<Context>, data: &[E]) -> Result<Self, Error> {
         let element_buffer = Self::new(gl)?;
-        element_buffer.set_data_with_usage(data, glow::STATIC_DRAW);
+        element_buffer.set_static(data);
 
         Ok(element_buffer)
     }
 
-    pub fn set(&self, data: &[E]) {
+    pub fn set<E: Element>(&self, data: &[E]) {
         self.set_data_with_usage(data, glow::STREAM_DRAW);
     }
 
-    fn set_data_with_usage(&self, data: &[E], usage: u32) {
-        let data_u8 = bytemuck::cast_slice(data);
+    /// Uploads data that is not going to change often, keeping a copy for
+    /// restoring the buffer after context loss.
+    pub fn set_static<E: Element>(&self, data: &[E]) {
+        self.set_data_with_usage(data, glow::STATIC_DRAW);
+    }
 
-        unsafe {
-            self.gl
-                .bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.id));
-            self.gl
-                .buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, data_u8, usage);
-        }
+    /// Uploads the elements as `u16` if all of them fit, and as `u32`
+    /// otherwise.
+    pub fn set_compact(&self, data: &[u32]) {
+        self.set_compact_with_usage(data, glow::STREAM_DRAW);
+    }
 
-        self.len.set(data.len());
+    /// Like `set_compact`, but keeps a copy for restoring the buffer after
+    /// context loss.
+    pub fn set_compact_static(&self, data: &[u32]) {
+        self.set_compact_with_usage(data, glow::STATIC_DRAW);
+    }
+
+    fn set_compact_with_usage(&self, data: &[u32], usage: u32) {
+        if data.iter().all(|&index| index <= u16::MAX_INDEX) {
+            self.set_data_with_usage(&u16::from_u32_slice(data), usage);
+        } else {
+            self.set_data_with_usage(data, usage);
+        }
     }
-}
 
-impl<E> ElementBuffer<E> {
     pub fn gl(&self) -> Rc<Context> {
         self.gl.clone()
     }
 
-    pub fn id(&self) -> glow::Buffer {
-        self.id
+    pub fn id(&self) -> BufferId {
+        self.id.get(&self.gl, |old_id| {
+            let data = self.data.borrow();
+            if data.is_empty() {
+                // Streamed buffers come back empty.
+                self.len.set(0);
+            }
+
+            restore_buffer(
+                &self.gl,
+                old_id,
+                glow::ELEMENT_ARRAY_BUFFER,
+                &data,
+                self.usage.get(),
+            )
+        })
     }
 
     pub fn len(&self) -> usize {
+        // Restore first, since streamed buffers come back empty.
+        self.id();
         self.len.get()
     }
 
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// GL type of the elements that are currently stored.
+    pub fn element_type(&self) -> u32 {
+        self.element_type.get()
+    }
+
+    /// Size in bytes of the elements that are currently stored.
+    pub fn element_size(&self) -> usize {
+        self.element_size.get()
+    }
+
+    fn set_data_with_usage<E: Element>(&self, data: &[E], usage: u32) {
+        let data_u8 = bytemuck::cast_slice(data);
+
+        self.gl
+            .set_buffer_data(glow::ELEMENT_ARRAY_BUFFER, self.id(), data_u8, usage);
+
+        self.len.set(data.len());
+        self.element_type.set(E::to_gl());
+        self.element_size.set(std::mem::size_of::<E>());
+        self.gl.set_resource_size(self.id(), data_u8.len());
+
+        let mut retained = self.data.borrow_mut();
+        retained.clear();
+        if usage == glow::STATIC_DRAW {
+            retained.extend_from_slice(data_u8);
+        } else {
+            retained.shrink_to_fit();
+        }
+        self.usage.set(usage);
+    }
 }
 
-impl<E> Drop for ElementBuffer<E> {
+impl Drop for ElementBuffer {
     fn drop(&mut self) {
+        let id = self.id.current();
+        self.gl.forget_buffer(id);
+        self.gl.unregister_resource(id);
         unsafe {
-            self.gl.delete_buffer(self.id);
+            self.gl.delete_buffer(id);
         }
     }
 }
@@ -0,0 +1,77 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use super::Context;
+
+/// Counts the work that has been submitted to GL during one frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FrameStats {
+    /// Number of draw calls, including instanced ones.
+    pub draw_calls: usize,
+    pub instanced_draw_calls: usize,
+
+    /// Number of elements submitted with draw calls. For instanced draw
+    /// calls, this is multiplied by the number of instances.
+    pub elements: usize,
+
+    pub buffer_uploads: usize,
+    pub buffer_bytes_uploaded: usize,
+    pub texture_uploads: usize,
+    pub texture_bytes_uploaded: usize,
+
+    pub framebuffer_switches: usize,
+    pub program_switches: usize,
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "draws: {} ({} instanced)",
+            self.draw_calls, self.instanced_draw_calls
+        )?;
+        writeln!(f, "elements: {}", self.elements)?;
+        writeln!(
+            f,
+            "buffer uploads: {} ({:.1} KiB)",
+            self.buffer_uploads,
+            self.buffer_bytes_uploaded as f64 / 1024.0
+        )?;
+        writeln!(
+            f,
+            "texture uploads: {} ({:.1} KiB)",
+            self.texture_uploads,
+            self.texture_bytes_uploaded as f64 / 1024.0
+        )?;
+        writeln!(f, "framebuffer switches: {}", self.framebuffer_switches)?;
+        write!(f, "program switches: {}", self.program_switches)
+    }
+}
+
+impl Context {
+    /// Returns the statistics that have been collected since the last call
+    /// to `end_frame_stats`.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_stats.get()
+    }
+
+    /// Returns the statistics of the last completed frame.
+    pub fn last_frame_stats(&self) -> FrameStats {
+        self.last_frame_stats.get()
+    }
+
+    /// Completes the statistics of the current frame and starts counting
+    /// from zero. `Profile` calls this when its `FrameGuard` is dropped.
+    pub fn end_frame_stats(&self) -> FrameStats {
+        let stats = self.frame_stats.take();
+        self.last_frame_stats.set(stats);
+        stats
+    }
+
+    pub(super) fn count_frame_stats(&self, f: impl FnOnce(&mut FrameStats)) {
+        let mut stats = self.frame_stats.get();
+        f(&mut stats);
+        self.frame_stats.set(stats);
+    }
+}
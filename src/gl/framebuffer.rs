@@ -1,13 +1,15 @@
 use std::rc::Rc;
 
-use glow::{HasContext, PixelPackData};
+use glow::PixelPackData;
 use half::f16;
-use nalgebra::Vector2;
+use nalgebra::{Point2, Vector2};
 use thiserror::Error;
 
 use crate::gl::TextureValueType;
 
-use super::{Context, NewTextureError, Texture};
+use super::{
+    restore::RestorableId, Context, FramebufferId, NewTextureError, PixelComponent, Texture,
+};
 
 #[derive(Error, Debug)]
 pub enum NewFramebufferError {
@@ -19,13 +21,17 @@ pub enum NewFramebufferError {
 
     #[error("texture error: {0}")]
     Texture(#[from] NewTextureError),
+
+    #[error("texture value type {0:?} can not be used as a color attachment")]
+    NotColorRenderable(TextureValueType),
 }
 
 pub struct Framebuffer {
     gl: Rc<Context>,
     textures: Vec<Rc<Texture>>,
+    mipmap_levels: Vec<u32>,
     sizes: Vec<Vector2<u32>>,
-    id: glow::Framebuffer,
+    id: RestorableId<FramebufferId>,
     attachments: Vec<u32>,
 }
 
@@ -61,6 +67,15 @@ impl Framebuffer {
             .iter()
             .all(|(t, _)| t.size() == textures.first().unwrap().0.size()));
 
+        if let Some((texture, _)) = textures.iter().find(|(t, _)| {
+            let value_type = t.params().value_type;
+            !value_type.is_depth() && !value_type.is_color_renderable(&t.gl())
+        }) {
+            return Err(NewFramebufferError::NotColorRenderable(
+                texture.params().value_type,
+            ));
+        }
+
         let gl = textures[0].0.gl();
 
         if num_color > Self::max_color_attachments(&gl) as usize {
@@ -70,71 +85,37 @@ impl Framebuffer {
             ));
         }
 
-        let id = unsafe { gl.create_framebuffer() }.map_err(super::Error::Glow)?;
-
-        unsafe {
-            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(id));
-        }
-
-        let mut draw_buffers = Vec::new();
-        let mut attachments = Vec::new();
-        for (location, (texture, mipmap_level)) in textures
-            .iter()
-            .filter(|(t, _)| !t.params().value_type.is_depth())
-            .enumerate()
-        {
-            let attachment = glow::COLOR_ATTACHMENT0 + location as u32;
-            draw_buffers.push(attachment);
-            attachments.push(attachment);
-
-            unsafe {
-                gl.framebuffer_texture_2d(
-                    glow::FRAMEBUFFER,
-                    attachment,
-                    glow::TEXTURE_2D,
-                    Some(texture.id()),
-                    i32::try_from(*mipmap_level).unwrap(),
-                );
-            }
-        }
-
-        for (texture, mipmap_level) in textures
-            .iter()
-            .filter(|(t, _)| t.params().value_type.is_depth())
-        {
-            let attachment = glow::DEPTH_ATTACHMENT;
-            attachments.push(attachment);
-
-            unsafe {
-                gl.framebuffer_texture_2d(
-                    glow::FRAMEBUFFER,
-                    attachment,
-                    glow::TEXTURE_2D,
-                    Some(texture.id()),
-                    i32::try_from(*mipmap_level).unwrap(),
-                );
-            }
-        }
-
-        unsafe {
-            gl.draw_buffers(&draw_buffers);
-            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
-        }
+        let mipmap_levels: Vec<u32> = textures.iter().map(|(_, level)| *level).collect();
+        let textures: Vec<Rc<Texture>> = textures.into_iter().map(|(t, _)| t).collect();
+        let (id, attachments) = create_framebuffer(&gl, &textures, &mipmap_levels)?;
 
         let sizes = textures
             .iter()
+            .zip(&mipmap_levels)
             .map(|(t, level)| {
                 let w = (t.size().x / 2_u32.pow(*level)).max(1);
                 let h = (t.size().y / 2_u32.pow(*level)).max(1);
                 Vector2::new(w, h)
             })
-            .collect();
+            .collect::<Vec<_>>();
+
+        gl.register_resource(
+            id,
+            format!(
+                "framebuffer {}x{}, {} attachments",
+                sizes[0].x,
+                sizes[0].y,
+                attachments.len()
+            ),
+            0,
+        );
 
         Ok(Framebuffer {
+            id: RestorableId::new(&gl, id),
             gl,
-            textures: textures.into_iter().map(|(t, _)| t).collect(),
+            textures,
+            mipmap_levels,
             sizes,
-            id,
             attachments,
         })
     }
@@ -151,56 +132,162 @@ impl Framebuffer {
         &self.sizes
     }
 
-    pub fn id(&self) -> glow::Framebuffer {
-        self.id
+    pub fn id(&self) -> FramebufferId {
+        self.id.get(&self.gl, |old_id| {
+            let (id, _) = create_framebuffer(&self.gl, &self.textures, &self.mipmap_levels)?;
+            self.gl.rename_resource(old_id, id);
+            Ok(id)
+        })
     }
 
     pub fn attachments(&self) -> &[u32] {
         &self.attachments
     }
 
-    pub fn read_pixel_row_f16(&self, location: usize, y: u32) -> Vec<f16> {
-        let texture = &self.textures[location];
-        let attachment = glow::COLOR_ATTACHMENT0 + location as u32;
+    /// Sets the label under which the framebuffer is listed in
+    /// `Context::resource_dump`.
+    pub fn set_label(&self, label: &str) {
+        self.gl.set_resource_label(self.id(), label);
+    }
+
+    /// Reads a row of an `RgF16` attachment.
+    pub fn read_pixel_row_f16(&self, index: usize, y: u32) -> Vec<f16> {
+        assert!(self.textures[index].params().value_type == TextureValueType::RgF16);
+
+        let width = self.sizes[index].x;
+        self.read_pixels::<f32>(index, Point2::new(0, y), Vector2::new(width, 1))
+            .into_iter()
+            .map(f16::from_f32)
+            .collect()
+    }
+
+    /// Reads a region of the color attachment of `self.textures()[index]`.
+    ///
+    /// `T` must match `TextureValueType::read_type_gl`, i.e. `u8` for
+    /// normalized formats such as `RgbaU8`, `f32` for float formats including
+    /// `RgF16`, and `u32` or `i32` for integer formats. The result holds
+    /// `num_components` values per pixel, with rows going from bottom to top.
+    ///
+    /// The pixels are read as RGBA, which WebGL 2 always accepts, and the
+    /// texture's components are extracted afterwards.
+    pub fn read_pixels<T: PixelComponent>(
+        &self,
+        index: usize,
+        pos: Point2<u32>,
+        size: Vector2<u32>,
+    ) -> Vec<T> {
+        let value_type = self.textures[index].params().value_type;
+
+        assert!(
+            !value_type.is_depth(),
+            "Depth attachments can not be read back in WebGL"
+        );
+        assert!(
+            T::TYPE_GL == value_type.read_type_gl(),
+            "Component type does not match texture value type {:?}",
+            value_type,
+        );
+        assert!(pos.x + size.x <= self.sizes[index].x);
+        assert!(pos.y + size.y <= self.sizes[index].y);
 
-        // TODO
-        assert!(texture.params().value_type == TextureValueType::RgF16);
+        let mut rgba: Vec<T> = vec![T::zeroed(); size.x as usize * size.y as usize * 4];
 
-        let mut data: Vec<f16> = vec![f16::from_f32(0.0); 2 * texture.size().x as usize];
+        let previous_framebuffer = self.gl.current_framebuffer();
+        let id = self.id();
 
+        self.gl.set_framebuffer(Some(id));
         unsafe {
-            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.id));
-            self.gl.read_buffer(attachment);
+            self.gl.read_buffer(self.attachments[index]);
             self.gl.read_pixels(
-                0,
-                i32::try_from(y).unwrap(),
-                i32::try_from(texture.size().x).unwrap(),
-                1,
-                texture.params().value_type.format_gl(),
-                texture.params().value_type.type_gl(),
-                PixelPackData::Slice(bytemuck::cast_slice_mut(&mut data)),
+                i32::try_from(pos.x).unwrap(),
+                i32::try_from(pos.y).unwrap(),
+                i32::try_from(size.x).unwrap(),
+                i32::try_from(size.y).unwrap(),
+                value_type.read_format_gl(),
+                value_type.read_type_gl(),
+                PixelPackData::Slice(bytemuck::cast_slice_mut(&mut rgba)),
             );
-            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
         }
-
-        data
+        self.gl.set_framebuffer(previous_framebuffer);
+
+        let num_components = value_type.num_components();
+        if num_components == 4 {
+            rgba
+        } else {
+            rgba.chunks_exact(4)
+                .flat_map(|pixel| pixel[..num_components].iter().copied())
+                .collect()
+        }
     }
 
     pub fn invalidate(&self) {
         let gl = self.gl();
 
+        gl.set_framebuffer(Some(self.id()));
         unsafe {
-            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.id));
             gl.invalidate_framebuffer(glow::FRAMEBUFFER, &self.attachments);
-            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
         }
+        gl.set_framebuffer(None);
     }
 }
 
 impl Drop for Framebuffer {
     fn drop(&mut self) {
+        let id = self.id.current();
+        self.gl.forget_framebuffer(id);
+        self.gl.unregister_resource(id);
         unsafe {
-            self.gl.delete_framebuffer(self.id);
+            self.gl.delete_framebuffer(id);
         }
     }
 }
+
+fn create_framebuffer(
+    gl: &Context,
+    textures: &[Rc<Texture>],
+    mipmap_levels: &[u32],
+) -> Result<(FramebufferId, Vec<u32>), super::Error> {
+    // Get the texture ids first, since restoring textures after context loss
+    // changes texture bindings.
+    let texture_ids: Vec<_> = textures.iter().map(|t| t.id()).collect();
+
+    let id = unsafe { gl.create_framebuffer() }.map_err(super::Error::Glow)?;
+
+    gl.set_framebuffer(Some(id));
+
+    let mut draw_buffers = Vec::new();
+    let mut attachments = Vec::new();
+    let mut num_color = 0;
+    for ((texture, texture_id), mipmap_level) in textures.iter().zip(texture_ids).zip(mipmap_levels)
+    {
+        let value_type = texture.params().value_type;
+        let attachment = if value_type.has_stencil() {
+            glow::DEPTH_STENCIL_ATTACHMENT
+        } else if value_type.is_depth() {
+            glow::DEPTH_ATTACHMENT
+        } else {
+            let attachment = glow::COLOR_ATTACHMENT0 + num_color;
+            draw_buffers.push(attachment);
+            num_color += 1;
+            attachment
+        };
+        attachments.push(attachment);
+
+        unsafe {
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                attachment,
+                glow::TEXTURE_2D,
+                Some(texture_id),
+                i32::try_from(*mipmap_level).unwrap(),
+            );
+        }
+    }
+
+    unsafe {
+        gl.draw_buffers(&draw_buffers);
+    }
+    gl.set_framebuffer(None);
+
+    Ok((id, attachments))
+}
@@ -0,0 +1,494 @@
+use std::{any::Any, collections::HashSet};
+
+use glow::{HasContext, PixelPackData, PixelUnpackData};
+
+use super::backend::{
+    Backend, BufferId, FramebufferId, Handle, ProgramId, QueryId, ShaderId, TextureId,
+    UniformLocation, VertexArrayId,
+};
+
+/// Backend that forwards to an actual OpenGL context.
+pub struct GlowBackend {
+    context: glow::Context,
+}
+
+impl GlowBackend {
+    pub fn new(context: glow::Context) -> Self {
+        Self { context }
+    }
+
+    pub fn context(&self) -> &glow::Context {
+        &self.context
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+impl Backend for GlowBackend {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn supported_extensions(&self) -> &HashSet<String> {
+        self.context.supported_extensions()
+    }
+
+    unsafe fn create_buffer(&self) -> Result<BufferId, String> {
+        self.context
+            .create_buffer()
+            .map(|id| BufferId(Handle::Glow(id)))
+    }
+
+    unsafe fn create_framebuffer(&self) -> Result<FramebufferId, String> {
+        self.context
+            .create_framebuffer()
+            .map(|id| FramebufferId(Handle::Glow(id)))
+    }
+
+    unsafe fn create_program(&self) -> Result<ProgramId, String> {
+        self.context
+            .create_program()
+            .map(|id| ProgramId(Handle::Glow(id)))
+    }
+
+    unsafe fn create_query(&self) -> Result<QueryId, String> {
+        self.context
+            .create_query()
+            .map(|id| QueryId(Handle::Glow(id)))
+    }
+
+    unsafe fn create_shader(&self, shader_type: u32) -> Result<ShaderId, String> {
+        self.context
+            .create_shader(shader_type)
+            .map(|id| ShaderId(Handle::Glow(id)))
+    }
+
+    unsafe fn create_texture(&self) -> Result<TextureId, String> {
+        self.context
+            .create_texture()
+            .map(|id| TextureId(Handle::Glow(id)))
+    }
+
+    unsafe fn create_vertex_array(&self) -> Result<VertexArrayId, String> {
+        self.context
+            .create_vertex_array()
+            .map(|id| VertexArrayId(Handle::Glow(id)))
+    }
+
+    unsafe fn delete_buffer(&self, buffer: BufferId) {
+        self.context.delete_buffer(buffer.0.glow())
+    }
+
+    unsafe fn delete_framebuffer(&self, framebuffer: FramebufferId) {
+        self.context.delete_framebuffer(framebuffer.0.glow())
+    }
+
+    unsafe fn delete_program(&self, program: ProgramId) {
+        self.context.delete_program(program.0.glow())
+    }
+
+    unsafe fn delete_query(&self, query: QueryId) {
+        self.context.delete_query(query.0.glow())
+    }
+
+    unsafe fn delete_shader(&self, shader: ShaderId) {
+        self.context.delete_shader(shader.0.glow())
+    }
+
+    unsafe fn delete_texture(&self, texture: TextureId) {
+        self.context.delete_texture(texture.0.glow())
+    }
+
+    unsafe fn delete_vertex_array(&self, vertex_array: VertexArrayId) {
+        self.context.delete_vertex_array(vertex_array.0.glow())
+    }
+
+    unsafe fn active_texture(&self, unit: u32) {
+        self.context.active_texture(unit)
+    }
+
+    unsafe fn attach_shader(&self, program: ProgramId, shader: ShaderId) {
+        self.context
+            .attach_shader(program.0.glow(), shader.0.glow())
+    }
+
+    unsafe fn detach_shader(&self, program: ProgramId, shader: ShaderId) {
+        self.context
+            .detach_shader(program.0.glow(), shader.0.glow())
+    }
+
+    unsafe fn begin_query(&self, target: u32, query: QueryId) {
+        self.context.begin_query(target, query.0.glow())
+    }
+
+    unsafe fn end_query(&self, target: u32) {
+        self.context.end_query(target)
+    }
+
+    unsafe fn bind_attrib_location(&self, program: ProgramId, index: u32, name: &str) {
+        self.context
+            .bind_attrib_location(program.0.glow(), index, name)
+    }
+
+    unsafe fn bind_buffer(&self, target: u32, buffer: Option<BufferId>) {
+        self.context
+            .bind_buffer(target, buffer.map(|id| id.0.glow()))
+    }
+
+    unsafe fn bind_buffer_base(&self, target: u32, index: u32, buffer: Option<BufferId>) {
+        self.context
+            .bind_buffer_base(target, index, buffer.map(|id| id.0.glow()))
+    }
+
+    unsafe fn bind_framebuffer(&self, target: u32, framebuffer: Option<FramebufferId>) {
+        self.context
+            .bind_framebuffer(target, framebuffer.map(|id| id.0.glow()))
+    }
+
+    unsafe fn bind_texture(&self, target: u32, texture: Option<TextureId>) {
+        self.context
+            .bind_texture(target, texture.map(|id| id.0.glow()))
+    }
+
+    unsafe fn bind_vertex_array(&self, vertex_array: Option<VertexArrayId>) {
+        self.context
+            .bind_vertex_array(vertex_array.map(|id| id.0.glow()))
+    }
+
+    unsafe fn blend_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+        self.context.blend_color(red, green, blue, alpha)
+    }
+
+    unsafe fn blend_equation(&self, mode: u32) {
+        self.context.blend_equation(mode)
+    }
+
+    unsafe fn blend_equation_separate(&self, mode_rgb: u32, mode_alpha: u32) {
+        self.context.blend_equation_separate(mode_rgb, mode_alpha)
+    }
+
+    unsafe fn blend_func(&self, src: u32, dst: u32) {
+        self.context.blend_func(src, dst)
+    }
+
+    unsafe fn blend_func_separate(
+        &self,
+        src_rgb: u32,
+        dst_rgb: u32,
+        src_alpha: u32,
+        dst_alpha: u32,
+    ) {
+        self.context
+            .blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha)
+    }
+
+    unsafe fn buffer_data_u8_slice(&self, target: u32, data: &[u8], usage: u32) {
+        self.context.buffer_data_u8_slice(target, data, usage)
+    }
+
+    unsafe fn clear(&self, mask: u32) {
+        self.context.clear(mask)
+    }
+
+    unsafe fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+        self.context.clear_color(red, green, blue, alpha)
+    }
+
+    unsafe fn clear_depth_f32(&self, depth: f32) {
+        self.context.clear_depth_f32(depth)
+    }
+
+    unsafe fn color_mask(&self, red: bool, green: bool, blue: bool, alpha: bool) {
+        self.context.color_mask(red, green, blue, alpha)
+    }
+
+    unsafe fn compile_shader(&self, shader: ShaderId) {
+        self.context.compile_shader(shader.0.glow())
+    }
+
+    unsafe fn depth_func(&self, func: u32) {
+        self.context.depth_func(func)
+    }
+
+    unsafe fn depth_mask(&self, value: bool) {
+        self.context.depth_mask(value)
+    }
+
+    unsafe fn depth_range_f32(&self, near: f32, far: f32) {
+        self.context.depth_range_f32(near, far)
+    }
+
+    unsafe fn disable(&self, parameter: u32) {
+        self.context.disable(parameter)
+    }
+
+    unsafe fn enable(&self, parameter: u32) {
+        self.context.enable(parameter)
+    }
+
+    unsafe fn draw_buffers(&self, buffers: &[u32]) {
+        self.context.draw_buffers(buffers)
+    }
+
+    unsafe fn draw_elements(&self, mode: u32, count: i32, element_type: u32, offset: i32) {
+        self.context
+            .draw_elements(mode, count, element_type, offset)
+    }
+
+    unsafe fn draw_elements_instanced(
+        &self,
+        mode: u32,
+        count: i32,
+        element_type: u32,
+        offset: i32,
+        instance_count: i32,
+    ) {
+        self.context
+            .draw_elements_instanced(mode, count, element_type, offset, instance_count)
+    }
+
+    unsafe fn enable_vertex_attrib_array(&self, index: u32) {
+        self.context.enable_vertex_attrib_array(index)
+    }
+
+    unsafe fn framebuffer_texture_2d(
+        &self,
+        target: u32,
+        attachment: u32,
+        texture_target: u32,
+        texture: Option<TextureId>,
+        level: i32,
+    ) {
+        self.context.framebuffer_texture_2d(
+            target,
+            attachment,
+            texture_target,
+            texture.map(|id| id.0.glow()),
+            level,
+        )
+    }
+
+    unsafe fn generate_mipmap(&self, target: u32) {
+        self.context.generate_mipmap(target)
+    }
+
+    unsafe fn get_parameter_i32(&self, parameter: u32) -> i32 {
+        self.context.get_parameter_i32(parameter)
+    }
+
+    unsafe fn get_parameter_i32_slice(&self, parameter: u32, out: &mut [i32]) {
+        self.context.get_parameter_i32_slice(parameter, out)
+    }
+
+    unsafe fn get_program_info_log(&self, program: ProgramId) -> String {
+        self.context.get_program_info_log(program.0.glow())
+    }
+
+    unsafe fn get_program_link_status(&self, program: ProgramId) -> bool {
+        self.context.get_program_link_status(program.0.glow())
+    }
+
+    unsafe fn get_query_parameter_u32(&self, query: QueryId, parameter: u32) -> u32 {
+        self.context
+            .get_query_parameter_u32(query.0.glow(), parameter)
+    }
+
+    unsafe fn get_shader_compile_status(&self, shader: ShaderId) -> bool {
+        self.context.get_shader_compile_status(shader.0.glow())
+    }
+
+    unsafe fn get_shader_info_log(&self, shader: ShaderId) -> String {
+        self.context.get_shader_info_log(shader.0.glow())
+    }
+
+    unsafe fn get_uniform_block_index(&self, program: ProgramId, name: &str) -> Option<u32> {
+        self.context.get_uniform_block_index(program.0.glow(), name)
+    }
+
+    unsafe fn get_uniform_location(
+        &self,
+        program: ProgramId,
+        name: &str,
+    ) -> Option<UniformLocation> {
+        self.context
+            .get_uniform_location(program.0.glow(), name)
+            .map(|location| UniformLocation(Handle::Glow(location)))
+    }
+
+    unsafe fn invalidate_framebuffer(&self, target: u32, attachments: &[u32]) {
+        self.context.invalidate_framebuffer(target, attachments)
+    }
+
+    unsafe fn line_width(&self, width: f32) {
+        self.context.line_width(width)
+    }
+
+    unsafe fn link_program(&self, program: ProgramId) {
+        self.context.link_program(program.0.glow())
+    }
+
+    unsafe fn pixel_store_i32(&self, parameter: u32, value: i32) {
+        self.context.pixel_store_i32(parameter, value)
+    }
+
+    unsafe fn read_buffer(&self, src: u32) {
+        self.context.read_buffer(src)
+    }
+
+    unsafe fn read_pixels(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        gltype: u32,
+        pixels: PixelPackData,
+    ) {
+        self.context
+            .read_pixels(x, y, width, height, format, gltype, pixels)
+    }
+
+    unsafe fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.context.scissor(x, y, width, height)
+    }
+
+    unsafe fn shader_source(&self, shader: ShaderId, source: &str) {
+        self.context.shader_source(shader.0.glow(), source)
+    }
+
+    unsafe fn tex_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        border: i32,
+        format: u32,
+        ty: u32,
+        pixels: Option<&[u8]>,
+    ) {
+        self.context.tex_image_2d(
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            border,
+            format,
+            ty,
+            pixels,
+        )
+    }
+
+    #[cfg(feature = "web")]
+    unsafe fn tex_image_2d_with_image_bitmap(
+        &self,
+        target: u32,
+        level: i32,
+        internal_format: i32,
+        format: u32,
+        ty: u32,
+        pixels: &web_sys::ImageBitmap,
+    ) {
+        #[cfg(target_arch = "wasm32")]
+        self.context.tex_image_2d_with_image_bitmap(
+            target,
+            level,
+            internal_format,
+            format,
+            ty,
+            pixels,
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = (target, level, internal_format, format, ty, pixels);
+            unreachable!("Image bitmaps are only available in the browser");
+        }
+    }
+
+    unsafe fn tex_parameter_i32(&self, target: u32, parameter: u32, value: i32) {
+        self.context.tex_parameter_i32(target, parameter, value)
+    }
+
+    unsafe fn tex_storage_2d(
+        &self,
+        target: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        self.context
+            .tex_storage_2d(target, levels, internal_format, width, height)
+    }
+
+    unsafe fn tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: u32,
+        ty: u32,
+        pixels: PixelUnpackData,
+    ) {
+        self.context.tex_sub_image_2d(
+            target, level, x_offset, y_offset, width, height, format, ty, pixels,
+        )
+    }
+
+    unsafe fn uniform_1_i32(&self, location: Option<&UniformLocation>, x: i32) {
+        let location = location.map(|location| match &location.0 {
+            Handle::Glow(location) => location,
+            Handle::Mock(_) => panic!("Handle was not created by the glow backend"),
+        });
+
+        self.context.uniform_1_i32(location, x)
+    }
+
+    unsafe fn uniform_block_binding(&self, program: ProgramId, index: u32, binding: u32) {
+        self.context
+            .uniform_block_binding(program.0.glow(), index, binding)
+    }
+
+    unsafe fn use_program(&self, program: Option<ProgramId>) {
+        self.context.use_program(program.map(|id| id.0.glow()))
+    }
+
+    unsafe fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        self.context.vertex_attrib_divisor(index, divisor)
+    }
+
+    unsafe fn vertex_attrib_pointer_f32(
+        &self,
+        index: u32,
+        size: i32,
+        data_type: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i32,
+    ) {
+        self.context
+            .vertex_attrib_pointer_f32(index, size, data_type, normalized, stride, offset)
+    }
+
+    unsafe fn vertex_attrib_pointer_i32(
+        &self,
+        index: u32,
+        size: i32,
+        data_type: u32,
+        stride: i32,
+        offset: i32,
+    ) {
+        self.context
+            .vertex_attrib_pointer_i32(index, size, data_type, stride, offset)
+    }
+
+    unsafe fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.context.viewport(x, y, width, height)
+    }
+}
@@ -0,0 +1,693 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+use glow::{PixelPackData, PixelUnpackData};
+
+use super::backend::{
+    Backend, BufferId, FramebufferId, Handle, ProgramId, QueryId, ShaderId, TextureId,
+    UniformLocation, VertexArrayId,
+};
+
+/// Size of the main framebuffer that is reported by `MockBackend`.
+pub const MOCK_MAIN_VIEWPORT: [i32; 4] = [0, 0, 800, 600];
+
+/// Backend that does not render anything, but records the objects that are
+/// created and the draw calls that are issued.
+///
+/// This is meant for testing rendering code natively. All shaders compile,
+/// all framebuffers are complete, and pixels read back are zero. Float
+/// textures are renderable, as with `EXT_color_buffer_float`.
+pub struct MockBackend {
+    state: RefCell<MockState>,
+    supported_extensions: HashSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockTextureInfo {
+    pub width: i32,
+    pub height: i32,
+    pub internal_format: u32,
+    pub levels: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockDrawCall {
+    pub mode: u32,
+    pub count: i32,
+    pub element_type: u32,
+    pub offset: i32,
+    pub instance_count: Option<i32>,
+
+    pub program: Option<ProgramId>,
+    pub vertex_array: Option<VertexArrayId>,
+    pub framebuffer: Option<FramebufferId>,
+
+    /// Textures that are bound at the time of the draw call, ordered by their
+    /// texture unit.
+    pub textures: Vec<(u32, TextureId)>,
+
+    pub viewport: [i32; 4],
+    pub scissor: Option<[i32; 4]>,
+    pub blend: bool,
+    pub depth_test: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockClear {
+    pub mask: u32,
+    pub framebuffer: Option<FramebufferId>,
+    pub color: [f32; 4],
+    pub depth: f32,
+}
+
+#[derive(Default)]
+struct MockState {
+    next_id: u32,
+
+    buffers: HashMap<u32, Vec<u8>>,
+    textures: HashMap<u32, Option<MockTextureInfo>>,
+    shaders: HashMap<u32, String>,
+    programs: HashMap<u32, Vec<u32>>,
+    framebuffers: HashMap<u32, BTreeMap<u32, u32>>,
+    vertex_arrays: HashSet<u32>,
+    queries: HashSet<u32>,
+
+    bound_buffers: HashMap<u32, u32>,
+    bound_textures: BTreeMap<u32, u32>,
+    active_texture: u32,
+    bound_framebuffer: Option<u32>,
+    bound_vertex_array: Option<u32>,
+    program: Option<u32>,
+
+    viewport: [i32; 4],
+    scissor: [i32; 4],
+    capabilities: HashSet<u32>,
+    clear_color: [f32; 4],
+    clear_depth: f32,
+
+    draw_calls: Vec<MockDrawCall>,
+    clears: Vec<MockClear>,
+}
+
+impl Default for MockBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self {
+            state: RefCell::new(MockState {
+                next_id: 1,
+                viewport: MOCK_MAIN_VIEWPORT,
+                clear_depth: 1.0,
+                ..MockState::default()
+            }),
+            supported_extensions: ["EXT_color_buffer_float".to_owned()].into_iter().collect(),
+        }
+    }
+
+    pub fn draw_calls(&self) -> Vec<MockDrawCall> {
+        self.state.borrow().draw_calls.clone()
+    }
+
+    /// Returns the draw calls that have been recorded since the last call.
+    pub fn take_draw_calls(&self) -> Vec<MockDrawCall> {
+        std::mem::take(&mut self.state.borrow_mut().draw_calls)
+    }
+
+    pub fn clears(&self) -> Vec<MockClear> {
+        self.state.borrow().clears.clone()
+    }
+
+    pub fn take_clears(&self) -> Vec<MockClear> {
+        std::mem::take(&mut self.state.borrow_mut().clears)
+    }
+
+    pub fn num_buffers(&self) -> usize {
+        self.state.borrow().buffers.len()
+    }
+
+    pub fn num_textures(&self) -> usize {
+        self.state.borrow().textures.len()
+    }
+
+    pub fn num_programs(&self) -> usize {
+        self.state.borrow().programs.len()
+    }
+
+    pub fn num_framebuffers(&self) -> usize {
+        self.state.borrow().framebuffers.len()
+    }
+
+    pub fn num_vertex_arrays(&self) -> usize {
+        self.state.borrow().vertex_arrays.len()
+    }
+
+    /// Returns the data that has last been uploaded to a buffer.
+    pub fn buffer_data(&self, buffer: BufferId) -> Option<Vec<u8>> {
+        self.state.borrow().buffers.get(&buffer.0.mock()).cloned()
+    }
+
+    /// Returns the storage of a texture, or `None` if the texture does not
+    /// exist or no storage has been allocated yet.
+    pub fn texture_info(&self, texture: TextureId) -> Option<MockTextureInfo> {
+        self.state
+            .borrow()
+            .textures
+            .get(&texture.0.mock())
+            .cloned()
+            .flatten()
+    }
+
+    /// Returns the textures that are attached to a framebuffer, ordered by
+    /// attachment point.
+    pub fn framebuffer_attachments(&self, framebuffer: FramebufferId) -> Vec<(u32, TextureId)> {
+        self.state
+            .borrow()
+            .framebuffers
+            .get(&framebuffer.0.mock())
+            .map(|attachments| {
+                attachments
+                    .iter()
+                    .map(|(attachment, texture)| (*attachment, TextureId(Handle::Mock(*texture))))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn is_enabled(&self, capability: u32) -> bool {
+        self.state.borrow().capabilities.contains(&capability)
+    }
+}
+
+impl MockState {
+    fn new_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn bound_texture(&self) -> Option<u32> {
+        self.bound_textures.get(&self.active_texture).copied()
+    }
+
+    fn set_texture_info(&mut self, info: MockTextureInfo) {
+        let texture = self
+            .bound_texture()
+            .expect("No texture bound to the active unit");
+        self.textures.insert(texture, Some(info));
+    }
+
+    fn draw(
+        &mut self,
+        mode: u32,
+        count: i32,
+        element_type: u32,
+        offset: i32,
+        instances: Option<i32>,
+    ) {
+        let draw_call = MockDrawCall {
+            mode,
+            count,
+            element_type,
+            offset,
+            instance_count: instances,
+            program: self.program.map(|id| ProgramId(Handle::Mock(id))),
+            vertex_array: self
+                .bound_vertex_array
+                .map(|id| VertexArrayId(Handle::Mock(id))),
+            framebuffer: self
+                .bound_framebuffer
+                .map(|id| FramebufferId(Handle::Mock(id))),
+            textures: self
+                .bound_textures
+                .iter()
+                .map(|(unit, texture)| (*unit, TextureId(Handle::Mock(*texture))))
+                .collect(),
+            viewport: self.viewport,
+            scissor: self.enabled_scissor(),
+            blend: self.capabilities.contains(&glow::BLEND),
+            depth_test: self.capabilities.contains(&glow::DEPTH_TEST),
+        };
+
+        self.draw_calls.push(draw_call);
+    }
+
+    fn enabled_scissor(&self) -> Option<[i32; 4]> {
+        if self.capabilities.contains(&glow::SCISSOR_TEST) {
+            Some(self.scissor)
+        } else {
+            None
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+impl Backend for MockBackend {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn supported_extensions(&self) -> &HashSet<String> {
+        &self.supported_extensions
+    }
+
+    unsafe fn create_buffer(&self) -> Result<BufferId, String> {
+        let mut state = self.state.borrow_mut();
+        let id = state.new_id();
+        state.buffers.insert(id, Vec::new());
+        Ok(BufferId(Handle::Mock(id)))
+    }
+
+    unsafe fn create_framebuffer(&self) -> Result<FramebufferId, String> {
+        let mut state = self.state.borrow_mut();
+        let id = state.new_id();
+        state.framebuffers.insert(id, BTreeMap::new());
+        Ok(FramebufferId(Handle::Mock(id)))
+    }
+
+    unsafe fn create_program(&self) -> Result<ProgramId, String> {
+        let mut state = self.state.borrow_mut();
+        let id = state.new_id();
+        state.programs.insert(id, Vec::new());
+        Ok(ProgramId(Handle::Mock(id)))
+    }
+
+    unsafe fn create_query(&self) -> Result<QueryId, String> {
+        let mut state = self.state.borrow_mut();
+        let id = state.new_id();
+        state.queries.insert(id);
+        Ok(QueryId(Handle::Mock(id)))
+    }
+
+    unsafe fn create_shader(&self, _: u32) -> Result<ShaderId, String> {
+        let mut state = self.state.borrow_mut();
+        let id = state.new_id();
+        state.shaders.insert(id, String::new());
+        Ok(ShaderId(Handle::Mock(id)))
+    }
+
+    unsafe fn create_texture(&self) -> Result<TextureId, String> {
+        let mut state = self.state.borrow_mut();
+        let id = state.new_id();
+        state.textures.insert(id, None);
+        Ok(TextureId(Handle::Mock(id)))
+    }
+
+    unsafe fn create_vertex_array(&self) -> Result<VertexArrayId, String> {
+        let mut state = self.state.borrow_mut();
+        let id = state.new_id();
+        state.vertex_arrays.insert(id);
+        Ok(VertexArrayId(Handle::Mock(id)))
+    }
+
+    unsafe fn delete_buffer(&self, buffer: BufferId) {
+        let mut state = self.state.borrow_mut();
+        let id = buffer.0.mock();
+        state.buffers.remove(&id);
+        state.bound_buffers.retain(|_, bound| *bound != id);
+    }
+
+    unsafe fn delete_framebuffer(&self, framebuffer: FramebufferId) {
+        let mut state = self.state.borrow_mut();
+        let id = framebuffer.0.mock();
+        state.framebuffers.remove(&id);
+        if state.bound_framebuffer == Some(id) {
+            state.bound_framebuffer = None;
+        }
+    }
+
+    unsafe fn delete_program(&self, program: ProgramId) {
+        let mut state = self.state.borrow_mut();
+        let id = program.0.mock();
+        state.programs.remove(&id);
+        if state.program == Some(id) {
+            state.program = None;
+        }
+    }
+
+    unsafe fn delete_query(&self, query: QueryId) {
+        self.state.borrow_mut().queries.remove(&query.0.mock());
+    }
+
+    unsafe fn delete_shader(&self, shader: ShaderId) {
+        self.state.borrow_mut().shaders.remove(&shader.0.mock());
+    }
+
+    unsafe fn delete_texture(&self, texture: TextureId) {
+        let mut state = self.state.borrow_mut();
+        let id = texture.0.mock();
+        state.textures.remove(&id);
+        state.bound_textures.retain(|_, bound| *bound != id);
+        for attachments in state.framebuffers.values_mut() {
+            attachments.retain(|_, attached| *attached != id);
+        }
+    }
+
+    unsafe fn delete_vertex_array(&self, vertex_array: VertexArrayId) {
+        let mut state = self.state.borrow_mut();
+        let id = vertex_array.0.mock();
+        state.vertex_arrays.remove(&id);
+        if state.bound_vertex_array == Some(id) {
+            state.bound_vertex_array = None;
+        }
+    }
+
+    unsafe fn active_texture(&self, unit: u32) {
+        self.state.borrow_mut().active_texture = unit - glow::TEXTURE0;
+    }
+
+    unsafe fn attach_shader(&self, program: ProgramId, shader: ShaderId) {
+        if let Some(shaders) = self.state.borrow_mut().programs.get_mut(&program.0.mock()) {
+            shaders.push(shader.0.mock());
+        }
+    }
+
+    unsafe fn detach_shader(&self, program: ProgramId, shader: ShaderId) {
+        if let Some(shaders) = self.state.borrow_mut().programs.get_mut(&program.0.mock()) {
+            shaders.retain(|id| *id != shader.0.mock());
+        }
+    }
+
+    unsafe fn begin_query(&self, _: u32, _: QueryId) {}
+
+    unsafe fn end_query(&self, _: u32) {}
+
+    unsafe fn bind_attrib_location(&self, _: ProgramId, _: u32, _: &str) {}
+
+    unsafe fn bind_buffer(&self, target: u32, buffer: Option<BufferId>) {
+        let mut state = self.state.borrow_mut();
+        match buffer {
+            Some(buffer) => state.bound_buffers.insert(target, buffer.0.mock()),
+            None => state.bound_buffers.remove(&target),
+        };
+    }
+
+    unsafe fn bind_buffer_base(&self, _: u32, _: u32, _: Option<BufferId>) {}
+
+    unsafe fn bind_framebuffer(&self, _: u32, framebuffer: Option<FramebufferId>) {
+        self.state.borrow_mut().bound_framebuffer = framebuffer.map(|id| id.0.mock());
+    }
+
+    unsafe fn bind_texture(&self, _: u32, texture: Option<TextureId>) {
+        let mut state = self.state.borrow_mut();
+        let unit = state.active_texture;
+        match texture {
+            Some(texture) => state.bound_textures.insert(unit, texture.0.mock()),
+            None => state.bound_textures.remove(&unit),
+        };
+    }
+
+    unsafe fn bind_vertex_array(&self, vertex_array: Option<VertexArrayId>) {
+        self.state.borrow_mut().bound_vertex_array = vertex_array.map(|id| id.0.mock());
+    }
+
+    unsafe fn blend_color(&self, _: f32, _: f32, _: f32, _: f32) {}
+
+    unsafe fn blend_equation(&self, _: u32) {}
+
+    unsafe fn blend_equation_separate(&self, _: u32, _: u32) {}
+
+    unsafe fn blend_func(&self, _: u32, _: u32) {}
+
+    unsafe fn blend_func_separate(&self, _: u32, _: u32, _: u32, _: u32) {}
+
+    unsafe fn buffer_data_u8_slice(&self, target: u32, data: &[u8], _: u32) {
+        let mut state = self.state.borrow_mut();
+        let buffer = *state
+            .bound_buffers
+            .get(&target)
+            .expect("No buffer bound to target");
+        state.buffers.insert(buffer, data.to_vec());
+    }
+
+    unsafe fn clear(&self, mask: u32) {
+        let mut state = self.state.borrow_mut();
+        let clear = MockClear {
+            mask,
+            framebuffer: state
+                .bound_framebuffer
+                .map(|id| FramebufferId(Handle::Mock(id))),
+            color: state.clear_color,
+            depth: state.clear_depth,
+        };
+        state.clears.push(clear);
+    }
+
+    unsafe fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+        self.state.borrow_mut().clear_color = [red, green, blue, alpha];
+    }
+
+    unsafe fn clear_depth_f32(&self, depth: f32) {
+        self.state.borrow_mut().clear_depth = depth;
+    }
+
+    unsafe fn color_mask(&self, _: bool, _: bool, _: bool, _: bool) {}
+
+    unsafe fn compile_shader(&self, _: ShaderId) {}
+
+    unsafe fn depth_func(&self, _: u32) {}
+
+    unsafe fn depth_mask(&self, _: bool) {}
+
+    unsafe fn depth_range_f32(&self, _: f32, _: f32) {}
+
+    unsafe fn disable(&self, parameter: u32) {
+        self.state.borrow_mut().capabilities.remove(&parameter);
+    }
+
+    unsafe fn enable(&self, parameter: u32) {
+        self.state.borrow_mut().capabilities.insert(parameter);
+    }
+
+    unsafe fn draw_buffers(&self, _: &[u32]) {}
+
+    unsafe fn draw_elements(&self, mode: u32, count: i32, element_type: u32, offset: i32) {
+        self.state
+            .borrow_mut()
+            .draw(mode, count, element_type, offset, None);
+    }
+
+    unsafe fn draw_elements_instanced(
+        &self,
+        mode: u32,
+        count: i32,
+        element_type: u32,
+        offset: i32,
+        instance_count: i32,
+    ) {
+        self.state
+            .borrow_mut()
+            .draw(mode, count, element_type, offset, Some(instance_count));
+    }
+
+    unsafe fn enable_vertex_attrib_array(&self, _: u32) {}
+
+    unsafe fn framebuffer_texture_2d(
+        &self,
+        _: u32,
+        attachment: u32,
+        _: u32,
+        texture: Option<TextureId>,
+        _: i32,
+    ) {
+        let mut state = self.state.borrow_mut();
+        let framebuffer = state
+            .bound_framebuffer
+            .expect("No framebuffer bound for attachment");
+        let attachments = state.framebuffers.entry(framebuffer).or_default();
+
+        match texture {
+            Some(texture) => attachments.insert(attachment, texture.0.mock()),
+            None => attachments.remove(&attachment),
+        };
+    }
+
+    unsafe fn generate_mipmap(&self, _: u32) {}
+
+    unsafe fn get_parameter_i32(&self, parameter: u32) -> i32 {
+        match parameter {
+            glow::MAX_TEXTURE_SIZE => 4096,
+            glow::MAX_COLOR_ATTACHMENTS | glow::MAX_DRAW_BUFFERS => 8,
+            _ => 0,
+        }
+    }
+
+    unsafe fn get_parameter_i32_slice(&self, parameter: u32, out: &mut [i32]) {
+        let state = self.state.borrow();
+        let value = match parameter {
+            glow::VIEWPORT => state.viewport,
+            glow::SCISSOR_BOX => state.scissor,
+            _ => [0; 4],
+        };
+
+        for (out, value) in out.iter_mut().zip(value.iter()) {
+            *out = *value;
+        }
+    }
+
+    unsafe fn get_program_info_log(&self, _: ProgramId) -> String {
+        String::new()
+    }
+
+    unsafe fn get_program_link_status(&self, _: ProgramId) -> bool {
+        true
+    }
+
+    unsafe fn get_query_parameter_u32(&self, _: QueryId, parameter: u32) -> u32 {
+        match parameter {
+            glow::QUERY_RESULT_AVAILABLE => 1,
+            _ => 0,
+        }
+    }
+
+    unsafe fn get_shader_compile_status(&self, _: ShaderId) -> bool {
+        true
+    }
+
+    unsafe fn get_shader_info_log(&self, _: ShaderId) -> String {
+        String::new()
+    }
+
+    unsafe fn get_uniform_block_index(&self, _: ProgramId, _: &str) -> Option<u32> {
+        Some(0)
+    }
+
+    unsafe fn get_uniform_location(&self, _: ProgramId, _: &str) -> Option<UniformLocation> {
+        let id = self.state.borrow_mut().new_id();
+        Some(UniformLocation(Handle::Mock(id)))
+    }
+
+    unsafe fn invalidate_framebuffer(&self, _: u32, _: &[u32]) {}
+
+    unsafe fn line_width(&self, _: f32) {}
+
+    unsafe fn link_program(&self, _: ProgramId) {}
+
+    unsafe fn pixel_store_i32(&self, _: u32, _: i32) {}
+
+    unsafe fn read_buffer(&self, _: u32) {}
+
+    unsafe fn read_pixels(
+        &self,
+        _: i32,
+        _: i32,
+        _: i32,
+        _: i32,
+        _: u32,
+        _: u32,
+        pixels: PixelPackData,
+    ) {
+        if let PixelPackData::Slice(data) = pixels {
+            data.iter_mut().for_each(|x| *x = 0);
+        }
+    }
+
+    unsafe fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.state.borrow_mut().scissor = [x, y, width, height];
+    }
+
+    unsafe fn shader_source(&self, shader: ShaderId, source: &str) {
+        self.state
+            .borrow_mut()
+            .shaders
+            .insert(shader.0.mock(), source.to_owned());
+    }
+
+    unsafe fn tex_image_2d(
+        &self,
+        _: u32,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        _: i32,
+        _: u32,
+        _: u32,
+        _: Option<&[u8]>,
+    ) {
+        if level == 0 {
+            self.state.borrow_mut().set_texture_info(MockTextureInfo {
+                width,
+                height,
+                internal_format: internal_format as u32,
+                levels: 1,
+            });
+        }
+    }
+
+    #[cfg(feature = "web")]
+    unsafe fn tex_image_2d_with_image_bitmap(
+        &self,
+        _: u32,
+        level: i32,
+        internal_format: i32,
+        _: u32,
+        _: u32,
+        pixels: &web_sys::ImageBitmap,
+    ) {
+        if level == 0 {
+            self.state.borrow_mut().set_texture_info(MockTextureInfo {
+                width: pixels.width() as i32,
+                height: pixels.height() as i32,
+                internal_format: internal_format as u32,
+                levels: 1,
+            });
+        }
+    }
+
+    unsafe fn tex_parameter_i32(&self, _: u32, _: u32, _: i32) {}
+
+    unsafe fn tex_storage_2d(
+        &self,
+        _: u32,
+        levels: i32,
+        internal_format: u32,
+        width: i32,
+        height: i32,
+    ) {
+        self.state.borrow_mut().set_texture_info(MockTextureInfo {
+            width,
+            height,
+            internal_format,
+            levels,
+        });
+    }
+
+    unsafe fn tex_sub_image_2d(
+        &self,
+        _: u32,
+        _: i32,
+        _: i32,
+        _: i32,
+        _: i32,
+        _: i32,
+        _: u32,
+        _: u32,
+        _: PixelUnpackData,
+    ) {
+    }
+
+    unsafe fn uniform_1_i32(&self, _: Option<&UniformLocation>, _: i32) {}
+
+    unsafe fn uniform_block_binding(&self, _: ProgramId, _: u32, _: u32) {}
+
+    unsafe fn use_program(&self, program: Option<ProgramId>) {
+        self.state.borrow_mut().program = program.map(|id| id.0.mock());
+    }
+
+    unsafe fn vertex_attrib_divisor(&self, _: u32, _: u32) {}
+
+    unsafe fn vertex_attrib_pointer_f32(&self, _: u32, _: i32, _: u32, _: bool, _: i32, _: i32) {}
+
+    unsafe fn vertex_attrib_pointer_i32(&self, _: u32, _: i32, _: u32, _: i32, _: i32) {}
+
+    unsafe fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.state.borrow_mut().viewport = [x, y, width, height];
+    }
+}
@@ -4,7 +4,9 @@ mod vertex;
 #[macro_use]
 mod program;
 
+mod backend;
 mod blend;
+mod command;
 mod context;
 mod depth_test;
 mod draw;
@@ -13,33 +15,53 @@ mod draw_timer;
 mod draw_unit;
 mod element_buffer;
 mod error;
+mod frame_stats;
 mod framebuffer;
+mod glow_backend;
+mod mock;
+mod resources;
+mod restore;
+mod state_cache;
 mod texture;
 mod uniform;
 mod uniform_block;
 mod vertex_array;
 mod vertex_buffer;
 
+pub use backend::{
+    Backend, BufferId, FramebufferId, ProgramId, QueryId, ShaderId, TextureId, UniformLocation,
+    VertexArrayId,
+};
 pub use blend::{Blend, BlendEquation, BlendFactor, BlendFunc, BlendOp};
+pub use command::{record, CommandList, RenderCommand};
 pub use context::Context;
 pub use depth_test::{DepthFunc, DepthTest};
 pub use draw::{
     clear_color, clear_color_and_depth, clear_depth, draw, draw_instanced, with_framebuffer,
-    with_framebuffer_invalidating,
+    with_framebuffer_invalidating, with_viewport,
 };
 pub use draw_params::DrawParams;
 pub use draw_timer::{DrawTimer, DrawTimingInfo};
 pub use draw_unit::{DrawUnit, InstancedDrawUnit, PrimitiveMode};
 pub use element_buffer::{Element, ElementBuffer};
 pub use error::Error;
+pub use frame_stats::FrameStats;
 pub use framebuffer::{Framebuffer, NewFramebufferError};
+pub use glow_backend::GlowBackend;
+pub use mock::{MockBackend, MockClear, MockDrawCall, MockTextureInfo, MOCK_MAIN_VIEWPORT};
 pub use program::{Glsl, Program, ProgramDef};
+pub use resources::{ResourceId, ResourceInfo, ResourceTotals};
+pub use state_cache::{StateCacheStats, StateCounter};
+#[cfg(feature = "web")]
+pub use texture::LoadTextureError;
 pub use texture::{
-    LoadTextureError, NewTextureError, Texture, TextureMagFilter, TextureMinFilter, TextureParams,
+    NewTextureError, PixelComponent, Texture, TextureMagFilter, TextureMinFilter, TextureParams,
     TextureValueType, TextureWrap,
 };
 pub use uniform::Uniform;
 pub use uniform_block::{UniformBlock, UniformDecls};
-pub use vertex::{Attribute, AttributeValueType, DataType, Vertex, VertexDecls};
+pub use vertex::{
+    Attribute, AttributeValueType, DataType, Half2, Half4, Unorm16x2, Unorm8x4, Vertex, VertexDecls,
+};
 pub use vertex_array::VertexArray;
 pub use vertex_buffer::VertexBuffer;
@@ -1,12 +1,15 @@
 use std::{marker::PhantomData, rc::Rc};
 
-use glow::HasContext;
+use super::{
+    restore::RestorableId, vertex::VertexDecls, Attribute, Context, Error, ProgramId, UniformDecls,
+};
 
-use super::{vertex::VertexDecls, Attribute, Context, Error, UniformDecls};
+type RebuildProgram = Box<dyn Fn(&Context) -> Result<ProgramId, Error>>;
 
 pub struct Program<U, V, const S: usize> {
     gl: Rc<Context>,
-    id: glow::Program,
+    id: RestorableId<ProgramId>,
+    rebuild: RebuildProgram,
     uniform_block_bindings: Vec<u32>,
     _phantom: PhantomData<(U, V)>,
 }
@@ -16,8 +19,8 @@ impl<U, V, const S: usize> Program<U, V, S> {
         self.gl.clone()
     }
 
-    pub fn id(&self) -> glow::Program {
-        self.id
+    pub fn id(&self) -> ProgramId {
+        self.id.get(&self.gl, |_| (self.rebuild)(&self.gl))
     }
 
     pub fn uniform_block_bindings(&self) -> &[u32] {
@@ -25,9 +28,7 @@ impl<U, V, const S: usize> Program<U, V, S> {
     }
 
     pub fn bind(&self) {
-        unsafe {
-            self.gl.use_program(Some(self.id));
-        }
+        self.gl.set_program(Some(self.id()));
     }
 }
 
@@ -43,8 +44,8 @@ pub struct ProgramDef<const N: usize, const S: usize, const A: usize> {
 
 impl<U, V, const S: usize> Program<U, V, S>
 where
-    U: UniformDecls,
-    V: VertexDecls,
+    U: UniformDecls + 'static,
+    V: VertexDecls + 'static,
 {
     pub fn new<const N: usize, const A: usize>(
         gl: Rc<Context>,
@@ -56,11 +57,16 @@ where
         assert!(A == V::N);
 
         let id = create_program::<U, V, N, S, A>(&*gl, &def)?;
+        let uniform_block_bindings = def.uniforms.iter().map(|(_, b)| *b).collect();
+
+        // Keep the definition around for recompiling after context loss.
+        let rebuild = Box::new(move |gl: &Context| create_program::<U, V, N, S, A>(gl, &def));
 
         Ok(Self {
+            id: RestorableId::new(&gl, id),
             gl,
-            id,
-            uniform_block_bindings: def.uniforms.iter().map(|(_, b)| *b).collect(),
+            rebuild,
+            uniform_block_bindings,
             _phantom: PhantomData,
         })
     }
@@ -131,7 +137,7 @@ where
 fn create_program<U, V, const N: usize, const S: usize, const A: usize>(
     gl: &Context,
     def: &ProgramDef<N, S, A>,
-) -> Result<glow::Program, Error>
+) -> Result<ProgramId, Error>
 where
     U: UniformDecls,
     V: VertexDecls,
@@ -210,9 +216,7 @@ where
     }
 
     // Set texture uniforms.
-    unsafe {
-        gl.use_program(Some(program));
-    }
+    gl.set_program(Some(program));
     for (i, sampler) in def.samplers.iter().enumerate() {
         if let Some(location) = unsafe { gl.get_uniform_location(program, sampler) } {
             unsafe {
@@ -252,8 +256,10 @@ fn vertex_source_header(attributes: &[Attribute]) -> String {
 
 impl<U, V, const S: usize> Drop for Program<U, V, S> {
     fn drop(&mut self) {
+        let id = self.id.current();
+        self.gl.forget_program(id);
         unsafe {
-            self.gl.delete_program(self.id);
+            self.gl.delete_program(id);
         }
     }
 }
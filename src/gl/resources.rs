@@ -0,0 +1,218 @@
+use std::{collections::HashMap, fmt::Write};
+
+use serde::Serialize;
+
+use super::{BufferId, Context, FramebufferId, TextureId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ResourceId {
+    Texture(TextureId),
+    Buffer(BufferId),
+    Framebuffer(FramebufferId),
+}
+
+/// A live GL object, as tracked by `Context`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResourceInfo {
+    pub id: ResourceId,
+    pub label: Option<String>,
+    pub description: String,
+
+    /// Estimated GPU memory used by the object. Framebuffers do not own any
+    /// memory themselves, their attachments are accounted for as textures.
+    pub size_bytes: usize,
+
+    /// Number of resources that have been created before this one.
+    pub serial: usize,
+}
+
+/// Number of live resources and their estimated memory use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ResourceTotals {
+    pub num_textures: usize,
+    pub texture_bytes: usize,
+    pub num_buffers: usize,
+    pub buffer_bytes: usize,
+    pub num_framebuffers: usize,
+}
+
+#[derive(Default)]
+pub(super) struct ResourceRegistry {
+    resources: HashMap<ResourceId, ResourceInfo>,
+    next_serial: usize,
+}
+
+impl From<TextureId> for ResourceId {
+    fn from(id: TextureId) -> Self {
+        ResourceId::Texture(id)
+    }
+}
+
+impl From<BufferId> for ResourceId {
+    fn from(id: BufferId) -> Self {
+        ResourceId::Buffer(id)
+    }
+}
+
+impl From<FramebufferId> for ResourceId {
+    fn from(id: FramebufferId) -> Self {
+        ResourceId::Framebuffer(id)
+    }
+}
+
+impl ResourceTotals {
+    pub fn total_bytes(&self) -> usize {
+        self.texture_bytes + self.buffer_bytes
+    }
+}
+
+impl Context {
+    /// Returns all live resources, in the order in which they have been
+    /// created.
+    pub fn resources(&self) -> Vec<ResourceInfo> {
+        let mut resources: Vec<_> = self
+            .resources
+            .borrow()
+            .resources
+            .values()
+            .cloned()
+            .collect();
+        resources.sort_by_key(|info| info.serial);
+        resources
+    }
+
+    pub fn resource(&self, id: impl Into<ResourceId>) -> Option<ResourceInfo> {
+        self.resources.borrow().resources.get(&id.into()).cloned()
+    }
+
+    pub fn resource_totals(&self) -> ResourceTotals {
+        let mut totals = ResourceTotals::default();
+
+        for info in self.resources.borrow().resources.values() {
+            match info.id {
+                ResourceId::Texture(_) => {
+                    totals.num_textures += 1;
+                    totals.texture_bytes += info.size_bytes;
+                }
+                ResourceId::Buffer(_) => {
+                    totals.num_buffers += 1;
+                    totals.buffer_bytes += info.size_bytes;
+                }
+                ResourceId::Framebuffer(_) => {
+                    totals.num_framebuffers += 1;
+                }
+            }
+        }
+
+        totals
+    }
+
+    /// Attaches a label to a resource, so that it can be recognized in
+    /// `resource_dump`.
+    pub fn set_resource_label(&self, id: impl Into<ResourceId>, label: &str) {
+        if let Some(info) = self.resources.borrow_mut().resources.get_mut(&id.into()) {
+            info.label = Some(label.to_owned());
+        }
+    }
+
+    /// Returns a human-readable listing of all live resources, largest first,
+    /// followed by the totals.
+    pub fn resource_dump(&self) -> String {
+        let mut resources = self.resources();
+        resources.sort_by(|a, b| {
+            b.size_bytes
+                .cmp(&a.size_bytes)
+                .then(a.serial.cmp(&b.serial))
+        });
+
+        let mut dump = String::new();
+        for info in resources {
+            let _ = writeln!(
+                dump,
+                "{:>10} {:<30} {} (#{}, {:?})",
+                format_bytes(info.size_bytes),
+                info.label.as_deref().unwrap_or("<unlabeled>"),
+                info.description,
+                info.serial,
+                info.id,
+            );
+        }
+
+        let totals = self.resource_totals();
+        let _ = write!(
+            dump,
+            "{} textures ({}), {} buffers ({}), {} framebuffers, total {}",
+            totals.num_textures,
+            format_bytes(totals.texture_bytes),
+            totals.num_buffers,
+            format_bytes(totals.buffer_bytes),
+            totals.num_framebuffers,
+            format_bytes(totals.total_bytes()),
+        );
+
+        dump
+    }
+
+    pub(super) fn register_resource(
+        &self,
+        id: impl Into<ResourceId>,
+        description: String,
+        size_bytes: usize,
+    ) {
+        let id = id.into();
+        let mut registry = self.resources.borrow_mut();
+        let serial = registry.next_serial;
+        registry.next_serial += 1;
+
+        registry.resources.insert(
+            id,
+            ResourceInfo {
+                id,
+                label: None,
+                description,
+                size_bytes,
+                serial,
+            },
+        );
+    }
+
+    pub(super) fn set_resource_size(&self, id: impl Into<ResourceId>, size_bytes: usize) {
+        if let Some(info) = self.resources.borrow_mut().resources.get_mut(&id.into()) {
+            info.size_bytes = size_bytes;
+        }
+    }
+
+    /// Moves the entry of a resource that has been recreated after the
+    /// context was restored.
+    pub(super) fn rename_resource(
+        &self,
+        old_id: impl Into<ResourceId>,
+        new_id: impl Into<ResourceId>,
+    ) {
+        let new_id = new_id.into();
+        let mut registry = self.resources.borrow_mut();
+
+        if let Some(mut info) = registry.resources.remove(&old_id.into()) {
+            info.id = new_id;
+            registry.resources.insert(new_id, info);
+        }
+    }
+
+    pub(super) fn unregister_resource(&self, id: impl Into<ResourceId>) {
+        self.resources.borrow_mut().resources.remove(&id.into());
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const KIB: f64 = 1024.0;
+    const MIB: f64 = 1024.0 * 1024.0;
+
+    let bytes_f64 = bytes as f64;
+    if bytes_f64 >= MIB {
+        format!("{:.1} MiB", bytes_f64 / MIB)
+    } else if bytes_f64 >= KIB {
+        format!("{:.1} KiB", bytes_f64 / KIB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
@@ -0,0 +1,118 @@
+use std::cell::Cell;
+
+use super::{BufferId, Context, Error};
+
+/// Id of a GL object that is recreated on demand after the context has been
+/// lost and restored.
+///
+/// The object keeps whatever data it needs for recreating itself, and passes
+/// a closure for doing so to `get`. This way, objects only need to be
+/// restored once they are used again, and objects that depend on others
+/// (e.g. a `VertexArray` on its buffers) can restore their dependencies first
+/// simply by asking for their ids.
+pub(super) struct RestorableId<T> {
+    id: Cell<T>,
+    generation: Cell<u64>,
+}
+
+impl<T: Copy> RestorableId<T> {
+    pub fn new(gl: &Context, id: T) -> Self {
+        Self {
+            id: Cell::new(id),
+            generation: Cell::new(gl.generation()),
+        }
+    }
+
+    /// Returns the id, recreating the object first if it belongs to an
+    /// earlier generation of the context. `restore` is given the old id.
+    ///
+    /// If restoring fails, the error is logged and the old id is returned,
+    /// so that restoring is tried again on the next use.
+    pub fn get(&self, gl: &Context, restore: impl FnOnce(T) -> Result<T, Error>) -> T {
+        if self.generation.get() != gl.generation() && !gl.is_lost() {
+            match restore(self.id.get()) {
+                Ok(id) => {
+                    self.id.set(id);
+                    self.generation.set(gl.generation());
+                }
+                Err(err) => {
+                    log::warn!("Failed to restore GL object: {}", err);
+                }
+            }
+        }
+
+        self.id.get()
+    }
+
+    /// Returns the id without restoring the object, e.g. for deleting it.
+    pub fn current(&self) -> T {
+        self.id.get()
+    }
+}
+
+/// Recreates a buffer with the data that has been kept for it, which is empty
+/// for streamed buffers.
+pub(super) fn restore_buffer(
+    gl: &Context,
+    old_id: BufferId,
+    target: u32,
+    data: &[u8],
+    usage: u32,
+) -> Result<BufferId, Error> {
+    let id = unsafe { gl.create_buffer() }.map_err(Error::Glow)?;
+
+    if target == glow::ELEMENT_ARRAY_BUFFER {
+        gl.set_vertex_array(None);
+    }
+
+    unsafe {
+        gl.bind_buffer(target, Some(id));
+        gl.buffer_data_u8_slice(target, data, usage);
+    }
+
+    gl.rename_resource(old_id, id);
+    gl.set_resource_size(id, data.len());
+
+    Ok(id)
+}
+
+impl Context {
+    /// Returns true if the browser has taken away the WebGL context.
+    ///
+    /// While the context is lost, submitted commands are dropped.
+    pub fn is_lost(&self) -> bool {
+        self.lost.get()
+    }
+
+    /// Number of times the context has been restored after having been lost.
+    pub fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    /// Called by the `webglcontextlost` listener of `Canvas`.
+    pub fn mark_lost(&self) {
+        if self.lost.replace(true) {
+            return;
+        }
+
+        log::warn!("WebGL context lost");
+    }
+
+    /// Called by the `webglcontextrestored` listener of `Canvas`.
+    ///
+    /// All objects that have been created before are invalid now. They are
+    /// recreated the next time they are used.
+    pub fn mark_restored(&self) {
+        if !self.lost.replace(false) {
+            return;
+        }
+
+        log::info!("WebGL context restored");
+
+        self.generation.set(self.generation.get() + 1);
+        self.invalidate_state_cache();
+
+        self.set_viewport(self.main_viewport.get());
+        self.apply_main_scissor();
+    }
+}
@@ -0,0 +1,480 @@
+use crate::Color4;
+
+use super::{
+    BlendEquation, BlendFunc, BufferId, Context, DepthFunc, FramebufferId, ProgramId, TextureId,
+    VertexArrayId,
+};
+
+/// Number of state changes that have been passed on to the backend, and
+/// number of state changes that have been skipped since the state was
+/// already set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCounter {
+    pub issued: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCacheStats {
+    pub program: StateCounter,
+    pub vertex_array: StateCounter,
+    pub framebuffer: StateCounter,
+    pub texture: StateCounter,
+    pub uniform_buffer: StateCounter,
+    pub capability: StateCounter,
+    pub blend: StateCounter,
+    pub depth: StateCounter,
+    pub color_mask: StateCounter,
+    pub line_width: StateCounter,
+    pub viewport: StateCounter,
+    pub scissor: StateCounter,
+}
+
+/// Remembers the GL state that has been set through `Context`, so that
+/// redundant calls can be skipped.
+///
+/// A value of `None` means that the state is unknown, in which case the next
+/// change is always passed on.
+#[derive(Default)]
+pub(super) struct StateCache {
+    enabled: bool,
+    stats: StateCacheStats,
+
+    program: Option<Option<ProgramId>>,
+    vertex_array: Option<Option<VertexArrayId>>,
+    framebuffer: Option<Option<FramebufferId>>,
+    active_texture: Option<u32>,
+    textures: Vec<Option<Option<TextureId>>>,
+    uniform_buffers: Vec<Option<BufferId>>,
+
+    blend_enabled: Option<bool>,
+    depth_test_enabled: Option<bool>,
+    scissor_test_enabled: Option<bool>,
+
+    blend_equation: Option<BlendEquation>,
+    blend_func: Option<BlendFunc>,
+    blend_color: Option<Color4>,
+    depth_func: Option<DepthFunc>,
+    depth_range: Option<(f32, f32)>,
+    depth_mask: Option<bool>,
+    color_mask: Option<(bool, bool, bool, bool)>,
+    line_width: Option<f32>,
+    viewport: Option<[i32; 4]>,
+    scissor: Option<[i32; 4]>,
+}
+
+impl StateCounter {
+    pub fn total(&self) -> usize {
+        self.issued + self.skipped
+    }
+}
+
+impl StateCacheStats {
+    pub fn counters(&self) -> [(&'static str, StateCounter); 12] {
+        [
+            ("program", self.program),
+            ("vertex_array", self.vertex_array),
+            ("framebuffer", self.framebuffer),
+            ("texture", self.texture),
+            ("uniform_buffer", self.uniform_buffer),
+            ("capability", self.capability),
+            ("blend", self.blend),
+            ("depth", self.depth),
+            ("color_mask", self.color_mask),
+            ("line_width", self.line_width),
+            ("viewport", self.viewport),
+            ("scissor", self.scissor),
+        ]
+    }
+
+    pub fn issued(&self) -> usize {
+        self.counters()
+            .iter()
+            .map(|(_, counter)| counter.issued)
+            .sum()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.counters()
+            .iter()
+            .map(|(_, counter)| counter.skipped)
+            .sum()
+    }
+}
+
+impl StateCache {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            ..Self::default()
+        }
+    }
+
+    /// Forgets all state, e.g. after GL has been used without going through
+    /// `Context`.
+    fn invalidate(&mut self) {
+        *self = Self {
+            enabled: self.enabled,
+            stats: self.stats,
+            ..Self::default()
+        };
+    }
+}
+
+/// Updates a cached value and returns true if the state change needs to be
+/// passed on to the backend.
+fn update<T: PartialEq>(
+    enabled: bool,
+    cached: &mut Option<T>,
+    value: T,
+    counter: &mut StateCounter,
+) -> bool {
+    if enabled && cached.as_ref() == Some(&value) {
+        counter.skipped += 1;
+        false
+    } else {
+        *cached = Some(value);
+        counter.issued += 1;
+        true
+    }
+}
+
+fn slot<T>(slots: &mut Vec<Option<T>>, index: u32) -> &mut Option<T> {
+    let index = index as usize;
+    if slots.len() <= index {
+        slots.resize_with(index + 1, || None);
+    }
+
+    &mut slots[index]
+}
+
+impl Context {
+    pub fn state_cache_stats(&self) -> StateCacheStats {
+        self.state.borrow().stats
+    }
+
+    pub fn reset_state_cache_stats(&self) {
+        self.state.borrow_mut().stats = StateCacheStats::default();
+    }
+
+    /// Disabling the cache passes on every state change, which is useful for
+    /// measuring its effect.
+    pub fn set_state_cache_enabled(&self, enabled: bool) {
+        let mut state = self.state.borrow_mut();
+        state.enabled = enabled;
+        state.invalidate();
+    }
+
+    /// Needs to be called after changing GL state directly through the
+    /// backend.
+    pub fn invalidate_state_cache(&self) {
+        self.state.borrow_mut().invalidate();
+    }
+
+    pub(super) fn set_program(&self, program: Option<ProgramId>) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.program,
+            program,
+            &mut state.stats.program,
+        ) {
+            unsafe {
+                self.backend().use_program(program);
+            }
+            self.count_frame_stats(|stats| stats.program_switches += 1);
+        }
+    }
+
+    pub(super) fn set_vertex_array(&self, vertex_array: Option<VertexArrayId>) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.vertex_array,
+            vertex_array,
+            &mut state.stats.vertex_array,
+        ) {
+            unsafe {
+                self.backend().bind_vertex_array(vertex_array);
+            }
+        }
+    }
+
+    /// Returns the framebuffer that has last been bound through
+    /// `set_framebuffer`. This is `None` for the default framebuffer, but also
+    /// if the binding is unknown, e.g. after context loss.
+    pub(super) fn current_framebuffer(&self) -> Option<FramebufferId> {
+        self.state.borrow().framebuffer.flatten()
+    }
+
+    pub(super) fn set_framebuffer(&self, framebuffer: Option<FramebufferId>) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.framebuffer,
+            framebuffer,
+            &mut state.stats.framebuffer,
+        ) {
+            unsafe {
+                self.backend()
+                    .bind_framebuffer(glow::FRAMEBUFFER, framebuffer);
+            }
+            self.count_frame_stats(|stats| stats.framebuffer_switches += 1);
+        }
+    }
+
+    pub(super) fn set_texture(&self, unit: u32, texture: Option<TextureId>) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            slot(&mut state.textures, unit),
+            texture,
+            &mut state.stats.texture,
+        ) {
+            if state.active_texture != Some(unit) || !state.enabled {
+                state.active_texture = Some(unit);
+                unsafe {
+                    self.backend().active_texture(glow::TEXTURE0 + unit);
+                }
+            }
+
+            unsafe {
+                self.backend().bind_texture(glow::TEXTURE_2D, texture);
+            }
+        }
+    }
+
+    pub(super) fn set_uniform_buffer(&self, binding: u32, buffer: BufferId) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            slot(&mut state.uniform_buffers, binding),
+            buffer,
+            &mut state.stats.uniform_buffer,
+        ) {
+            unsafe {
+                self.backend()
+                    .bind_buffer_base(glow::UNIFORM_BUFFER, binding, Some(buffer));
+            }
+        }
+    }
+
+    pub(super) fn set_capability(&self, capability: u32, enable: bool) {
+        let state = &mut *self.state.borrow_mut();
+        let cached = match capability {
+            glow::BLEND => &mut state.blend_enabled,
+            glow::DEPTH_TEST => &mut state.depth_test_enabled,
+            glow::SCISSOR_TEST => &mut state.scissor_test_enabled,
+            _ => panic!("Capability {:#x} is not cached", capability),
+        };
+
+        if update(state.enabled, cached, enable, &mut state.stats.capability) {
+            unsafe {
+                if enable {
+                    self.backend().enable(capability);
+                } else {
+                    self.backend().disable(capability);
+                }
+            }
+        }
+    }
+
+    pub(super) fn set_blend_equation(&self, equation: BlendEquation) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.blend_equation,
+            equation,
+            &mut state.stats.blend,
+        ) {
+            unsafe {
+                if equation.is_same() {
+                    self.backend().blend_equation(equation.color.to_gl());
+                } else {
+                    self.backend()
+                        .blend_equation_separate(equation.color.to_gl(), equation.alpha.to_gl());
+                }
+            }
+        }
+    }
+
+    pub(super) fn set_blend_func(&self, func: BlendFunc) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.blend_func,
+            func,
+            &mut state.stats.blend,
+        ) {
+            unsafe {
+                if func.is_same() {
+                    self.backend()
+                        .blend_func(func.src_color.to_gl(), func.dst_color.to_gl());
+                } else {
+                    self.backend().blend_func_separate(
+                        func.src_color.to_gl(),
+                        func.dst_color.to_gl(),
+                        func.src_alpha.to_gl(),
+                        func.dst_alpha.to_gl(),
+                    );
+                }
+            }
+        }
+    }
+
+    pub(super) fn set_blend_color(&self, color: Color4) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.blend_color,
+            color,
+            &mut state.stats.blend,
+        ) {
+            unsafe {
+                self.backend()
+                    .blend_color(color.r, color.g, color.b, color.a);
+            }
+        }
+    }
+
+    pub(super) fn set_depth_func(&self, func: DepthFunc) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.depth_func,
+            func,
+            &mut state.stats.depth,
+        ) {
+            unsafe {
+                self.backend().depth_func(func.to_gl());
+            }
+        }
+    }
+
+    pub(super) fn set_depth_range(&self, near: f32, far: f32) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.depth_range,
+            (near, far),
+            &mut state.stats.depth,
+        ) {
+            unsafe {
+                self.backend().depth_range_f32(near, far);
+            }
+        }
+    }
+
+    pub(super) fn set_depth_mask(&self, write: bool) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.depth_mask,
+            write,
+            &mut state.stats.depth,
+        ) {
+            unsafe {
+                self.backend().depth_mask(write);
+            }
+        }
+    }
+
+    pub(super) fn set_color_mask(&self, mask: (bool, bool, bool, bool)) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.color_mask,
+            mask,
+            &mut state.stats.color_mask,
+        ) {
+            unsafe {
+                self.backend().color_mask(mask.0, mask.1, mask.2, mask.3);
+            }
+        }
+    }
+
+    pub(super) fn set_line_width(&self, width: f32) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.line_width,
+            width,
+            &mut state.stats.line_width,
+        ) {
+            unsafe {
+                self.backend().line_width(width);
+            }
+        }
+    }
+
+    pub(super) fn set_viewport(&self, viewport: [i32; 4]) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.viewport,
+            viewport,
+            &mut state.stats.viewport,
+        ) {
+            unsafe {
+                self.backend()
+                    .viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+            }
+        }
+    }
+
+    pub(super) fn set_scissor(&self, scissor: [i32; 4]) {
+        let state = &mut *self.state.borrow_mut();
+        if update(
+            state.enabled,
+            &mut state.scissor,
+            scissor,
+            &mut state.stats.scissor,
+        ) {
+            unsafe {
+                self.backend()
+                    .scissor(scissor[0], scissor[1], scissor[2], scissor[3]);
+            }
+        }
+    }
+
+    /// Forgets cached bindings of an object that is about to be deleted.
+    /// Deleting a bound object unbinds it, and its id may be reused.
+    pub(super) fn forget_program(&self, program: ProgramId) {
+        let mut state = self.state.borrow_mut();
+        if state.program == Some(Some(program)) {
+            state.program = None;
+        }
+    }
+
+    pub(super) fn forget_vertex_array(&self, vertex_array: VertexArrayId) {
+        let mut state = self.state.borrow_mut();
+        if state.vertex_array == Some(Some(vertex_array)) {
+            state.vertex_array = None;
+        }
+    }
+
+    pub(super) fn forget_framebuffer(&self, framebuffer: FramebufferId) {
+        let mut state = self.state.borrow_mut();
+        if state.framebuffer == Some(Some(framebuffer)) {
+            state.framebuffer = None;
+        }
+    }
+
+    pub(super) fn forget_texture(&self, texture: TextureId) {
+        let mut state = self.state.borrow_mut();
+        for cached in state.textures.iter_mut() {
+            if *cached == Some(Some(texture)) {
+                *cached = None;
+            }
+        }
+    }
+
+    pub(super) fn forget_buffer(&self, buffer: BufferId) {
+        let mut state = self.state.borrow_mut();
+        for cached in state.uniform_buffers.iter_mut() {
+            if *cached == Some(buffer) {
+                *cached = None;
+            }
+        }
+    }
+}
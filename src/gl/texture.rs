@@ -3,14 +3,17 @@ use std::rc::Rc;
 use nalgebra::{Point2, Vector2};
 use thiserror::Error;
 
-use glow::HasContext;
+#[cfg(feature = "web")]
 use wasm_bindgen::{JsCast, JsValue};
+#[cfg(feature = "web")]
 use wasm_bindgen_futures::JsFuture;
+#[cfg(feature = "web")]
 use web_sys::ImageBitmap;
 
+#[cfg(feature = "web")]
 use crate::FetchError;
 
-use super::Context;
+use super::{restore::RestorableId, Context, TextureId};
 
 #[derive(Error, Debug)]
 pub enum NewTextureError {
@@ -21,6 +24,7 @@ pub enum NewTextureError {
     TooLarge(u32, u32),
 }
 
+#[cfg(feature = "web")]
 #[derive(Error, Debug)]
 pub enum LoadTextureError {
     #[error("new texture error: {0}")]
@@ -65,8 +69,25 @@ pub enum TextureValueType {
     RgU8,
     RgF16,
     RgF32,
+    RU8,
+    RF16,
+    RF32,
+    /// RGBA with sRGB-encoded color channels. Sampling returns linear values.
+    SrgbaU8,
+    RU32,
+    RI32,
+    RgU32,
+    RgI32,
+    RgbaU32,
+    RgbaI32,
     Depth,
-    // TODO: Texture value type is incomplete
+    DepthF32,
+    DepthStencil,
+}
+
+/// Component type of pixel data, as returned by `Framebuffer::read_pixels`.
+pub trait PixelComponent: bytemuck::Pod {
+    const TYPE_GL: u32;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -94,9 +115,20 @@ pub enum TextureWrap {
 
 pub struct Texture {
     gl: Rc<Context>,
-    id: glow::Texture,
+    id: RestorableId<TextureId>,
     size: Vector2<u32>,
     params: TextureParams,
+    source: TextureSource,
+}
+
+/// The data that a texture has been created with. This is kept so that the
+/// texture can be restored after context loss.
+enum TextureSource {
+    /// Uninitialized storage, e.g. for render targets.
+    Storage,
+    Rgba(Vec<u8>),
+    #[cfg(feature = "web")]
+    ImageBitmap(ImageBitmap),
 }
 
 impl Texture {
@@ -110,27 +142,11 @@ impl Texture {
         size: Vector2<u32>,
         params: TextureParams,
     ) -> Result<Self, NewTextureError> {
-        let texture = Self::new_impl(gl.clone(), size, params.clone())?;
-
-        let levels = if params.min_filter.uses_mipmap() {
-            (size.x as f32).max(size.y as f32).log2() as i32 + 1
-        } else {
-            1
-        };
-
-        unsafe {
-            gl.tex_storage_2d(
-                glow::TEXTURE_2D,
-                levels,
-                params.value_type.internal_format_gl() as u32,
-                i32::try_from(size.x).unwrap(),
-                i32::try_from(size.y).unwrap(),
-            );
-        }
-
-        Ok(texture)
+        Self::new_impl(gl, size, params, TextureSource::Storage)
     }
 
+    /// Creates a texture from RGBA data. A copy of `rgba` is kept, so that
+    /// the texture can be restored after context loss.
     pub fn from_rgba(
         gl: Rc<Context>,
         rgba: &[u8],
@@ -138,60 +154,36 @@ impl Texture {
         params: TextureParams,
     ) -> Result<Self, NewTextureError> {
         assert!(rgba.len() as u32 == size.x * size.y * 4);
-        assert!(params.value_type == TextureValueType::RgbaU8);
-
-        let texture = Self::new_impl(gl.clone(), size, params.clone())?;
-
-        unsafe {
-            gl.tex_image_2d(
-                glow::TEXTURE_2D,
-                0,
-                params.value_type.internal_format_gl(),
-                i32::try_from(size.x).unwrap(),
-                i32::try_from(size.y).unwrap(),
-                0,
-                params.value_type.format_gl(),
-                params.value_type.type_gl(),
-                Some(rgba),
-            );
-        }
+        assert!(matches!(
+            params.value_type,
+            TextureValueType::RgbaU8 | TextureValueType::SrgbaU8
+        ));
 
-        if texture.params.min_filter.uses_mipmap() {
-            texture.generate_mipmap();
-        }
-
-        Ok(texture)
+        Self::new_impl(gl, size, params, TextureSource::Rgba(rgba.to_vec()))
     }
 
+    #[cfg(feature = "web")]
     pub async fn from_image_bitmap(
         gl: Rc<Context>,
         image_bitmap: ImageBitmap,
         params: TextureParams,
     ) -> Result<Self, LoadTextureError> {
-        assert!(params.value_type == TextureValueType::RgbaU8);
+        assert!(matches!(
+            params.value_type,
+            TextureValueType::RgbaU8 | TextureValueType::SrgbaU8
+        ));
 
         let size = Vector2::new(image_bitmap.width(), image_bitmap.height());
-        let texture = Self::new_impl(gl.clone(), size, params.clone())?;
-
-        unsafe {
-            // FIXME: Not sure if ImageBitmap applies color space conversion here.
-            gl.tex_image_2d_with_image_bitmap(
-                glow::TEXTURE_2D,
-                0,
-                params.value_type.internal_format_gl(),
-                params.value_type.format_gl(),
-                params.value_type.type_gl(),
-                &image_bitmap,
-            );
-        }
-
-        if texture.params.min_filter.uses_mipmap() {
-            texture.generate_mipmap();
-        }
 
-        Ok(texture)
+        Ok(Self::new_impl(
+            gl,
+            size,
+            params,
+            TextureSource::ImageBitmap(image_bitmap),
+        )?)
     }
 
+    #[cfg(feature = "web")]
     pub async fn load(
         gl: Rc<Context>,
         path: &str,
@@ -213,6 +205,7 @@ impl Texture {
         Self::from_image_bitmap(gl, image_bitmap, params).await
     }
 
+    #[cfg(feature = "web")]
     pub async fn from_data(
         gl: Rc<Context>,
         data: &mut [u8],
@@ -238,8 +231,12 @@ impl Texture {
         self.gl.clone()
     }
 
-    pub fn id(&self) -> glow::Texture {
-        self.id
+    pub fn id(&self) -> TextureId {
+        self.id.get(&self.gl, |old_id| {
+            let id = create_texture(&self.gl, self.size, &self.params, &self.source)?;
+            self.gl.rename_resource(old_id, id);
+            Ok(id)
+        })
     }
 
     pub fn size(&self) -> Vector2<u32> {
@@ -250,13 +247,26 @@ impl Texture {
         &self.params
     }
 
+    /// Sets the label under which the texture is listed in
+    /// `Context::resource_dump`.
+    pub fn set_label(&self, label: &str) {
+        self.gl.set_resource_label(self.id(), label);
+    }
+
+    /// Overwrites a part of the texture.
+    ///
+    /// Note that the data is not kept around, so it is lost on context loss.
+    /// Check `Context::generation` to find out if it needs to be set again.
     pub fn set_sub_image(&self, pos: Point2<u32>, size: Vector2<u32>, data: &[u8]) {
         assert!(pos.x + size.x <= self.size.x);
         assert!(pos.y + size.y <= self.size.y);
-        assert!(self.params.value_type == TextureValueType::RgbaU8);
+        assert!(matches!(
+            self.params.value_type,
+            TextureValueType::RgbaU8 | TextureValueType::SrgbaU8
+        ));
 
+        self.gl.set_texture(0, Some(self.id()));
         unsafe {
-            self.gl.bind_texture(glow::TEXTURE_2D, Some(self.id));
             self.gl.tex_sub_image_2d(
                 glow::TEXTURE_2D,
                 0,
@@ -269,6 +279,10 @@ impl Texture {
                 glow::PixelUnpackData::Slice(data),
             );
         }
+        self.gl.count_frame_stats(|stats| {
+            stats.texture_uploads += 1;
+            stats.texture_bytes_uploaded += data.len();
+        });
 
         if self.params.min_filter.uses_mipmap() {
             self.generate_mipmap();
@@ -278,8 +292,8 @@ impl Texture {
     pub fn generate_mipmap(&self) {
         assert!(self.params.min_filter.uses_mipmap());
 
+        self.gl.set_texture(0, Some(self.id()));
         unsafe {
-            self.gl.bind_texture(glow::TEXTURE_2D, Some(self.id));
             self.gl.generate_mipmap(glow::TEXTURE_2D);
         }
     }
@@ -288,9 +302,16 @@ impl Texture {
         gl: Rc<Context>,
         size: Vector2<u32>,
         params: TextureParams,
+        source: TextureSource,
     ) -> Result<Self, NewTextureError> {
         assert!(size.x > 0, "Texture width must be positive");
         assert!(size.y > 0, "Texture height must be positive");
+        assert!(
+            !params.value_type.is_integer()
+                || (params.min_filter == TextureMinFilter::Nearest
+                    && params.mag_filter == TextureMagFilter::Nearest),
+            "Integer textures must use nearest filtering"
+        );
 
         if size.x > Self::max_size(&*gl) {
             return Err(NewTextureError::TooLarge(size.x, Self::max_size(&*gl)));
@@ -302,27 +323,42 @@ impl Texture {
         // TODO:
         // https://developer.mozilla.org/en-US/docs/Web/API/WebGL_API/WebGL_best_practices#dont_assume_you_can_render_into_float_textures
 
-        let id = unsafe { gl.create_texture() }.map_err(super::Error::Glow)?;
-
-        unsafe {
-            gl.bind_texture(glow::TEXTURE_2D, Some(id));
-        }
-
-        set_texture_params(&*gl, &params);
+        let id = create_texture(&gl, size, &params, &source)?;
+
+        let levels = num_levels(size, &params);
+        let size_bytes = (0..levels)
+            .map(|level| {
+                let w = (size.x >> level).max(1) as usize;
+                let h = (size.y >> level).max(1) as usize;
+                w * h * params.value_type.bytes_per_pixel()
+            })
+            .sum();
+        gl.register_resource(
+            id,
+            format!(
+                "texture {}x{} {:?}, {} levels",
+                size.x, size.y, params.value_type, levels
+            ),
+            size_bytes,
+        );
 
         Ok(Self {
+            id: RestorableId::new(&gl, id),
             gl,
-            id,
             size,
             params,
+            source,
         })
     }
 }
 
 impl Drop for Texture {
     fn drop(&mut self) {
+        let id = self.id.current();
+        self.gl.forget_texture(id);
+        self.gl.unregister_resource(id);
         unsafe {
-            self.gl.delete_texture(self.id);
+            self.gl.delete_texture(id);
         }
     }
 }
@@ -413,7 +449,19 @@ impl TextureValueType {
             RgU8 => glow::RG8 as i32,
             RgF16 => glow::RG16F as i32,
             RgF32 => glow::RG32F as i32,
+            RU8 => glow::R8 as i32,
+            RF16 => glow::R16F as i32,
+            RF32 => glow::R32F as i32,
+            SrgbaU8 => glow::SRGB8_ALPHA8 as i32,
+            RU32 => glow::R32UI as i32,
+            RI32 => glow::R32I as i32,
+            RgU32 => glow::RG32UI as i32,
+            RgI32 => glow::RG32I as i32,
+            RgbaU32 => glow::RGBA32UI as i32,
+            RgbaI32 => glow::RGBA32I as i32,
             Depth => glow::DEPTH_COMPONENT24 as i32,
+            DepthF32 => glow::DEPTH_COMPONENT32F as i32,
+            DepthStencil => glow::DEPTH24_STENCIL8 as i32,
         }
     }
 
@@ -430,7 +478,19 @@ impl TextureValueType {
             RgU8 => glow::RG,
             RgF16 => glow::RG,
             RgF32 => glow::RG,
+            RU8 => glow::RED,
+            RF16 => glow::RED,
+            RF32 => glow::RED,
+            SrgbaU8 => glow::RGBA,
+            RU32 => glow::RED_INTEGER,
+            RI32 => glow::RED_INTEGER,
+            RgU32 => glow::RG_INTEGER,
+            RgI32 => glow::RG_INTEGER,
+            RgbaU32 => glow::RGBA_INTEGER,
+            RgbaI32 => glow::RGBA_INTEGER,
             Depth => glow::DEPTH_COMPONENT,
+            DepthF32 => glow::DEPTH_COMPONENT,
+            DepthStencil => glow::DEPTH_STENCIL,
         }
     }
 
@@ -447,15 +507,155 @@ impl TextureValueType {
             RgU8 => glow::UNSIGNED_BYTE,
             RgF16 => glow::HALF_FLOAT,
             RgF32 => glow::FLOAT,
+            RU8 => glow::UNSIGNED_BYTE,
+            RF16 => glow::HALF_FLOAT,
+            RF32 => glow::FLOAT,
+            SrgbaU8 => glow::UNSIGNED_BYTE,
+            RU32 => glow::UNSIGNED_INT,
+            RI32 => glow::INT,
+            RgU32 => glow::UNSIGNED_INT,
+            RgI32 => glow::INT,
+            RgbaU32 => glow::UNSIGNED_INT,
+            RgbaI32 => glow::INT,
             Depth => glow::UNSIGNED_INT,
+            DepthF32 => glow::FLOAT,
+            DepthStencil => glow::UNSIGNED_INT_24_8,
+        }
+    }
+
+    /// Format in which `Framebuffer::read_pixels` reads the type. WebGL 2
+    /// only guarantees reading RGBA, so the components are extracted
+    /// afterwards.
+    pub fn read_format_gl(self) -> u32 {
+        if self.is_integer() {
+            glow::RGBA_INTEGER
+        } else {
+            glow::RGBA
+        }
+    }
+
+    /// Component type in which `Framebuffer::read_pixels` reads the type.
+    /// Half floats are read as `FLOAT`.
+    pub fn read_type_gl(self) -> u32 {
+        use TextureValueType::*;
+
+        match self {
+            RgbaU8 | RgbU8 | RgU8 | RU8 | SrgbaU8 => glow::UNSIGNED_BYTE,
+            RgbaF16 | RgbF16 | RgF16 | RF16 => glow::FLOAT,
+            RgbaF32 | RgbF32 | RgF32 | RF32 => glow::FLOAT,
+            RU32 | RgU32 | RgbaU32 => glow::UNSIGNED_INT,
+            RI32 | RgI32 | RgbaI32 => glow::INT,
+            Depth | DepthF32 | DepthStencil => self.type_gl(),
+        }
+    }
+
+    /// Number of components per pixel in the data returned by
+    /// `Framebuffer::read_pixels`.
+    pub fn num_components(self) -> usize {
+        use TextureValueType::*;
+
+        match self {
+            RgbaU8 | RgbaF16 | RgbaF32 | SrgbaU8 | RgbaU32 | RgbaI32 => 4,
+            RgbU8 | RgbF16 | RgbF32 => 3,
+            RgU8 | RgF16 | RgF32 | RgU32 | RgI32 => 2,
+            RU8 | RF16 | RF32 | RU32 | RI32 => 1,
+            Depth | DepthF32 | DepthStencil => 1,
+        }
+    }
+
+    /// Estimated number of bytes that a pixel takes in GPU memory.
+    pub fn bytes_per_pixel(self) -> usize {
+        use TextureValueType::*;
+
+        match self {
+            RgbaU8 => 4,
+            RgbaF16 => 8,
+            RgbaF32 => 16,
+            RgbU8 => 3,
+            RgbF16 => 6,
+            RgbF32 => 12,
+            RgU8 => 2,
+            RgF16 => 4,
+            RgF32 => 8,
+            RU8 => 1,
+            RF16 => 2,
+            RF32 => 4,
+            SrgbaU8 => 4,
+            RU32 => 4,
+            RI32 => 4,
+            RgU32 => 8,
+            RgI32 => 8,
+            RgbaU32 => 16,
+            RgbaI32 => 16,
+            // Drivers usually pad 24-bit depth to 32 bits.
+            Depth => 4,
+            DepthF32 => 4,
+            DepthStencil => 4,
         }
     }
 
     pub fn is_depth(self) -> bool {
-        self == TextureValueType::Depth
+        use TextureValueType::*;
+
+        matches!(self, Depth | DepthF32 | DepthStencil)
+    }
+
+    pub fn has_stencil(self) -> bool {
+        self == TextureValueType::DepthStencil
+    }
+
+    /// Returns true for unnormalized integer formats. These need to be
+    /// sampled with `usampler2D` or `isampler2D`, and can not be filtered
+    /// linearly.
+    pub fn is_integer(self) -> bool {
+        use TextureValueType::*;
+
+        matches!(self, RU32 | RI32 | RgU32 | RgI32 | RgbaU32 | RgbaI32)
+    }
+
+    pub fn is_float(self) -> bool {
+        use TextureValueType::*;
+
+        matches!(
+            self,
+            RgbaF16 | RgbaF32 | RgbF16 | RgbF32 | RgF16 | RgF32 | RF16 | RF32 | DepthF32
+        )
+    }
+
+    /// Returns true if the type can be used as a color attachment.
+    ///
+    /// In WebGL 2, float formats other than RGB are renderable only if
+    /// `EXT_color_buffer_float` is available, while RGB float formats are
+    /// never renderable.
+    pub fn is_color_renderable(self, gl: &Context) -> bool {
+        use TextureValueType::*;
+
+        if self.is_depth() || matches!(self, RgbF16 | RgbF32) {
+            false
+        } else if self.is_float() {
+            gl.supports_color_buffer_float()
+        } else {
+            true
+        }
     }
 }
 
+impl PixelComponent for u8 {
+    const TYPE_GL: u32 = glow::UNSIGNED_BYTE;
+}
+
+impl PixelComponent for f32 {
+    const TYPE_GL: u32 = glow::FLOAT;
+}
+
+impl PixelComponent for u32 {
+    const TYPE_GL: u32 = glow::UNSIGNED_INT;
+}
+
+impl PixelComponent for i32 {
+    const TYPE_GL: u32 = glow::INT;
+}
+
 impl TextureMinFilter {
     pub fn to_gl(self) -> u32 {
         use TextureMinFilter::*;
@@ -500,6 +700,88 @@ impl TextureWrap {
     }
 }
 
+fn create_texture(
+    gl: &Context,
+    size: Vector2<u32>,
+    params: &TextureParams,
+    source: &TextureSource,
+) -> Result<TextureId, super::Error> {
+    let id = unsafe { gl.create_texture() }.map_err(super::Error::Glow)?;
+
+    gl.set_texture(0, Some(id));
+    set_texture_params(gl, params);
+
+    let uploaded_bytes = match source {
+        TextureSource::Storage => {
+            unsafe {
+                gl.tex_storage_2d(
+                    glow::TEXTURE_2D,
+                    i32::try_from(num_levels(size, params)).unwrap(),
+                    params.value_type.internal_format_gl() as u32,
+                    i32::try_from(size.x).unwrap(),
+                    i32::try_from(size.y).unwrap(),
+                );
+            }
+
+            return Ok(id);
+        }
+        TextureSource::Rgba(rgba) => {
+            unsafe {
+                gl.tex_image_2d(
+                    glow::TEXTURE_2D,
+                    0,
+                    params.value_type.internal_format_gl(),
+                    i32::try_from(size.x).unwrap(),
+                    i32::try_from(size.y).unwrap(),
+                    0,
+                    params.value_type.format_gl(),
+                    params.value_type.type_gl(),
+                    Some(rgba),
+                );
+            }
+
+            rgba.len()
+        }
+        #[cfg(feature = "web")]
+        TextureSource::ImageBitmap(image_bitmap) => {
+            unsafe {
+                // FIXME: Not sure if ImageBitmap applies color space conversion here.
+                gl.tex_image_2d_with_image_bitmap(
+                    glow::TEXTURE_2D,
+                    0,
+                    params.value_type.internal_format_gl(),
+                    params.value_type.format_gl(),
+                    params.value_type.type_gl(),
+                    image_bitmap,
+                );
+            }
+
+            size.x as usize * size.y as usize * 4
+        }
+    };
+
+    gl.count_frame_stats(|stats| {
+        stats.texture_uploads += 1;
+        stats.texture_bytes_uploaded += uploaded_bytes;
+    });
+
+    if params.min_filter.uses_mipmap() {
+        unsafe {
+            gl.generate_mipmap(glow::TEXTURE_2D);
+        }
+    }
+
+    Ok(id)
+}
+
+fn num_levels(size: Vector2<u32>, params: &TextureParams) -> u32 {
+    if params.min_filter.uses_mipmap() {
+        (size.x as f32).max(size.y as f32).log2() as u32 + 1
+    } else {
+        1
+    }
+}
+
 fn set_texture_params(gl: &Context, params: &TextureParams) {
     unsafe {
         gl.tex_parameter_i32(
@@ -1,13 +1,19 @@
-use std::{marker::PhantomData, rc::Rc};
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
 
 use crevice::std140::AsStd140;
-use glow::HasContext;
 
-use super::{Context, Error};
+use super::{
+    restore::{restore_buffer, RestorableId},
+    BufferId, Context, Error,
+};
 
 pub struct Uniform<U> {
     gl: Rc<Context>,
-    id: <glow::Context as HasContext>::Buffer,
+    id: RestorableId<BufferId>,
+
+    // Kept for restoring the buffer after context loss.
+    data: RefCell<Vec<u8>>,
+
     _phantom: PhantomData<U>,
 }
 
@@ -17,9 +23,15 @@ where
 {
     pub fn new(gl: Rc<Context>, uniform: U) -> Result<Self, Error> {
         let id = unsafe { gl.create_buffer() }.map_err(Error::Glow)?;
+        gl.register_resource(
+            id,
+            format!("uniform buffer of {}", std::any::type_name::<U>()),
+            0,
+        );
         let uniform_buffer = Uniform {
+            id: RestorableId::new(&gl, id),
             gl,
-            id,
+            data: RefCell::new(Vec::new()),
             _phantom: PhantomData,
         };
 
@@ -32,11 +44,11 @@ where
         let data_std140 = data.as_std140();
         let data_u8 = bytemuck::bytes_of(&data_std140);
 
-        unsafe {
-            self.gl.bind_buffer(glow::UNIFORM_BUFFER, Some(self.id));
-            self.gl
-                .buffer_data_u8_slice(glow::UNIFORM_BUFFER, data_u8, glow::STREAM_DRAW);
-        }
+        self.gl
+            .set_buffer_data(glow::UNIFORM_BUFFER, self.id(), data_u8, glow::STREAM_DRAW);
+
+        self.gl.set_resource_size(self.id(), data_u8.len());
+        self.data.replace(data_u8.to_vec());
     }
 }
 
@@ -45,15 +57,26 @@ impl<U> Uniform<U> {
         self.gl.clone()
     }
 
-    pub fn id(&self) -> glow::Buffer {
-        self.id
+    pub fn id(&self) -> BufferId {
+        self.id.get(&self.gl, |old_id| {
+            restore_buffer(
+                &self.gl,
+                old_id,
+                glow::UNIFORM_BUFFER,
+                &self.data.borrow(),
+                glow::STREAM_DRAW,
+            )
+        })
     }
 }
 
 impl<U> Drop for Uniform<U> {
     fn drop(&mut self) {
+        let id = self.id.current();
+        self.gl.forget_buffer(id);
+        self.gl.unregister_resource(id);
         unsafe {
-            self.gl.delete_buffer(self.id);
+            self.gl.delete_buffer(id);
         }
     }
 }
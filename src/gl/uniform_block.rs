@@ -1,23 +1,22 @@
 use std::rc::Rc;
 
 use crevice::{glsl::GlslStruct, std140::AsStd140};
-use glow::HasContext;
 
-use super::{Context, Uniform};
+use super::{BufferId, Context, ProgramId, Uniform};
 
 pub trait UniformBlock: AsStd140 + GlslStruct {}
 
 pub trait UniformBuffers {
     type UniformBlockDecls: UniformDecls;
 
-    fn bind(&self, bindings: &[u32]);
+    fn buffer_ids(&self) -> Vec<BufferId>;
 }
 
 pub trait UniformDecls {
     const N: usize;
 
     fn glsl_decls(instance_names: &[&str]) -> String;
-    fn bind_to_program(gl: &Context, id: glow::Program, uniform_blocks: &[(&str, u32)]);
+    fn bind_to_program(gl: &Context, id: ProgramId, uniform_blocks: &[(&str, u32)]);
 }
 
 impl UniformDecls for () {
@@ -29,7 +28,7 @@ impl UniformDecls for () {
         String::new()
     }
 
-    fn bind_to_program(_: &Context, _: glow::Program, uniform_blocks: &[(&str, u32)]) {
+    fn bind_to_program(_: &Context, _: ProgramId, uniform_blocks: &[(&str, u32)]) {
         assert!(uniform_blocks.len() == Self::N);
     }
 }
@@ -64,7 +63,7 @@ where
         output
     }
 
-    fn bind_to_program(gl: &Context, id: glow::Program, uniform_blocks: &[(&str, u32)]) {
+    fn bind_to_program(gl: &Context, id: ProgramId, uniform_blocks: &[(&str, u32)]) {
         assert!(uniform_blocks.len() == Self::N);
 
         if let Some(index) =
@@ -92,7 +91,7 @@ where
         U0::glsl_decls(&[instance_names[0]]) + &U1::glsl_decls(&[instance_names[1]])
     }
 
-    fn bind_to_program(gl: &Context, id: glow::Program, uniform_blocks: &[(&str, u32)]) {
+    fn bind_to_program(gl: &Context, id: ProgramId, uniform_blocks: &[(&str, u32)]) {
         assert!(uniform_blocks.len() == Self::N);
 
         U0::bind_to_program(gl, id, &[uniform_blocks[0]]);
@@ -116,7 +115,7 @@ where
             + &U2::glsl_decls(&[instance_names[2]])
     }
 
-    fn bind_to_program(gl: &Context, id: glow::Program, uniform_blocks: &[(&str, u32)]) {
+    fn bind_to_program(gl: &Context, id: ProgramId, uniform_blocks: &[(&str, u32)]) {
         assert!(uniform_blocks.len() == Self::N);
 
         U0::bind_to_program(gl, id, &[uniform_blocks[0]]);
@@ -128,8 +127,8 @@ where
 impl UniformBuffers for () {
     type UniformBlockDecls = ();
 
-    fn bind(&self, bindings: &[u32]) {
-        assert!(bindings.len() == <Self::UniformBlockDecls as UniformDecls>::N);
+    fn buffer_ids(&self) -> Vec<BufferId> {
+        Vec::new()
     }
 }
 
@@ -139,13 +138,8 @@ where
 {
     type UniformBlockDecls = U;
 
-    fn bind(&self, bindings: &[u32]) {
-        assert!(bindings.len() == <Self::UniformBlockDecls as UniformDecls>::N);
-
-        unsafe {
-            self.gl()
-                .bind_buffer_base(glow::UNIFORM_BUFFER, bindings[0], Some(self.id()));
-        }
+    fn buffer_ids(&self) -> Vec<BufferId> {
+        vec![self.id()]
     }
 }
 
@@ -156,18 +150,10 @@ where
 {
     type UniformBlockDecls = (U0, U1);
 
-    fn bind(&self, bindings: &[u32]) {
-        assert!(bindings.len() == <Self::UniformBlockDecls as UniformDecls>::N);
+    fn buffer_ids(&self) -> Vec<BufferId> {
         assert!(Rc::ptr_eq(&self.0.gl(), &self.1.gl()));
 
-        unsafe {
-            self.0
-                .gl()
-                .bind_buffer_base(glow::UNIFORM_BUFFER, bindings[0], Some(self.0.id()));
-            self.0
-                .gl()
-                .bind_buffer_base(glow::UNIFORM_BUFFER, bindings[1], Some(self.1.id()));
-        }
+        vec![self.0.id(), self.1.id()]
     }
 }
 
@@ -179,22 +165,11 @@ where
 {
     type UniformBlockDecls = (U0, U1, U2);
 
-    fn bind(&self, bindings: &[u32]) {
-        assert!(bindings.len() == <Self::UniformBlockDecls as UniformDecls>::N);
+    fn buffer_ids(&self) -> Vec<BufferId> {
         assert!(Rc::ptr_eq(&self.0.gl(), &self.1.gl()));
         assert!(Rc::ptr_eq(&self.0.gl(), &self.2.gl()));
 
-        unsafe {
-            self.0
-                .gl()
-                .bind_buffer_base(glow::UNIFORM_BUFFER, bindings[0], Some(self.0.id()));
-            self.0
-                .gl()
-                .bind_buffer_base(glow::UNIFORM_BUFFER, bindings[1], Some(self.1.id()));
-            self.0
-                .gl()
-                .bind_buffer_base(glow::UNIFORM_BUFFER, bindings[2], Some(self.2.id()));
-        }
+        vec![self.0.id(), self.1.id(), self.2.id()]
     }
 }
 
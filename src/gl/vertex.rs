@@ -1,10 +1,9 @@
 use std::rc::Rc;
 
-use bytemuck::Pod;
+use bytemuck::{Pod, Zeroable};
+use half::f16;
 use nalgebra::{Matrix2, Matrix3, Matrix4, Point2, Point3, Point4, Vector2, Vector3, Vector4};
 
-use glow::HasContext;
-
 use crate::{Color3, Color4};
 
 use super::VertexBuffer;
@@ -13,8 +12,31 @@ use super::VertexBuffer;
 pub enum AttributeValueType {
     Float,
     Int,
+    HalfFloat,
+    UnsignedByteNormalized,
+    UnsignedShortNormalized,
 }
 
+/// Four unsigned bytes that are mapped to `[0, 1]` in the shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Zeroable, Pod)]
+#[repr(C)]
+pub struct Unorm8x4(pub [u8; 4]);
+
+/// Two unsigned shorts that are mapped to `[0, 1]` in the shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Zeroable, Pod)]
+#[repr(C)]
+pub struct Unorm16x2(pub [u16; 2]);
+
+/// Two half-precision floats that are converted to `float` in the shader.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Zeroable, Pod)]
+#[repr(C)]
+pub struct Half2(pub [f16; 2]);
+
+/// Four half-precision floats that are converted to `float` in the shader.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Zeroable, Pod)]
+#[repr(C)]
+pub struct Half4(pub [f16; 4]);
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Attribute {
     pub name: String,
@@ -45,11 +67,14 @@ pub trait Vertex: Pod {
             gl.vertex_attrib_divisor(index as u32, divisor);
 
             match attribute.value_type {
-                AttributeValueType::Float => gl.vertex_attrib_pointer_f32(
+                AttributeValueType::Float
+                | AttributeValueType::HalfFloat
+                | AttributeValueType::UnsignedByteNormalized
+                | AttributeValueType::UnsignedShortNormalized => gl.vertex_attrib_pointer_f32(
                     index as u32,
                     attribute.num_elements as i32,
                     attribute.value_type.to_gl(),
-                    false,
+                    attribute.value_type.is_normalized(),
                     std::mem::size_of::<Self>() as i32,
                     attribute.offset as i32,
                 ),
@@ -208,6 +233,9 @@ impl AttributeValueType {
         match self {
             AttributeValueType::Float => glow::FLOAT,
             AttributeValueType::Int => glow::INT,
+            AttributeValueType::HalfFloat => glow::HALF_FLOAT,
+            AttributeValueType::UnsignedByteNormalized => glow::UNSIGNED_BYTE,
+            AttributeValueType::UnsignedShortNormalized => glow::UNSIGNED_SHORT,
         }
     }
 
@@ -215,8 +243,63 @@ impl AttributeValueType {
         match self {
             AttributeValueType::Float => std::mem::size_of::<f32>(),
             AttributeValueType::Int => std::mem::size_of::<i32>(),
+            AttributeValueType::HalfFloat => std::mem::size_of::<f16>(),
+            AttributeValueType::UnsignedByteNormalized => std::mem::size_of::<u8>(),
+            AttributeValueType::UnsignedShortNormalized => std::mem::size_of::<u16>(),
         }
     }
+
+    pub fn is_normalized(self) -> bool {
+        matches!(
+            self,
+            AttributeValueType::UnsignedByteNormalized
+                | AttributeValueType::UnsignedShortNormalized
+        )
+    }
+}
+
+impl Unorm8x4 {
+    /// Packs values, clamping them to `[0, 1]`.
+    pub fn new(values: [f32; 4]) -> Self {
+        Self(values.map(|value| (value.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8))
+    }
+}
+
+impl Unorm16x2 {
+    /// Packs values, clamping them to `[0, 1]`.
+    pub fn new(values: [f32; 2]) -> Self {
+        Self(values.map(|value| (value.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16))
+    }
+}
+
+impl Half2 {
+    pub fn new(values: [f32; 2]) -> Self {
+        Self(values.map(f16::from_f32))
+    }
+}
+
+impl Half4 {
+    pub fn new(values: [f32; 4]) -> Self {
+        Self(values.map(f16::from_f32))
+    }
+}
+
+impl From<Color4> for Unorm8x4 {
+    fn from(color: Color4) -> Self {
+        Self::new([color.r, color.g, color.b, color.a])
+    }
+}
+
+impl From<Color4> for Half4 {
+    fn from(color: Color4) -> Self {
+        Self::new([color.r, color.g, color.b, color.a])
+    }
+}
+
+impl From<Point2<f32>> for Half2 {
+    fn from(p: Point2<f32>) -> Self {
+        Self::new([p.x, p.y])
+    }
 }
 
 impl Attribute {
@@ -278,3 +361,8 @@ impl_data_type!(Matrix4<i32>, imat4, Int, 4 * 4);
 
 impl_data_type!(Color3, vec3, Float, 3);
 impl_data_type!(Color4, vec4, Float, 4);
+
+impl_data_type!(Unorm8x4, vec4, UnsignedByteNormalized, 4);
+impl_data_type!(Unorm16x2, vec2, UnsignedShortNormalized, 2);
+impl_data_type!(Half2, vec2, HalfFloat, 2);
+impl_data_type!(Half4, vec4, HalfFloat, 4);
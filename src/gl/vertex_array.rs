@@ -1,74 +1,56 @@
 use std::rc::Rc;
 
-use glow::HasContext;
+use super::{
+    restore::RestorableId, Context, ElementBuffer, Error, Vertex, VertexArrayId, VertexBuffer,
+    VertexDecls,
+};
 
-use super::{Context, ElementBuffer, Error, Vertex, VertexBuffer, VertexDecls};
-
-pub struct VertexArray<V, E = u32>
+pub struct VertexArray<V>
 where
     V: VertexDecls,
 {
-    element_buffer: Rc<ElementBuffer<E>>,
+    element_buffer: Rc<ElementBuffer>,
     vertex_buffers: V::RcVertexBufferTuple,
-    id: glow::VertexArray,
+    divisors: Vec<u32>,
+    id: RestorableId<VertexArrayId>,
 }
 
-impl<V, E> VertexArray<V, E>
+impl<V> VertexArray<V>
 where
     V: Vertex,
 {
     pub fn new(
-        element_buffer: Rc<ElementBuffer<E>>,
+        element_buffer: Rc<ElementBuffer>,
         vertex_buffer: Rc<VertexBuffer<V>>,
     ) -> Result<Self, Error> {
-        let gl = element_buffer.gl();
-        let id = unsafe { gl.create_vertex_array() }.map_err(Error::Glow)?;
-
-        unsafe {
-            gl.bind_vertex_array(Some(id));
-            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(element_buffer.id()));
-            V::bind_to_vertex_array(&*vertex_buffer, 0, 0);
-            gl.bind_vertex_array(None);
-        }
-
-        Ok(Self {
-            element_buffer,
-            vertex_buffers: vertex_buffer,
-            id,
-        })
+        Self::new_instanced(element_buffer, vertex_buffer, &[0])
     }
 }
 
-impl<V, E> VertexArray<V, E>
+impl<V> VertexArray<V>
 where
     V: VertexDecls,
 {
     pub fn new_instanced(
-        element_buffer: Rc<ElementBuffer<E>>,
+        element_buffer: Rc<ElementBuffer>,
         vertex_buffers: V::RcVertexBufferTuple,
         divisors: &[u32],
     ) -> Result<Self, Error> {
         assert!(divisors.len() == V::N);
 
         let gl = element_buffer.gl();
-        let id = unsafe { gl.create_vertex_array() }.map_err(Error::Glow)?;
-
-        unsafe {
-            gl.bind_vertex_array(Some(id));
-            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(element_buffer.id()));
-            V::bind_to_vertex_array(vertex_buffers.clone(), divisors, 0);
-            gl.bind_vertex_array(None);
-        }
+        let id = create_vertex_array::<V>(&gl, &element_buffer, &vertex_buffers, divisors)?;
 
         Ok(Self {
             element_buffer,
             vertex_buffers,
-            id,
+            divisors: divisors.to_vec(),
+            id: RestorableId::new(&gl, id),
         })
     }
 }
 
-impl<V, E> VertexArray<V, E>
+impl<V> VertexArray<V>
 where
     V: VertexDecls,
 {
@@ -76,7 +58,7 @@ where
         self.element_buffer.gl()
     }
 
-    pub fn element_buffer(&self) -> Rc<ElementBuffer<E>> {
+    pub fn element_buffer(&self) -> Rc<ElementBuffer> {
         self.element_buffer.clone()
     }
 
@@ -84,24 +66,59 @@ where
         self.vertex_buffers.clone()
     }
 
-    pub fn id(&self) -> glow::VertexArray {
-        self.id
+    pub fn id(&self) -> VertexArrayId {
+        let gl = self.gl();
+
+        self.id.get(&gl, |_| {
+            create_vertex_array::<V>(
+                &gl,
+                &self.element_buffer,
+                &self.vertex_buffers,
+                &self.divisors,
+            )
+        })
     }
 
     pub fn bind(&self) {
-        unsafe {
-            self.gl().bind_vertex_array(Some(self.id));
-        }
+        self.gl().set_vertex_array(Some(self.id()));
+    }
+}
+
+fn create_vertex_array<V>(
+    gl: &Context,
+    element_buffer: &ElementBuffer,
+    vertex_buffers: &V::RcVertexBufferTuple,
+    divisors: &[u32],
+) -> Result<VertexArrayId, Error>
+where
+    V: VertexDecls,
+{
+    // Restoring the element buffer after context loss unbinds the current
+    // vertex array, so this needs to happen before binding ours.
+    let element_buffer_id = element_buffer.id();
+
+    let id = unsafe { gl.create_vertex_array() }.map_err(Error::Glow)?;
+
+    gl.set_vertex_array(Some(id));
+    unsafe {
+        gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(element_buffer_id));
+        V::bind_to_vertex_array(vertex_buffers.clone(), divisors, 0);
     }
+    gl.set_vertex_array(None);
+
+    Ok(id)
 }
 
-impl<V, E> Drop for VertexArray<V, E>
+impl<V> Drop for VertexArray<V>
 where
     V: VertexDecls,
 {
     fn drop(&mut self) {
+        let gl = self.gl();
+        let id = self.id.current();
+        gl.forget_vertex_array(id);
         unsafe {
-            self.gl().delete_vertex_array(self.id);
+            gl.delete_vertex_array(id);
         }
     }
 }
@@ -1,13 +1,30 @@
-use std::{cell::Cell, marker::PhantomData, rc::Rc};
-
-use glow::HasContext;
-
-use super::{Context, Error, Vertex};
-
+use std::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    rc::Rc,
+};
+
+use super::{
+    restore::{restore_buffer, RestorableId},
+    BufferId, Context, Error, Vertex,
+};
+
+/// Buffer of vertices.
+///
+/// Buffers that are created with `new_static` or filled with `set_static` keep
+/// a copy of their data, so that they can be restored after the WebGL context
+/// has been lost. Streamed buffers, which are filled with `set`, come back
+/// empty instead, and need to be set again by their owner.
 pub struct VertexBuffer<V> {
     gl: Rc<Context>,
-    id: glow::Buffer,
+    id: RestorableId<BufferId>,
     len: Cell<usize>,
+
+    // Kept for restoring the buffer after context loss. Empty unless the
+    // buffer is static.
+    data: RefCell<Vec<u8>>,
+    usage: Cell<u32>,
+
     _phantom: PhantomData<V>,
 }
 
@@ -17,18 +34,25 @@ where
 {
     pub fn new(gl: Rc<Context>) -> Result<Self, Error> {
         let id = unsafe { gl.create_buffer() }.map_err(Error::Glow)?;
+        gl.register_resource(
+            id,
+            format!("vertex buffer of {}", std::any::type_name::<V>()),
+            0,
+        );
 
         Ok(Self {
+            id: RestorableId::new(&gl, id),
             gl,
-            id,
             len: Cell::new(0),
+            data: RefCell::new(Vec::new()),
+            usage: Cell::new(glow::STREAM_DRAW),
             _phantom: PhantomData,
         })
     }
 
     pub fn new_static(gl: Rc<Context>, data: &[V]) -> Result<Self, Error> {
         let vertex_buffer = Self::new(gl)?;
-        vertex_buffer.set_data_with_usage(data, glow::STATIC_DRAW);
+        vertex_buffer.set_static(data);
 
         Ok(vertex_buffer)
     }
@@ -37,16 +61,29 @@ where
         self.set_data_with_usage(data, glow::STREAM_DRAW);
     }
 
+    /// Uploads data that is not going to change often, keeping a copy for
+    /// restoring the buffer after context loss.
+    pub fn set_static(&self, data: &[V]) {
+        self.set_data_with_usage(data, glow::STATIC_DRAW);
+    }
+
     fn set_data_with_usage(&self, data: &[V], usage: u32) {
         let data_u8 = bytemuck::cast_slice(data);
 
-        unsafe {
-            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.id));
-            self.gl
-                .buffer_data_u8_slice(glow::ARRAY_BUFFER, data_u8, usage);
-        }
+        self.gl
+            .set_buffer_data(glow::ARRAY_BUFFER, self.id(), data_u8, usage);
 
         self.len.set(data.len());
+        self.gl.set_resource_size(self.id(), data_u8.len());
+
+        let mut retained = self.data.borrow_mut();
+        retained.clear();
+        if usage == glow::STATIC_DRAW {
+            retained.extend_from_slice(data_u8);
+        } else {
+            retained.shrink_to_fit();
+        }
+        self.usage.set(usage);
     }
 }
 
@@ -55,11 +92,27 @@ impl<V> VertexBuffer<V> {
         self.gl.clone()
     }
 
-    pub fn id(&self) -> glow::Buffer {
-        self.id
+    pub fn id(&self) -> BufferId {
+        self.id.get(&self.gl, |old_id| {
+            let data = self.data.borrow();
+            if data.is_empty() {
+                // Streamed buffers come back empty.
+                self.len.set(0);
+            }
+
+            restore_buffer(
+                &self.gl,
+                old_id,
+                glow::ARRAY_BUFFER,
+                &data,
+                self.usage.get(),
+            )
+        })
     }
 
     pub fn len(&self) -> usize {
+        // Restore first, since streamed buffers come back empty.
+        self.id();
         self.len.get()
     }
 
@@ -70,8 +123,11 @@ impl<V> VertexBuffer<V> {
 
 impl<V> Drop for VertexBuffer<V> {
     fn drop(&mut self) {
+        let id = self.id.current();
+        self.gl.forget_buffer(id);
+        self.gl.unregister_resource(id);
         unsafe {
-            self.gl.delete_buffer(self.id);
+            self.gl.delete_buffer(id);
         }
     }
 }
@@ -1,3 +1,4 @@
+#[cfg(feature = "web")]
 use web_sys::{KeyboardEvent, MouseEvent};
 
 use nalgebra::Point2;
@@ -11,6 +12,14 @@ pub enum Event {
     MousePressed(Button),
     MouseReleased(Button),
     MouseMoved(Point2<f64>),
+
+    /// The browser has taken away the WebGL context, e.g. after a GPU reset.
+    /// Nothing is drawn until `ContextRestored` arrives.
+    ContextLost,
+
+    /// The WebGL context is usable again. GL objects are recreated
+    /// automatically the next time they are used.
+    ContextRestored,
 }
 
 /// A key that can be pressed.
@@ -222,6 +231,7 @@ pub enum Button {
     Secondary,
 }
 
+#[cfg(feature = "web")]
 impl Key {
     pub fn from_keyboard_event(event: &KeyboardEvent) -> Option<Self> {
         use Key::*;
@@ -365,6 +375,7 @@ impl Key {
     }
 }
 
+#[cfg(feature = "web")]
 impl Button {
     pub fn from_mouse_event(event: &MouseEvent) -> Option<Self> {
         use Button::*;
@@ -1,9 +1,9 @@
 use std::{cell::RefCell, collections::VecDeque, rc::Rc};
 
 use nalgebra::Point2;
-use web_sys::{FocusEvent, HtmlCanvasElement, KeyboardEvent, MouseEvent};
+use web_sys::{FocusEvent, HtmlCanvasElement, KeyboardEvent, MouseEvent, WebGlContextEvent};
 
-use crate::error::InitError;
+use crate::{error::InitError, gl};
 
 use super::{Button, Event, EventListener, Key};
 
@@ -23,10 +23,12 @@ pub struct EventHandlers {
     _on_mouse_down: EventListener<MouseEvent>,
     _on_mouse_release: EventListener<MouseEvent>,
     _on_mouse_move: EventListener<MouseEvent>,
+    _on_context_lost: EventListener<WebGlContextEvent>,
+    _on_context_restored: EventListener<WebGlContextEvent>,
 }
 
 impl EventHandlers {
-    pub fn new(canvas: HtmlCanvasElement) -> Result<Self, InitError> {
+    pub fn new(canvas: HtmlCanvasElement, gl: Rc<gl::Context>) -> Result<Self, InitError> {
         let state = Rc::new(RefCell::new(SharedState::default()));
 
         let on_focus = EventListener::new_consuming(&canvas, "focus", {
@@ -116,6 +118,30 @@ impl EventHandlers {
             }
         });
 
+        let on_context_lost = EventListener::new(&canvas, "webglcontextlost", {
+            let state = state.clone();
+            let gl = gl.clone();
+            move |event: WebGlContextEvent| {
+                // Without this, the browser will never restore the context.
+                event.prevent_default();
+
+                // Mark the context right away, so that commands are dropped
+                // even before the application gets to see the event.
+                gl.mark_lost();
+
+                state.borrow_mut().events.push_back(Event::ContextLost);
+            }
+        });
+
+        let on_context_restored = EventListener::new(&canvas, "webglcontextrestored", {
+            let state = state.clone();
+            move |_: WebGlContextEvent| {
+                gl.mark_restored();
+
+                state.borrow_mut().events.push_back(Event::ContextRestored);
+            }
+        });
+
         Ok(Self {
             state,
             _on_focus: on_focus,
@@ -126,6 +152,8 @@ impl EventHandlers {
             _on_mouse_down: on_mouse_down,
             _on_mouse_release: on_mouse_release,
             _on_mouse_move: on_mouse_move,
+            _on_context_lost: on_context_lost,
+            _on_context_restored: on_context_restored,
         })
     }
 
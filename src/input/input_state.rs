@@ -22,7 +22,9 @@ impl Default for InputState {
 }
 
 impl InputState {
-    pub(crate) fn handle_event(&mut self, event: &Event) {
+    /// Updates the state with an event. `Context` does this for every event
+    /// that it returns, so this is only needed for feeding events manually.
+    pub fn handle_event(&mut self, event: &Event) {
         match event {
             Event::Unfocused => {
                 self.pressed_keys.clear();
@@ -1,9 +1,13 @@
 mod event;
+#[cfg(feature = "web")]
 mod event_handlers;
+#[cfg(feature = "web")]
 mod event_listener;
 mod input_state;
 
 pub use event::{Button, Event, Key};
+#[cfg(feature = "web")]
 pub use event_handlers::EventHandlers;
+#[cfg(feature = "web")]
 pub use event_listener::EventListener;
 pub use input_state::InputState;
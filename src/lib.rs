@@ -1,18 +1,28 @@
 //! `malen` is yet another one of these libraries for 2D web game development.
+//!
+//! Everything that depends on the browser is behind the default `web` feature.
+//! Without it, the crate builds natively, so that e.g. geometry, batching and
+//! text layout can be tested with plain `cargo test`.
 
+#[cfg(feature = "web")]
 mod canvas;
 mod color;
+#[cfg(feature = "web")]
 mod config;
+#[cfg(feature = "web")]
 mod context;
 mod error;
+#[cfg(feature = "web")]
 mod fetch;
 mod input;
+#[cfg(feature = "web")]
 mod main_loop;
-#[cfg(feature = "coarse-prof")]
+#[cfg(all(feature = "coarse-prof", feature = "web"))]
 mod profile;
 
 pub(crate) mod util;
 
+#[cfg(feature = "web")]
 pub mod al;
 pub mod data;
 pub mod geom;
@@ -21,22 +31,34 @@ pub mod light;
 pub mod particles;
 pub mod pass;
 pub mod plot;
+#[cfg(feature = "golden")]
+pub mod raster;
+pub mod scene;
 pub mod text;
+pub mod tilemap;
 
 // Re-export dependencies that occur in our public API.
 #[cfg(feature = "coarse-prof")]
 pub use coarse_prof;
 pub use glow;
 pub use nalgebra;
+#[cfg(feature = "web")]
 pub use web_sys;
 
+#[cfg(feature = "web")]
 pub use canvas::{Canvas, CanvasSizeConfig};
 pub use color::{Color3, Color4};
+#[cfg(feature = "web")]
 pub use config::Config;
+#[cfg(feature = "web")]
 pub use context::Context;
-pub use error::{FrameError, InitError};
+pub use error::FrameError;
+#[cfg(feature = "web")]
+pub use error::InitError;
+#[cfg(feature = "web")]
 pub use fetch::{fetch, fetch_array_buffer, fetch_blob, fetch_data, FetchError};
 pub use input::{Button, Event, InputState, Key};
+#[cfg(feature = "web")]
 pub use main_loop::main_loop;
-#[cfg(feature = "coarse-prof")]
+#[cfg(all(feature = "coarse-prof", feature = "web"))]
 pub use profile::{Profile, ProfileParams};
@@ -4,10 +4,13 @@ mod occluder;
 mod occluder_batch;
 pub(super) mod pass;
 mod pipeline;
+mod sprite_occluder;
 
 pub use def::{GlobalLightProps, Light, LightPipelineParams, ObjectLightProps};
 pub use occluder::{
-    OccluderCircle, OccluderLine, OccluderLineVertex, OccluderRect, OccluderRotatedRect,
+    OccluderCircle, OccluderLine, OccluderLineVertex, OccluderOutline, OccluderRect,
+    OccluderRotatedRect,
 };
 pub use occluder_batch::OccluderBatch;
 pub use pipeline::{LightPipeline, NewLightPipelineError};
+pub use sprite_occluder::SpriteOccluder;
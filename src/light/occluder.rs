@@ -4,7 +4,7 @@ use bytemuck::{Pod, Zeroable};
 
 use crate::{
     attributes,
-    data::{Geometry, LineTag},
+    data::{Geometry, HasPosition, LineTag},
     geom::{Circle, Line, Rect, RotatedRect},
     gl::{Attribute, Vertex},
 };
@@ -33,6 +33,24 @@ impl Vertex for OccluderLineVertex {
     }
 }
 
+impl HasPosition for OccluderLineVertex {
+    fn position(&self) -> Point2<f32> {
+        self.line_0
+    }
+
+    fn set_position(&mut self, position: Point2<f32>) {
+        self.line_0 = position;
+    }
+
+    fn map_positions<F>(&mut self, f: F)
+    where
+        F: Fn(Point2<f32>) -> Point2<f32>,
+    {
+        self.line_0 = f(self.line_0);
+        self.line_1 = f(self.line_1);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OccluderLine {
     pub line: Line,
@@ -41,6 +59,16 @@ pub struct OccluderLine {
     pub ignore_light_index2: Option<u32>,
 }
 
+/// A set of lines sharing the same occluder parameters, e.g. the merged outline
+/// from `geom::rects_outline` or `geom::grid_outline`.
+#[derive(Debug, Clone)]
+pub struct OccluderOutline {
+    pub lines: Vec<Line>,
+    pub height: f32,
+    pub ignore_light_index1: Option<u32>,
+    pub ignore_light_index2: Option<u32>,
+}
+
 #[derive(Debug, Clone)]
 pub struct OccluderRect {
     pub rect: Rect,
@@ -123,6 +151,22 @@ impl Geometry<LineTag> for OccluderLine {
     }
 }
 
+impl Geometry<LineTag> for OccluderOutline {
+    type Vertex = OccluderLineVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        for line in self.lines.iter().copied() {
+            OccluderLine {
+                line,
+                height: self.height,
+                ignore_light_index1: self.ignore_light_index1,
+                ignore_light_index2: self.ignore_light_index2,
+            }
+            .write(elements, vertices);
+        }
+    }
+}
+
 impl Geometry<LineTag> for OccluderRect {
     type Vertex = OccluderLineVertex;
 
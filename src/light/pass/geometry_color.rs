@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use crate::{
     data::ColorVertex,
-    gl::{self, DrawParams, DrawUnit, Element, Uniform},
+    gl::{self, DrawParams, DrawUnit, Uniform},
     program,
 };
 
@@ -62,15 +62,13 @@ impl GeometryColorPass {
         Ok(Self { program })
     }
 
-    pub fn draw<E>(
+    pub fn draw(
         &self,
         matrices: &Uniform<ViewMatrices>,
         object_light_props: &Uniform<ObjectLightProps>,
-        draw_unit: DrawUnit<ColorVertex, E>,
+        draw_unit: DrawUnit<ColorVertex>,
         draw_params: &DrawParams,
-    ) where
-        E: Element,
-    {
+    ) {
         gl::draw(
             &self.program,
             (matrices, object_light_props),
@@ -0,0 +1,95 @@
+use std::rc::Rc;
+
+use crate::{
+    data::SpriteVertex,
+    gl::{self, DrawParams, InstancedDrawUnit, Texture, Uniform},
+    pass::{SpriteInstance, ViewMatrices, MATRICES_BLOCK_BINDING},
+    program,
+};
+
+use super::{super::ObjectLightProps, OBJECT_LIGHT_PROPS_BLOCK_BINDING};
+
+program! {
+    program GeometryInstancedSpriteProgram
+    uniforms {
+        matrices: ViewMatrices = MATRICES_BLOCK_BINDING,
+        object_light_props: ObjectLightProps = OBJECT_LIGHT_PROPS_BLOCK_BINDING,
+    }
+    samplers {
+        sprite: Sampler2,
+    }
+    attributes {
+        a: SpriteVertex,
+        i: SpriteInstance,
+    }
+    vertex glsl! {
+        out vec2 v_uv;
+        out vec4 v_color;
+
+        void main() {
+            mat2 i_rot = mat2(
+                cos(i_angle), -sin(i_angle),
+                sin(i_angle), cos(i_angle)
+            );
+            vec2 world_pos = i_rot * (i_scale * (a_position.xy - i_pivot)) + i_position;
+
+            vec3 position = matrices.projection
+                * matrices.view
+                * vec3(world_pos, 1.0);
+            gl_Position = vec4(position.xy, a_position.z + i_z, 1.0);
+
+            v_uv = (i_tex_rect.xy + a_tex_coords * i_tex_rect.zw)
+                / vec2(textureSize(sprite, 0));
+            v_uv.y = 1.0 - v_uv.y;
+            v_color = a_color * i_color;
+        }
+    }
+    fragment glsl! {
+        in vec2 v_uv;
+        in vec4 v_color;
+        layout (location = 0) out vec4 f_albedo;
+        layout (location = 1) out vec4 f_normal;
+        layout (location = 2) out vec4 f_occlusion;
+
+        void main() {
+            vec4 albedo = texture(sprite, v_uv);
+            f_albedo = v_color * vec4(pow(albedo.rgb, vec3(2.2)), albedo.a);
+            f_normal = vec4(0.0, 0.0, 1.0, f_albedo.a);
+            f_occlusion = vec4(
+                object_light_props.occlusion,
+                object_light_props.reflectance,
+                0.0,
+                f_albedo.a
+            );
+        }
+    }
+}
+
+pub struct GeometryInstancedSpritePass {
+    program: GeometryInstancedSpriteProgram,
+}
+
+impl GeometryInstancedSpritePass {
+    pub fn new(gl: Rc<gl::Context>) -> Result<Self, gl::Error> {
+        let program = GeometryInstancedSpriteProgram::new(gl)?;
+
+        Ok(Self { program })
+    }
+
+    pub fn draw(
+        &self,
+        matrices: &Uniform<ViewMatrices>,
+        object_light_props: &Uniform<ObjectLightProps>,
+        texture: &Texture,
+        draw_unit: InstancedDrawUnit<(SpriteVertex, SpriteInstance)>,
+        draw_params: &DrawParams,
+    ) {
+        gl::draw_instanced(
+            &self.program,
+            (matrices, object_light_props),
+            [texture],
+            draw_unit,
+            draw_params,
+        );
+    }
+}
@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use crate::{
     data::SpriteVertex,
-    gl::{self, DrawParams, DrawUnit, Element, Texture, Uniform},
+    gl::{self, DrawParams, DrawUnit, Texture, Uniform},
     pass::{ViewMatrices, MATRICES_BLOCK_BINDING},
     program,
 };
@@ -90,17 +90,15 @@ impl GeometrySpriteWithNormalsPass {
         Ok(Self { program })
     }
 
-    pub fn draw<E>(
+    pub fn draw(
         &self,
         matrices: &Uniform<ViewMatrices>,
         object_light_props: &Uniform<ObjectLightProps>,
         texture: &Texture,
         normal_map: &Texture,
-        draw_unit: DrawUnit<SpriteVertex, E>,
+        draw_unit: DrawUnit<SpriteVertex>,
         draw_params: &DrawParams,
-    ) where
-        E: Element,
-    {
+    ) {
         gl::draw(
             &self.program,
             (matrices, object_light_props),
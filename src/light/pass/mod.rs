@@ -3,6 +3,7 @@ mod bindings;
 pub mod compose;
 pub mod compose_with_indirect;
 pub mod geometry_color;
+pub mod geometry_instanced_sprite;
 pub mod geometry_sprite;
 pub mod geometry_sprite_with_normals;
 pub mod screen_light;
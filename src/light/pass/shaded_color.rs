@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use crate::{
     data::ColorVertex,
-    gl::{self, DrawParams, DrawUnit, Element, Texture, Uniform},
+    gl::{self, DrawParams, DrawUnit, Texture, Uniform},
     pass::{ViewMatrices, MATRICES_BLOCK_BINDING},
     program,
 };
@@ -55,15 +55,13 @@ impl ShadedColorPass {
         Ok(Self { program })
     }
 
-    pub fn draw<E>(
+    pub fn draw(
         &self,
         matrices: &Uniform<ViewMatrices>,
         screen_light: &Texture,
-        draw_unit: DrawUnit<ColorVertex, E>,
+        draw_unit: DrawUnit<ColorVertex>,
         draw_params: &DrawParams,
-    ) where
-        E: Element,
-    {
+    ) {
         gl::draw(
             &self.program,
             matrices,
@@ -1,26 +1,33 @@
 //! This implementation follows the following with some modifications:
 //! https://www.gamasutra.com/blogs/RobWare/20180226/313491/Fast_2D_shadows_in_Unity_using_1D_shadow_mapping.php
 
-use std::{cell::RefCell, rc::Rc};
+use std::rc::Rc;
 
 use nalgebra::Vector2;
 use thiserror::Error;
 
+#[cfg(feature = "web")]
+use crate::Context;
 use crate::{
     data::{ColorVertex, SpriteVertex, TriangleBatch},
+    geom::Rect,
     gl::{
-        self, DrawParams, DrawUnit, Element, Framebuffer, NewFramebufferError, NewTextureError,
-        Texture, TextureParams, TextureValueType, Uniform, VertexBuffer,
+        self, DrawParams, DrawUnit, Framebuffer, InstancedDrawUnit, NewFramebufferError,
+        NewTextureError, Texture, TextureParams, TextureValueType, Uniform, VertexBuffer,
     },
-    pass::{BlurBuffer, BlurParams, BlurPass, ColorPass, GaussianMipmapStack, ViewMatrices},
-    Canvas, Color4, Context, FrameError,
+    pass::{
+        BlurBuffer, BlurParams, BlurPass, ColorPass, DecalLayer, GaussianMipmapStack,
+        SpriteInstance, ViewMatrices,
+    },
+    Color4, FrameError,
 };
 
 use super::{
     light_area::{LightAreaVertex, LightCircleSegment},
     pass::{
         compose::ComposePass, compose_with_indirect::ComposeWithIndirectPass,
-        geometry_color::GeometryColorPass, geometry_sprite::GeometrySpritePass,
+        geometry_color::GeometryColorPass, geometry_instanced_sprite::GeometryInstancedSpritePass,
+        geometry_sprite::GeometrySpritePass,
         geometry_sprite_with_normals::GeometrySpriteWithNormalsPass, screen_light::ScreenLightPass,
         shaded_color::ShadedColorPass, shaded_sprite::ShadedSpritePass, shadow_map::ShadowMapPass,
     },
@@ -28,22 +35,24 @@ use super::{
 };
 
 pub struct LightPipeline {
-    canvas: Rc<RefCell<Canvas>>,
     params: LightPipelineParams,
 
     light_instances: Rc<VertexBuffer<Light>>,
     light_area_batch: TriangleBatch<LightAreaVertex>,
     global_light_props: Uniform<GlobalLightProps>,
 
-    screen_geometry: Framebuffer,
-    screen_reflector: Framebuffer,
     shadow_map: Framebuffer,
-    screen_light: Framebuffer,
     blur_buffer: BlurBuffer,
 
+    // Screen targets of the current viewport size, and of recently used other
+    // sizes, so that split-screen rendering does not reallocate every frame.
+    screen: ScreenTargets,
+    unused_screens: Vec<ScreenTargets>,
+
     color_pass: Rc<ColorPass>,
     geometry_color_pass: GeometryColorPass,
     geometry_sprite_pass: GeometrySpritePass,
+    geometry_instanced_sprite_pass: GeometryInstancedSpritePass,
     geometry_sprite_normal_pass: GeometrySpriteWithNormalsPass,
     shadow_map_pass: ShadowMapPass,
     screen_light_pass: ScreenLightPass,
@@ -52,7 +61,12 @@ pub struct LightPipeline {
     compose_pass: ComposePass,
     compose_with_indirect_pass: ComposeWithIndirectPass,
     blur_pass: Rc<BlurPass>,
+}
 
+struct ScreenTargets {
+    geometry: Framebuffer,
+    reflector: Framebuffer,
+    light: Framebuffer,
     occlusion_mipmap_stack: GaussianMipmapStack,
     reflector_mipmap_stack: GaussianMipmapStack,
 }
@@ -73,57 +87,60 @@ const SCREEN_ALBEDO_LOCATION: usize = 0;
 const SCREEN_NORMALS_LOCATION: usize = 1;
 const SCREEN_OCCLUSION_LOCATION: usize = 2;
 
+/// Number of screen target sizes that are kept around besides the current
+/// one, e.g. for the other views in split-screen rendering.
+const MAX_UNUSED_SCREENS: usize = 3;
+
 impl LightPipeline {
+    #[cfg(feature = "web")]
     pub fn new(
         context: &Context,
         params: LightPipelineParams,
     ) -> Result<LightPipeline, NewLightPipelineError> {
-        let canvas = context.canvas();
-
-        let light_instances = Rc::new(VertexBuffer::new(context.gl())?);
-        let light_area_batch = TriangleBatch::new(context.gl())?;
-        let global_light_props = Uniform::new(context.gl(), GlobalLightProps::default())?;
-
-        let screen_geometry = new_screen_geometry(canvas.clone())?;
-        let screen_reflector = new_screen_reflector(canvas.clone())?;
-        let shadow_map = new_shadow_map(context.gl(), &params)?;
-        let screen_light = new_screen_light(canvas.clone())?;
-        let blur_buffer = BlurBuffer::new(context.gl())?;
-
-        let color_pass = context.color_pass();
-        let geometry_color_pass = GeometryColorPass::new(context.gl())?;
-        let geometry_sprite_pass = GeometrySpritePass::new(context.gl())?;
-        let geometry_sprite_normal_pass = GeometrySpriteWithNormalsPass::new(context.gl())?;
-        let shadow_map_pass = ShadowMapPass::new(context.gl(), params.max_num_lights)?;
-        let screen_light_pass = ScreenLightPass::new(context.gl(), params.clone())?;
-        let shaded_color_pass = ShadedColorPass::new(context.gl())?;
-        let shaded_sprite_pass = ShadedSpritePass::new(context.gl())?;
-        let compose_pass = ComposePass::new(context.gl())?;
-        let compose_with_indirect_pass =
-            ComposeWithIndirectPass::new(context.gl(), params.clone())?;
-        let blur_pass = Rc::new(BlurPass::new(context.gl(), BlurParams::default())?);
+        Self::with_gl(context.gl(), context.color_pass(), params)
+    }
 
-        let occlusion_mipmap_stack = GaussianMipmapStack::new(
-            blur_pass.clone(),
-            screen_geometry.textures()[SCREEN_OCCLUSION_LOCATION].clone(),
-        )?;
-        let reflector_mipmap_stack =
-            GaussianMipmapStack::new(blur_pass.clone(), screen_reflector.textures()[0].clone())?;
+    /// Creates the pipeline without a `Context`. The screen textures take the
+    /// size of the current main viewport.
+    pub fn with_gl(
+        gl: Rc<gl::Context>,
+        color_pass: Rc<ColorPass>,
+        params: LightPipelineParams,
+    ) -> Result<LightPipeline, NewLightPipelineError> {
+        let light_instances = Rc::new(VertexBuffer::new(gl.clone())?);
+        let light_area_batch = TriangleBatch::new(gl.clone())?;
+        let global_light_props = Uniform::new(gl.clone(), GlobalLightProps::default())?;
+
+        let shadow_map = new_shadow_map(gl.clone(), &params)?;
+        let blur_buffer = BlurBuffer::new(gl.clone())?;
+
+        let geometry_color_pass = GeometryColorPass::new(gl.clone())?;
+        let geometry_sprite_pass = GeometrySpritePass::new(gl.clone())?;
+        let geometry_instanced_sprite_pass = GeometryInstancedSpritePass::new(gl.clone())?;
+        let geometry_sprite_normal_pass = GeometrySpriteWithNormalsPass::new(gl.clone())?;
+        let shadow_map_pass = ShadowMapPass::new(gl.clone(), params.max_num_lights)?;
+        let screen_light_pass = ScreenLightPass::new(gl.clone(), params.clone())?;
+        let shaded_color_pass = ShadedColorPass::new(gl.clone())?;
+        let shaded_sprite_pass = ShadedSpritePass::new(gl.clone())?;
+        let compose_pass = ComposePass::new(gl.clone())?;
+        let compose_with_indirect_pass = ComposeWithIndirectPass::new(gl.clone(), params.clone())?;
+        let blur_pass = Rc::new(BlurPass::new(gl.clone(), BlurParams::default())?);
+
+        let screen = ScreenTargets::new(gl, blur_pass.clone())?;
 
         Ok(Self {
-            canvas,
             params,
             light_instances,
             light_area_batch,
             global_light_props,
-            screen_geometry,
-            screen_reflector,
             shadow_map,
-            screen_light,
             blur_buffer,
+            screen,
+            unused_screens: Vec::new(),
             color_pass,
             geometry_color_pass,
             geometry_sprite_pass,
+            geometry_instanced_sprite_pass,
             geometry_sprite_normal_pass,
             shadow_map_pass,
             screen_light_pass,
@@ -132,8 +149,6 @@ impl LightPipeline {
             compose_pass,
             compose_with_indirect_pass,
             blur_pass,
-            occlusion_mipmap_stack,
-            reflector_mipmap_stack,
         })
     }
 
@@ -150,23 +165,23 @@ impl LightPipeline {
     }
 
     pub fn screen_albedo(&self) -> &Texture {
-        &self.screen_geometry.textures()[SCREEN_ALBEDO_LOCATION]
+        &self.screen.geometry.textures()[SCREEN_ALBEDO_LOCATION]
     }
 
     pub fn screen_normals(&self) -> &Texture {
-        &self.screen_geometry.textures()[SCREEN_NORMALS_LOCATION]
+        &self.screen.geometry.textures()[SCREEN_NORMALS_LOCATION]
     }
 
     pub fn screen_occlusion(&self) -> &Texture {
-        &self.screen_geometry.textures()[SCREEN_OCCLUSION_LOCATION]
+        &self.screen.geometry.textures()[SCREEN_OCCLUSION_LOCATION]
     }
 
     pub fn screen_reflector(&self) -> &Texture {
-        &self.screen_reflector.textures()[0]
+        &self.screen.reflector.textures()[0]
     }
 
     pub fn screen_light(&self) -> &Texture {
-        &self.screen_light.textures()[0]
+        &self.screen.light.textures()[0]
     }
 
     pub fn new_occluder_batch(&self) -> Result<OccluderBatch, gl::Error> {
@@ -177,25 +192,30 @@ impl LightPipeline {
         &'a mut self,
         matrices: &'a Uniform<ViewMatrices>,
     ) -> Result<GeometryPhase<'a>, FrameError> {
-        if self.screen_geometry.textures()[0].size() != screen_light_size(self.canvas.clone()) {
-            self.screen_geometry = new_screen_geometry(self.canvas.clone())?;
-            self.screen_reflector = new_screen_reflector(self.canvas.clone())?;
-            self.screen_light = new_screen_light(self.canvas.clone())?;
-            self.occlusion_mipmap_stack = GaussianMipmapStack::new(
-                self.blur_pass.clone(),
-                self.screen_geometry.textures()[SCREEN_OCCLUSION_LOCATION].clone(),
-            )?;
-            self.reflector_mipmap_stack = GaussianMipmapStack::new(
-                self.blur_pass.clone(),
-                self.screen_reflector.textures()[0].clone(),
-            )?;
+        let size = screen_light_size(&self.gl());
+        if self.screen.size() != size {
+            let screen = match self
+                .unused_screens
+                .iter()
+                .position(|screen| screen.size() == size)
+            {
+                Some(index) => self.unused_screens.remove(index),
+                None => ScreenTargets::new(self.gl(), self.blur_pass.clone())?,
+            };
+
+            let prev_screen = std::mem::replace(&mut self.screen, screen);
+            self.unused_screens.push(prev_screen);
+
+            if self.unused_screens.len() > MAX_UNUSED_SCREENS {
+                self.unused_screens.remove(0);
+            }
         }
 
         {
             #[cfg(feature = "coarse-prof")]
             coarse_prof::profile!("clear");
 
-            gl::with_framebuffer(&self.screen_geometry, || {
+            gl::with_framebuffer(&self.screen.geometry, || {
                 gl::clear_color_and_depth(&self.gl(), Color4::new(0.0, 0.0, 0.0, 1.0), 1.0);
             });
         }
@@ -251,16 +271,14 @@ pub struct ComposeWithIndirectPhase<'a> {
 }
 
 impl<'a> GeometryPhase<'a> {
-    pub fn draw_colors<E>(
+    pub fn draw_colors(
         self,
         object_light_params: &Uniform<ObjectLightProps>,
-        draw_unit: DrawUnit<ColorVertex, E>,
+        draw_unit: DrawUnit<ColorVertex>,
         draw_params: &DrawParams,
     ) -> Self
-    where
-        E: Element,
-    {
-        gl::with_framebuffer(&self.pipeline.screen_geometry, || {
+where {
+        gl::with_framebuffer(&self.pipeline.screen.geometry, || {
             self.pipeline.geometry_color_pass.draw(
                 self.input.matrices,
                 object_light_params,
@@ -272,17 +290,15 @@ impl<'a> GeometryPhase<'a> {
         self
     }
 
-    pub fn draw_sprites<E>(
+    pub fn draw_sprites(
         self,
         object_light_params: &Uniform<ObjectLightProps>,
         texture: &Texture,
-        draw_unit: DrawUnit<SpriteVertex, E>,
+        draw_unit: DrawUnit<SpriteVertex>,
         draw_params: &DrawParams,
     ) -> Self
-    where
-        E: Element,
-    {
-        gl::with_framebuffer(&self.pipeline.screen_geometry, || {
+where {
+        gl::with_framebuffer(&self.pipeline.screen.geometry, || {
             self.pipeline.geometry_sprite_pass.draw(
                 self.input.matrices,
                 object_light_params,
@@ -295,18 +311,61 @@ impl<'a> GeometryPhase<'a> {
         self
     }
 
-    pub fn draw_sprites_with_normals<E>(
+    /// Draws the chunks of a `DecalLayer` as albedo. `draw_params` should use
+    /// `DecalLayer::blend`.
+    pub fn draw_decals(
+        self,
+        object_light_params: &Uniform<ObjectLightProps>,
+        decals: &DecalLayer,
+        visible_rect: Rect,
+        draw_params: &DrawParams,
+    ) -> Self {
+        gl::with_framebuffer(&self.pipeline.screen.geometry, || {
+            for chunk in decals.chunks_in(visible_rect) {
+                self.pipeline.geometry_sprite_pass.draw(
+                    self.input.matrices,
+                    object_light_params,
+                    chunk.texture(),
+                    chunk.draw_unit(),
+                    draw_params,
+                );
+            }
+        });
+
+        self
+    }
+
+    pub fn draw_instanced_sprites(
+        self,
+        object_light_params: &Uniform<ObjectLightProps>,
+        texture: &Texture,
+        draw_unit: InstancedDrawUnit<(SpriteVertex, SpriteInstance)>,
+        draw_params: &DrawParams,
+    ) -> Self
+where {
+        gl::with_framebuffer(&self.pipeline.screen.geometry, || {
+            self.pipeline.geometry_instanced_sprite_pass.draw(
+                self.input.matrices,
+                object_light_params,
+                texture,
+                draw_unit,
+                draw_params,
+            );
+        });
+
+        self
+    }
+
+    pub fn draw_sprites_with_normals(
         self,
         object_light_params: &Uniform<ObjectLightProps>,
         texture: &Texture,
         normal_map: &Texture,
-        draw_unit: DrawUnit<SpriteVertex, E>,
+        draw_unit: DrawUnit<SpriteVertex>,
         draw_params: &DrawParams,
     ) -> Self
-    where
-        E: Element,
-    {
-        gl::with_framebuffer(&self.pipeline.screen_geometry, || {
+where {
+        gl::with_framebuffer(&self.pipeline.screen.geometry, || {
             self.pipeline.geometry_sprite_normal_pass.draw(
                 self.input.matrices,
                 object_light_params,
@@ -389,9 +448,9 @@ impl<'a> ShadowMapPhase<'a> {
                     }),
             );
 
-        gl::with_framebuffer(&self.pipeline.screen_light, || {
+        gl::with_framebuffer(&self.pipeline.screen.light, || {
             gl::clear_color(
-                &self.pipeline.screen_light.gl(),
+                &self.pipeline.screen.light.gl(),
                 Color4::new(0.0, 0.0, 0.0, 1.0),
             );
 
@@ -400,17 +459,17 @@ impl<'a> ShadowMapPhase<'a> {
                 self.input.matrices,
                 &self.pipeline.global_light_props,
                 &self.pipeline.shadow_map.textures()[0],
-                &self.pipeline.screen_geometry.textures()[SCREEN_NORMALS_LOCATION],
+                &self.pipeline.screen.geometry.textures()[SCREEN_NORMALS_LOCATION],
                 draw_unit,
             );
         });
 
         /*self.pipeline.blur_pass.blur(
             10,
-            &self.pipeline.screen_light.textures()[0],
+            &self.pipeline.screen.light.textures()[0],
             0,
             &mut self.pipeline.blur_buffer,
-            &self.pipeline.screen_light,
+            &self.pipeline.screen.light,
         )?;*/
 
         //self.pipeline.shadow_map.invalidate();
@@ -429,7 +488,7 @@ impl<'a> ShadowMapPhase<'a> {
 
 impl<'a> BuiltScreenLightPhase<'a> {
     pub fn indirect_light_phase(self) -> IndirectLightPhase<'a> {
-        gl::with_framebuffer(&self.pipeline.screen_reflector, || {
+        gl::with_framebuffer(&self.pipeline.screen.reflector, || {
             gl::clear_color(&self.pipeline.gl(), Color4::new(0.0, 0.0, 0.0, 1.0));
         });
 
@@ -447,13 +506,13 @@ impl<'a> BuiltScreenLightPhase<'a> {
     pub fn compose(self) {
         self.pipeline.compose_pass.draw(
             &self.pipeline.global_light_props,
-            &self.pipeline.screen_geometry.textures()[SCREEN_ALBEDO_LOCATION],
-            &self.pipeline.screen_light.textures()[0],
+            &self.pipeline.screen.geometry.textures()[SCREEN_ALBEDO_LOCATION],
+            &self.pipeline.screen.light.textures()[0],
         );
 
-        /*self.pipeline.screen_geometry.invalidate();
+        /*self.pipeline.screen.geometry.invalidate();
         self.pipeline.screen_reflectors.invalidate();
-        self.pipeline.screen_light.invalidate();*/
+        self.pipeline.screen.light.invalidate();*/
     }
 }
 
@@ -470,10 +529,10 @@ impl<'a> IndirectLightPhase<'a> {
         draw_unit: DrawUnit<ColorVertex>,
         draw_params: &DrawParams,
     ) -> Self {
-        gl::with_framebuffer(&self.pipeline.screen_reflector, || {
+        gl::with_framebuffer(&self.pipeline.screen.reflector, || {
             self.pipeline.shaded_color_pass.draw(
                 self.input.matrices,
-                &self.pipeline.screen_light.textures()[0],
+                &self.pipeline.screen.light.textures()[0],
                 draw_unit,
                 &Self::draw_params(draw_params),
             );
@@ -488,11 +547,11 @@ impl<'a> IndirectLightPhase<'a> {
         draw_unit: DrawUnit<SpriteVertex>,
         draw_params: &DrawParams,
     ) -> Self {
-        gl::with_framebuffer(&self.pipeline.screen_reflector, || {
+        gl::with_framebuffer(&self.pipeline.screen.reflector, || {
             self.pipeline.shaded_sprite_pass.draw(
                 self.input.matrices,
                 texture,
-                &self.pipeline.screen_light.textures()[0],
+                &self.pipeline.screen.light.textures()[0],
                 draw_unit,
                 &Self::draw_params(draw_params),
             )
@@ -502,7 +561,7 @@ impl<'a> IndirectLightPhase<'a> {
     }
 
     pub fn draw_color_sources(self, draw_unit: DrawUnit<ColorVertex>) -> Self {
-        gl::with_framebuffer(&self.pipeline.screen_reflector, || {
+        gl::with_framebuffer(&self.pipeline.screen.reflector, || {
             self.pipeline.color_pass.draw(
                 self.input.matrices,
                 draw_unit,
@@ -518,8 +577,14 @@ impl<'a> IndirectLightPhase<'a> {
         blur: bool,
     ) -> Result<ComposeWithIndirectPhase<'a>, FrameError> {
         if blur {
-            self.pipeline.occlusion_mipmap_stack.create_mipmaps()?;
-            self.pipeline.reflector_mipmap_stack.create_mipmaps()?;
+            self.pipeline
+                .screen
+                .occlusion_mipmap_stack
+                .create_mipmaps()?;
+            self.pipeline
+                .screen
+                .reflector_mipmap_stack
+                .create_mipmaps()?;
         } else {
             self.pipeline.screen_occlusion().generate_mipmap();
             self.pipeline.screen_reflector().generate_mipmap();
@@ -540,23 +605,51 @@ impl<'a> ComposeWithIndirectPhase<'a> {
     pub fn compose(self) {
         self.pipeline.compose_with_indirect_pass.draw(
             &self.pipeline.global_light_props,
-            &self.pipeline.screen_geometry.textures()[SCREEN_ALBEDO_LOCATION],
-            &self.pipeline.screen_geometry.textures()[SCREEN_NORMALS_LOCATION],
-            &self.pipeline.screen_geometry.textures()[SCREEN_OCCLUSION_LOCATION],
-            &self.pipeline.screen_reflector.textures()[0],
-            &self.pipeline.screen_light.textures()[0],
+            &self.pipeline.screen.geometry.textures()[SCREEN_ALBEDO_LOCATION],
+            &self.pipeline.screen.geometry.textures()[SCREEN_NORMALS_LOCATION],
+            &self.pipeline.screen.geometry.textures()[SCREEN_OCCLUSION_LOCATION],
+            &self.pipeline.screen.reflector.textures()[0],
+            &self.pipeline.screen.light.textures()[0],
         );
 
-        /*self.pipeline.screen_geometry.invalidate();
+        /*self.pipeline.screen.geometry.invalidate();
         self.pipeline.screen_reflectors.invalidate();
-        self.pipeline.screen_light.invalidate();*/
+        self.pipeline.screen.light.invalidate();*/
     }
 }
 
-fn screen_light_size(canvas: Rc<RefCell<Canvas>>) -> Vector2<u32> {
-    let canvas = canvas.borrow();
-    let physical_size = canvas.physical_size();
-    let max_size = Texture::max_size(&*canvas.gl());
+impl ScreenTargets {
+    fn new(gl: Rc<gl::Context>, blur_pass: Rc<BlurPass>) -> Result<Self, NewFramebufferError> {
+        let geometry = new_screen_geometry(gl.clone())?;
+        let reflector = new_screen_reflector(gl.clone())?;
+        let light = new_screen_light(gl)?;
+        let occlusion_mipmap_stack = GaussianMipmapStack::new(
+            blur_pass.clone(),
+            geometry.textures()[SCREEN_OCCLUSION_LOCATION].clone(),
+        )?;
+        let reflector_mipmap_stack =
+            GaussianMipmapStack::new(blur_pass, reflector.textures()[0].clone())?;
+
+        Ok(Self {
+            geometry,
+            reflector,
+            light,
+            occlusion_mipmap_stack,
+            reflector_mipmap_stack,
+        })
+    }
+
+    fn size(&self) -> Vector2<u32> {
+        self.geometry.textures()[0].size()
+    }
+}
+
+fn screen_light_size(gl: &gl::Context) -> Vector2<u32> {
+    // Follow the main viewport rather than the canvas, so that the screen
+    // textures have the right aspect ratio when rendering split-screen.
+    let viewport = gl.main_viewport();
+    let physical_size = Vector2::new(viewport[2].max(1) as u32, viewport[3].max(1) as u32);
+    let max_size = Texture::max_size(gl);
 
     Vector2::new(physical_size.x.min(max_size), physical_size.y.min(max_size))
 }
@@ -565,58 +658,60 @@ fn new_shadow_map(
     gl: Rc<gl::Context>,
     params: &LightPipelineParams,
 ) -> Result<Framebuffer, NewFramebufferError> {
-    Framebuffer::from_textures(vec![Texture::new(
+    let shadow_map = Framebuffer::from_textures(vec![Texture::new(
         gl,
         Vector2::new(params.shadow_map_resolution, params.max_num_lights),
         TextureParams::linear(TextureValueType::RgF16),
-    )?])
+    )?])?;
+    shadow_map.textures()[0].set_label("light shadow map");
+
+    Ok(shadow_map)
 }
 
-fn new_screen_geometry(canvas: Rc<RefCell<Canvas>>) -> Result<Framebuffer, NewFramebufferError> {
-    let size = screen_light_size(canvas.clone());
+fn new_screen_geometry(gl: Rc<gl::Context>) -> Result<Framebuffer, NewFramebufferError> {
+    let size = screen_light_size(&gl);
     let albedo = Texture::new(
-        canvas.borrow().gl(),
+        gl.clone(),
         size,
         TextureParams::nearest(TextureValueType::RgbU8),
     )?;
     let normals = Texture::new(
-        canvas.borrow().gl(),
+        gl.clone(),
         size,
         TextureParams::nearest(TextureValueType::RgbaF16),
     )?;
     let occluder = Texture::new(
-        canvas.borrow().gl(),
+        gl.clone(),
         size,
         TextureParams::linear_mipmapped(TextureValueType::RgF16),
     )?;
-    let depth = Texture::new(
-        canvas.borrow().gl(),
-        size,
-        TextureParams::nearest(TextureValueType::Depth),
-    )?;
+    let depth = Texture::new(gl, size, TextureParams::nearest(TextureValueType::Depth))?;
+
+    albedo.set_label("light screen albedo");
+    normals.set_label("light screen normals");
+    occluder.set_label("light screen occluder");
+    depth.set_label("light screen depth");
 
     // Texture order corresponds to SCREEN_ALBEDO_LOCATION, etc.
     Framebuffer::from_textures(vec![albedo, normals, occluder, depth])
 }
 
-fn new_screen_reflector(canvas: Rc<RefCell<Canvas>>) -> Result<Framebuffer, NewFramebufferError> {
-    let size = screen_light_size(canvas.clone());
+fn new_screen_reflector(gl: Rc<gl::Context>) -> Result<Framebuffer, NewFramebufferError> {
+    let size = screen_light_size(&gl);
     let reflector = Texture::new(
-        canvas.borrow().gl(),
+        gl,
         size,
         TextureParams::linear_mipmapped(TextureValueType::RgbaF16),
     )?;
+    reflector.set_label("light screen reflector");
 
     Framebuffer::from_textures(vec![reflector])
 }
 
-fn new_screen_light(canvas: Rc<RefCell<Canvas>>) -> Result<Framebuffer, NewFramebufferError> {
-    let size = screen_light_size(canvas.clone());
-    let light = Texture::new(
-        canvas.borrow().gl(),
-        size,
-        TextureParams::linear(TextureValueType::RgbaF16),
-    )?;
+fn new_screen_light(gl: Rc<gl::Context>) -> Result<Framebuffer, NewFramebufferError> {
+    let size = screen_light_size(&gl);
+    let light = Texture::new(gl, size, TextureParams::linear(TextureValueType::RgbaF16))?;
+    light.set_label("light screen light");
 
     Framebuffer::from_textures(vec![light])
 }
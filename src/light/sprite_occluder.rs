@@ -0,0 +1,93 @@
+use nalgebra::{Point2, Rotation2, Vector2};
+
+use crate::geom::{self, ContourError, Line, Rect, RotatedRect};
+
+use super::{OccluderLine, OccluderOutline};
+
+/// Occluder geometry that follows the opaque shape of a sprite.
+///
+/// Lines are stored in sprite-local coordinates, where the sprite covers
+/// `[-0.5, 0.5]` in both axes with Y pointing down, so that they can be
+/// placed onto any `Rect` or `RotatedRect` that the sprite is drawn to.
+#[derive(Debug, Clone)]
+pub struct SpriteOccluder {
+    lines: Vec<Line>,
+}
+
+impl SpriteOccluder {
+    /// Traces the outline of the pixels whose alpha is larger than
+    /// `alpha_threshold` and simplifies it so that it deviates by at most
+    /// `tolerance` pixels.
+    pub fn from_rgba(
+        data: &[u8],
+        size: Vector2<u32>,
+        alpha_threshold: u8,
+        tolerance: f32,
+    ) -> Result<Self, ContourError> {
+        let size_f = Vector2::new(size.x as f32, size.y as f32);
+        let to_local =
+            |p: Point2<f32>| Point2::from(p.coords.component_div(&size_f) - Vector2::new(0.5, 0.5));
+
+        let mut lines = Vec::new();
+
+        for contour in geom::alpha_contours(data, size, alpha_threshold)? {
+            let contour = geom::simplify_polygon(&contour, tolerance);
+            if contour.len() < 3 {
+                continue;
+            }
+
+            for i in 0..contour.len() {
+                lines.push(Line(
+                    to_local(contour[i]),
+                    to_local(contour[(i + 1) % contour.len()]),
+                ));
+            }
+        }
+
+        Ok(Self { lines })
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Returns the lines in sprite-local coordinates.
+    pub fn local_occluder_lines(&self, height: f32) -> impl Iterator<Item = OccluderLine> + '_ {
+        self.lines.iter().map(move |line| OccluderLine {
+            line: *line,
+            height,
+            ignore_light_index1: None,
+            ignore_light_index2: None,
+        })
+    }
+
+    /// Places the lines onto a sprite that is drawn to `rect`.
+    pub fn to_rect(&self, rect: Rect, height: f32) -> OccluderOutline {
+        self.transform(height, |p| rect.center + p.coords.component_mul(&rect.size))
+    }
+
+    /// Places the lines onto a sprite that is drawn to `rect`.
+    pub fn to_rotated_rect(&self, rect: RotatedRect, height: f32) -> OccluderOutline {
+        let rotation = Rotation2::new(rect.angle);
+
+        self.transform(height, |p| {
+            rect.center + rotation * p.coords.component_mul(&rect.size)
+        })
+    }
+
+    fn transform<F>(&self, height: f32, f: F) -> OccluderOutline
+    where
+        F: Fn(Point2<f32>) -> Point2<f32>,
+    {
+        OccluderOutline {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| Line(f(line.0), f(line.1)))
+                .collect(),
+            height,
+            ignore_light_index1: None,
+            ignore_light_index2: None,
+        }
+    }
+}
@@ -1,8 +1,8 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 use crate::{
-    data::ColorVertex,
-    gl::{self, DrawParams, DrawUnit, Element, Uniform},
+    data::{ColorVertex, PackedColorVertex},
+    gl::{self, DrawParams, DrawUnit, Uniform},
     program,
 };
 
@@ -41,23 +41,50 @@ program! {
 
 pub struct ColorPass {
     program: ColorProgram,
+
+    // Compiled on first use, since most users never draw packed vertices.
+    packed_program: RefCell<Option<gl::Program<ViewMatrices, PackedColorVertex, 0>>>,
 }
 
 impl ColorPass {
     pub fn new(gl: Rc<gl::Context>) -> Result<Self, gl::Error> {
         let program = ColorProgram::new(gl)?;
 
-        Ok(Self { program })
+        Ok(Self {
+            program,
+            packed_program: RefCell::new(None),
+        })
     }
 
-    pub fn draw<E>(
+    pub fn draw(
         &self,
         matrices: &Uniform<ViewMatrices>,
-        draw_unit: DrawUnit<ColorVertex, E>,
+        draw_unit: DrawUnit<ColorVertex>,
         params: &DrawParams,
-    ) where
-        E: Element,
-    {
+    ) {
         gl::draw(&self.program, matrices, [], draw_unit, params);
     }
+
+    pub fn draw_packed(
+        &self,
+        matrices: &Uniform<ViewMatrices>,
+        draw_unit: DrawUnit<PackedColorVertex>,
+        params: &DrawParams,
+    ) -> Result<(), gl::Error> {
+        let mut packed_program = self.packed_program.borrow_mut();
+        if packed_program.is_none() {
+            // Same shader, only with 8-bit colors in the vertex buffer.
+            *packed_program = Some(gl::Program::new(self.program.gl(), ColorProgram::def())?);
+        }
+
+        gl::draw(
+            packed_program.as_ref().unwrap(),
+            matrices,
+            [],
+            draw_unit,
+            params,
+        );
+
+        Ok(())
+    }
 }
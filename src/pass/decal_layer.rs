@@ -0,0 +1,351 @@
+use std::{cmp::Ordering, collections::HashMap, ops::Range, rc::Rc};
+
+use nalgebra::{Matrix3, Point2, Vector2};
+
+use crate::{
+    data::{Geometry, Mesh, Sprite, SpriteBatch, SpriteVertex, TriangleTag},
+    geom::{Camera, Rect, Screen},
+    gl::{
+        self, Blend, BlendFactor, BlendFunc, DrawParams, DrawUnit, Framebuffer,
+        NewFramebufferError, PrimitiveMode, Texture, TextureParams, Uniform,
+    },
+    Color4,
+};
+
+use super::{SpritePass, ViewMatrices};
+
+#[derive(Debug, Clone)]
+pub struct DecalLayerParams {
+    /// Size of a chunk in world units.
+    pub chunk_size: f32,
+
+    /// Resolution of the chunk textures.
+    pub texels_per_unit: f32,
+
+    /// Upper bound on the number of chunks. When more chunks are needed, the
+    /// ones farthest away from the camera are dropped, together with their
+    /// decals. Chunks that are touched by a single `bake` call are never
+    /// dropped by it, so the bound can be exceeded temporarily.
+    pub max_chunks: usize,
+}
+
+type ChunkKey = (i32, i32);
+
+/// A square of the world into which decals have been baked.
+pub struct DecalChunk {
+    rect: Rect,
+    framebuffer: Framebuffer,
+    mesh: Mesh<SpriteVertex>,
+}
+
+/// Persistent marks in the world, e.g. scorch marks or splatter.
+///
+/// Decals are baked into render textures once, so drawing the layer costs the
+/// same no matter how many decals have been added. The world is divided into
+/// square chunks, whose textures are only allocated once a decal touches
+/// them.
+///
+/// Chunks store colors premultiplied by alpha, so the layer needs to be drawn
+/// with `DecalLayer::blend`.
+///
+/// Baked decals only live on the GPU, so they are lost together with the
+/// WebGL context. The chunks are dropped when that happens, and decals that
+/// are baked afterwards go into freshly allocated chunks.
+pub struct DecalLayer {
+    gl: Rc<gl::Context>,
+    sprite_pass: Rc<SpritePass>,
+    params: DecalLayerParams,
+    chunks: HashMap<ChunkKey, DecalChunk>,
+    focus: Point2<f32>,
+    generation: u64,
+
+    bake_matrices: Uniform<ViewMatrices>,
+    bake_batch: SpriteBatch,
+}
+
+impl Default for DecalLayerParams {
+    fn default() -> Self {
+        Self {
+            chunk_size: 512.0,
+            texels_per_unit: 1.0,
+            max_chunks: 64,
+        }
+    }
+}
+
+impl DecalChunk {
+    /// Returns the world rectangle that is covered by the chunk.
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.framebuffer.textures()[0]
+    }
+
+    /// Returns a quad that covers the chunk, to be drawn with `texture`.
+    pub fn draw_unit(&self) -> DrawUnit<SpriteVertex> {
+        self.mesh.draw_unit()
+    }
+}
+
+impl DecalLayer {
+    pub fn new(
+        gl: Rc<gl::Context>,
+        sprite_pass: Rc<SpritePass>,
+        params: DecalLayerParams,
+    ) -> Result<Self, NewFramebufferError> {
+        assert!(params.chunk_size > 0.0);
+        assert!(params.texels_per_unit > 0.0);
+
+        let bake_matrices = Uniform::new(gl.clone(), ViewMatrices::default())?;
+        let bake_batch = SpriteBatch::new(gl.clone())?;
+        let generation = gl.generation();
+
+        Ok(Self {
+            gl,
+            sprite_pass,
+            params,
+            chunks: HashMap::new(),
+            focus: Point2::origin(),
+            generation,
+            bake_matrices,
+            bake_batch,
+        })
+    }
+
+    /// Blending for drawing the chunks.
+    pub fn blend() -> Blend {
+        Blend {
+            func: BlendFunc::same(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+            ..Blend::default()
+        }
+    }
+
+    pub fn params(&self) -> &DecalLayerParams {
+        &self.params
+    }
+
+    pub fn num_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Removes all decals and frees the chunk textures.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Moves the point around which chunks are kept, and drops chunks that
+    /// exceed `max_chunks`.
+    pub fn update(&mut self, camera: &Camera) {
+        self.focus = camera.center;
+        self.drop_lost_chunks();
+        self.drop_far_chunks(&[]);
+    }
+
+    /// Bakes decals into the chunks that they touch.
+    ///
+    /// Decals are given in world coordinates. Their depth is ignored.
+    pub fn bake<G, I>(&mut self, texture: &Texture, decals: I) -> Result<(), NewFramebufferError>
+    where
+        G: Geometry<TriangleTag, Vertex = SpriteVertex>,
+        I: IntoIterator<Item = G>,
+    {
+        self.drop_lost_chunks();
+
+        // Element ranges of the decals that overlap each chunk.
+        let mut keys: Vec<(ChunkKey, Vec<Range<usize>>)> = Vec::new();
+
+        self.bake_batch.clear();
+
+        for decal in decals {
+            let first_element = self.bake_batch.num_elements();
+            let first_vertex = self.bake_batch.num_vertices();
+            self.bake_batch.push(decal);
+
+            let range = first_element..self.bake_batch.num_elements();
+            let bounds = match bounds(&self.bake_batch.buffer().vertices()[first_vertex..]) {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+
+            for key in self.chunk_keys(bounds) {
+                let ranges = match keys.iter_mut().find(|(other, _)| *other == key) {
+                    Some((_, ranges)) => ranges,
+                    None => {
+                        keys.push((key, Vec::new()));
+                        &mut keys.last_mut().unwrap().1
+                    }
+                };
+
+                // Decals that follow each other are drawn together.
+                match ranges.last_mut() {
+                    Some(last) if last.end == range.start => last.end = range.end,
+                    _ => ranges.push(range.clone()),
+                }
+            }
+        }
+
+        self.bake_batch.flush();
+
+        let draw_params = DrawParams {
+            blend: Some(Blend {
+                func: BlendFunc {
+                    src_color: BlendFactor::SrcAlpha,
+                    src_alpha: BlendFactor::One,
+                    dst_color: BlendFactor::OneMinusSrcAlpha,
+                    dst_alpha: BlendFactor::OneMinusSrcAlpha,
+                },
+                ..Blend::default()
+            }),
+            ..DrawParams::default()
+        };
+
+        for (key, ranges) in keys.iter() {
+            let key = *key;
+            if !self.chunks.contains_key(&key) {
+                let chunk = self.new_chunk(key)?;
+                self.chunks.insert(key, chunk);
+            }
+
+            let chunk = &self.chunks[&key];
+
+            // Map the chunk's world rectangle onto the whole render texture.
+            self.bake_matrices.set(ViewMatrices {
+                projection: Screen::project_to_ndc(chunk.rect.size),
+                view: Matrix3::new_translation(&-chunk.rect.top_left().coords),
+            });
+
+            gl::with_framebuffer(&chunk.framebuffer, || {
+                for range in ranges {
+                    self.sprite_pass.draw(
+                        &self.bake_matrices,
+                        texture,
+                        DrawUnit::new(
+                            self.bake_batch.vertex_array(),
+                            PrimitiveMode::Triangle,
+                            range.clone(),
+                        ),
+                        &draw_params,
+                    );
+                }
+            });
+        }
+
+        let touched: Vec<_> = keys.iter().map(|(key, _)| *key).collect();
+        self.drop_far_chunks(&touched);
+
+        Ok(())
+    }
+
+    /// Returns the chunks that overlap with `rect`.
+    pub fn chunks_in(&self, rect: Rect) -> impl Iterator<Item = &DecalChunk> {
+        self.chunk_keys(rect)
+            .filter_map(move |key| self.chunks.get(&key))
+    }
+
+    /// Draws the chunks that overlap with `visible_rect`, which usually is
+    /// `Camera::visible_world_rect`.
+    pub fn draw(&self, matrices: &Uniform<ViewMatrices>, visible_rect: Rect, params: &DrawParams) {
+        if self.generation != self.gl.generation() {
+            // The chunks are blank until `update` or `bake` drops them.
+            return;
+        }
+
+        for chunk in self.chunks_in(visible_rect) {
+            self.sprite_pass
+                .draw(matrices, chunk.texture(), chunk.draw_unit(), params);
+        }
+    }
+
+    fn chunk_keys(&self, rect: Rect) -> impl Iterator<Item = ChunkKey> {
+        let size = self.params.chunk_size;
+        let min_x = (rect.left_x() / size).floor() as i32;
+        let max_x = (rect.right_x() / size).floor() as i32;
+        let min_y = (rect.top_y() / size).floor() as i32;
+        let max_y = (rect.bottom_y() / size).floor() as i32;
+
+        (min_y..=max_y).flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+    }
+
+    fn new_chunk(&self, key: ChunkKey) -> Result<DecalChunk, NewFramebufferError> {
+        let size = self.params.chunk_size;
+        let rect = Rect::from_top_left(
+            Point2::new(key.0 as f32 * size, key.1 as f32 * size),
+            Vector2::new(size, size),
+        );
+
+        let texels = (size * self.params.texels_per_unit).ceil() as u32;
+        let texture = Texture::new(
+            self.gl.clone(),
+            Vector2::new(texels, texels),
+            TextureParams::linear_rgbau8(),
+        )?;
+        let framebuffer = Framebuffer::from_textures(vec![texture])?;
+
+        gl::with_framebuffer(&framebuffer, || {
+            gl::clear_color(&self.gl, Color4::new(0.0, 0.0, 0.0, 0.0));
+        });
+
+        let mesh = Mesh::from_geometry(
+            self.gl.clone(),
+            Sprite {
+                rect,
+                depth: 0.0,
+                tex_rect: Rect::from_top_left(
+                    Point2::origin(),
+                    Vector2::new(texels as f32, texels as f32),
+                ),
+                color: Color4::new(1.0, 1.0, 1.0, 1.0),
+            },
+        )?;
+
+        Ok(DecalChunk {
+            rect,
+            framebuffer,
+            mesh,
+        })
+    }
+
+    /// Drops all chunks if their contents have been lost with the context.
+    fn drop_lost_chunks(&mut self) {
+        if self.generation != self.gl.generation() {
+            self.chunks.clear();
+            self.generation = self.gl.generation();
+        }
+    }
+
+    /// Drops the chunks farthest away from the focus until there are at most
+    /// `max_chunks`, never dropping the chunks in `keep`.
+    fn drop_far_chunks(&mut self, keep: &[ChunkKey]) {
+        if self.chunks.len() <= self.params.max_chunks {
+            return;
+        }
+
+        let focus = self.focus;
+        let mut keys: Vec<_> = self
+            .chunks
+            .iter()
+            .filter(|(key, _)| !keep.contains(key))
+            .map(|(key, chunk)| (*key, (chunk.rect.center - focus).norm_squared()))
+            .collect();
+        keys.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let num_drop = (self.chunks.len() - self.params.max_chunks).min(keys.len());
+        for (key, _) in keys.drain(keys.len() - num_drop..) {
+            self.chunks.remove(&key);
+        }
+    }
+}
+
+fn bounds(vertices: &[SpriteVertex]) -> Option<Rect> {
+    let first = vertices.first()?.position.xy();
+    let (min, max) = vertices.iter().fold((first, first), |(min, max), v| {
+        (
+            Point2::new(min.x.min(v.position.x), min.y.min(v.position.y)),
+            Point2::new(max.x.max(v.position.x), max.y.max(v.position.y)),
+        )
+    });
+
+    Some(Rect::from_top_left(min, max - min))
+}
@@ -0,0 +1,324 @@
+use std::rc::Rc;
+
+use nalgebra::{Matrix3, Point2, Point3, Vector2};
+
+use crate::{
+    data::{
+        ColorCircle, ColorRect, ColorTriangleBatch, ColorVertex, Geometry, Mesh, Sprite,
+        SpriteVertex, TriangleTag,
+    },
+    geom::{Circle, Rect, Screen},
+    gl::{self, DrawParams, Framebuffer, NewFramebufferError, Texture, TextureParams, Uniform},
+    program, Color4, FrameError,
+};
+
+use super::{BlurBuffer, BlurPass, ColorPass, ViewMatrices, MATRICES_BLOCK_BINDING};
+
+#[derive(Debug, Clone)]
+pub struct FogOfWarParams {
+    /// World region that is covered by the fog. Everything outside is left
+    /// untouched.
+    pub bounds: Rect,
+
+    /// Resolution of the mask. The mask can be coarse, since its edges are
+    /// blurred anyway.
+    pub texels_per_unit: f32,
+
+    /// Number of blur iterations for softening the edges. Zero disables
+    /// blurring.
+    pub blur_iters: usize,
+
+    pub unexplored_color: Color4,
+    pub explored_color: Color4,
+}
+
+program! {
+    program FogOfWarProgram
+    params {
+        params: FogOfWarParams,
+    }
+    uniforms {
+        matrices: ViewMatrices = MATRICES_BLOCK_BINDING,
+    }
+    samplers {
+        mask: Sampler2,
+    }
+    attributes {
+        a: SpriteVertex,
+    }
+    defines [
+        unexplored_color => glsl_vec4(params.unexplored_color),
+        explored_color => glsl_vec4(params.explored_color),
+    ]
+    vertex glsl! {
+        out vec2 v_uv;
+
+        void main() {
+            vec3 position = matrices.projection
+                * matrices.view
+                * vec3(a_position.xy, 1.0);
+
+            gl_Position = vec4(position.xy, a_position.z, 1.0);
+
+            v_uv = a_tex_coords / vec2(textureSize(mask, 0));
+            v_uv.y = 1.0 - v_uv.y;
+        }
+    }
+    fragment glsl! {
+        in vec2 v_uv;
+        out vec4 f_color;
+
+        void main() {
+            // Red: explored, green: currently visible.
+            vec2 state = texture(mask, v_uv).rg;
+
+            vec4 fog = mix({{unexplored_color}}, {{explored_color}}, state.r);
+            f_color = vec4(fog.rgb, fog.a * (1.0 - state.g));
+        }
+    }
+}
+
+/// Hides the parts of the world that are not visible to the player.
+///
+/// The state is kept in a low-resolution mask that covers
+/// `FogOfWarParams::bounds`. Every frame, the currently visible regions are
+/// pushed and `update` is called. Regions that have been visible once stay
+/// explored until `reset` is called.
+///
+/// The mask only lives on the GPU, so the explored regions are forgotten when
+/// the WebGL context is lost, as if `reset` had been called.
+pub struct FogOfWar {
+    params: FogOfWarParams,
+    color_pass: Rc<ColorPass>,
+    blur_pass: Rc<BlurPass>,
+    program: FogOfWarProgram,
+
+    mask: Framebuffer,
+    blurred: Framebuffer,
+    blur_buffer: BlurBuffer,
+    mask_matrices: Uniform<ViewMatrices>,
+    bounds_color: Mesh<ColorVertex>,
+    bounds_sprite: Mesh<SpriteVertex>,
+    regions: ColorTriangleBatch,
+    generation: u64,
+}
+
+/// Fan of triangles around a point.
+struct VisibilityPolygon<'a> {
+    eye: Point2<f32>,
+    polygon: &'a [Point2<f32>],
+}
+
+impl FogOfWarParams {
+    pub fn new(bounds: Rect) -> Self {
+        Self {
+            bounds,
+            texels_per_unit: 1.0 / 8.0,
+            blur_iters: 2,
+            unexplored_color: Color4::new(0.0, 0.0, 0.0, 1.0),
+            explored_color: Color4::new(0.0, 0.0, 0.0, 0.6),
+        }
+    }
+}
+
+impl FogOfWar {
+    pub fn new(
+        gl: Rc<gl::Context>,
+        color_pass: Rc<ColorPass>,
+        blur_pass: Rc<BlurPass>,
+        params: FogOfWarParams,
+    ) -> Result<Self, NewFramebufferError> {
+        assert!(params.texels_per_unit > 0.0);
+
+        let bounds = params.bounds;
+        let mask_size = Vector2::new(
+            ((bounds.size.x * params.texels_per_unit).ceil() as u32).max(1),
+            ((bounds.size.y * params.texels_per_unit).ceil() as u32).max(1),
+        );
+
+        let program = FogOfWarProgram::new(gl.clone(), params.clone())?;
+        let mask = Framebuffer::from_textures(vec![Texture::new(
+            gl.clone(),
+            mask_size,
+            TextureParams::linear_rgbau8(),
+        )?])?;
+        let blurred = Framebuffer::from_textures(vec![Texture::new(
+            gl.clone(),
+            mask_size,
+            TextureParams::linear_rgbau8(),
+        )?])?;
+        let blur_buffer = BlurBuffer::new(gl.clone())?;
+
+        // Map the bounds onto the whole mask.
+        let mask_matrices = Uniform::new(
+            gl.clone(),
+            ViewMatrices {
+                projection: Screen::project_to_ndc(bounds.size),
+                view: Matrix3::new_translation(&-bounds.top_left().coords),
+            },
+        )?;
+
+        let bounds_color = Mesh::from_geometry::<TriangleTag, _>(
+            gl.clone(),
+            ColorRect {
+                rect: bounds,
+                z: 0.0,
+                color: Color4::new(0.0, 0.0, 0.0, 0.0),
+            },
+        )?;
+        let bounds_sprite = Mesh::from_geometry(
+            gl.clone(),
+            Sprite {
+                rect: bounds,
+                depth: 0.0,
+                tex_rect: Rect::from_top_left(Point2::origin(), nalgebra::convert(mask_size)),
+                color: Color4::new(1.0, 1.0, 1.0, 1.0),
+            },
+        )?;
+        let regions = ColorTriangleBatch::new(gl.clone())?;
+
+        gl::with_framebuffer(&mask, || {
+            gl::clear_color(&gl, Color4::new(0.0, 0.0, 0.0, 0.0));
+        });
+
+        Ok(Self {
+            params,
+            color_pass,
+            blur_pass,
+            program,
+            mask,
+            blurred,
+            blur_buffer,
+            mask_matrices,
+            bounds_color,
+            bounds_sprite,
+            regions,
+            generation: gl.generation(),
+        })
+    }
+
+    pub fn params(&self) -> &FogOfWarParams {
+        &self.params
+    }
+
+    /// Returns the unblurred mask. The red channel stores whether a texel has
+    /// been explored, and the green channel whether it is currently visible.
+    pub fn mask(&self) -> &Texture {
+        &self.mask.textures()[0]
+    }
+
+    /// Forgets which regions have been explored.
+    pub fn reset(&mut self) {
+        gl::with_framebuffer(&self.mask, || {
+            gl::clear_color(&self.mask.gl(), Color4::new(0.0, 0.0, 0.0, 0.0));
+        });
+    }
+
+    pub fn push_circle(&mut self, circle: Circle) {
+        self.regions.push(ColorCircle {
+            circle,
+            depth: 0.0,
+            angle: 0.0,
+            num_segments: 32,
+            color: Color4::new(1.0, 1.0, 0.0, 1.0),
+        });
+    }
+
+    /// Adds the visibility polygon of a viewer at `eye`. The polygon needs to
+    /// be star-shaped around `eye`, which visibility polygons always are.
+    pub fn push_visibility_polygon(&mut self, eye: Point2<f32>, polygon: &[Point2<f32>]) {
+        self.regions.push(VisibilityPolygon { eye, polygon });
+    }
+
+    /// Replaces the currently visible regions with the ones that have been
+    /// pushed since the last call, and marks them as explored.
+    pub fn update(&mut self) -> Result<(), FrameError> {
+        let generation = self.mask.gl().generation();
+        if self.generation != generation {
+            // The mask has been recreated after context loss.
+            self.reset();
+            self.generation = generation;
+        }
+
+        gl::with_framebuffer(&self.mask, || {
+            // Only clear visibility, keeping the explored channel.
+            self.color_pass.draw(
+                &self.mask_matrices,
+                self.bounds_color.draw_unit(),
+                &DrawParams {
+                    color_mask: (false, true, false, false),
+                    ..DrawParams::default()
+                },
+            );
+            self.color_pass.draw(
+                &self.mask_matrices,
+                self.regions.draw_unit(),
+                &DrawParams::default(),
+            );
+        });
+
+        self.regions.clear();
+
+        if self.params.blur_iters > 0 {
+            self.blur_pass.blur(
+                self.params.blur_iters,
+                &self.mask.textures()[0],
+                0,
+                &mut self.blur_buffer,
+                &self.blurred,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the fog with `matrices` of the camera that is used for the
+    /// scene. `draw_params` should enable blending.
+    pub fn draw(&self, matrices: &Uniform<ViewMatrices>, draw_params: &DrawParams) {
+        let mask = if self.params.blur_iters > 0 {
+            &self.blurred.textures()[0]
+        } else {
+            &self.mask.textures()[0]
+        };
+
+        gl::draw(
+            &self.program,
+            matrices,
+            [mask],
+            self.bounds_sprite.draw_unit(),
+            draw_params,
+        );
+    }
+}
+
+impl<'a> Geometry<TriangleTag> for VisibilityPolygon<'a> {
+    type Vertex = ColorVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let start_index = vertices.len() as u32;
+        let color = Color4::new(1.0, 1.0, 0.0, 1.0);
+
+        for p in std::iter::once(&self.eye).chain(self.polygon) {
+            vertices.push(ColorVertex {
+                position: Point3::new(p.x, p.y, 0.0),
+                color,
+            });
+        }
+
+        let n = self.polygon.len() as u32;
+        for i in 0..n {
+            elements.extend_from_slice(&[
+                start_index,
+                start_index + 1 + i,
+                start_index + 1 + (i + 1) % n,
+            ]);
+        }
+    }
+}
+
+fn glsl_vec4(color: Color4) -> String {
+    format!(
+        "vec4({:.8}, {:.8}, {:.8}, {:.8})",
+        color.r, color.g, color.b, color.a
+    )
+}
@@ -1,9 +1,13 @@
+use std::rc::Rc;
+
 use bytemuck::{Pod, Zeroable};
-use nalgebra::{Point2, Vector2};
+use nalgebra::{Point2, Vector2, Vector4};
 
 use crate::{
     attributes,
-    gl::{Attribute, Vertex},
+    data::{Mesh, Sprite, SpriteVertex, TriangleTag},
+    geom::Rect,
+    gl::{self, Attribute, Vertex},
     Color4,
 };
 
@@ -34,3 +38,71 @@ impl Default for ColorInstance {
         }
     }
 }
+
+/// Per-instance parameters for drawing a sprite with `InstancedSpritePass`.
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+pub struct SpriteInstance {
+    pub position: Point2<f32>,
+    pub z: f32,
+    pub angle: f32,
+
+    /// Size of the sprite in world units.
+    pub scale: Vector2<f32>,
+
+    /// Texture rectangle in texels, given as top-left corner and size, with
+    /// the same conventions as `Sprite::tex_rect`.
+    pub tex_rect: Vector4<f32>,
+
+    pub color: Color4,
+
+    /// Point of the sprite that is placed at `position` and that the sprite
+    /// rotates around, where `(0, 0)` is the top-left and `(1, 1)` the
+    /// bottom-right corner.
+    pub pivot: Vector2<f32>,
+}
+
+impl SpriteInstance {
+    /// Returns the quad mesh that `SpriteInstance`s are meant to be drawn with,
+    /// e.g. by creating an `InstanceBatch` from it.
+    pub fn quad_mesh(gl: Rc<gl::Context>) -> Result<Mesh<SpriteVertex>, gl::Error> {
+        Mesh::from_geometry::<TriangleTag, _>(
+            gl,
+            Sprite {
+                rect: Rect::zero_to_one(),
+                depth: 0.0,
+                tex_rect: Rect::zero_to_one(),
+                color: Color4::new(1.0, 1.0, 1.0, 1.0),
+            },
+        )
+    }
+
+    pub fn with_tex_rect(self, tex_rect: Rect) -> Self {
+        let top_left = tex_rect.top_left();
+
+        Self {
+            tex_rect: Vector4::new(top_left.x, top_left.y, tex_rect.size.x, tex_rect.size.y),
+            ..self
+        }
+    }
+}
+
+impl Vertex for SpriteInstance {
+    fn attributes() -> Vec<Attribute> {
+        attributes![position, z, angle, scale, tex_rect, color, pivot]
+    }
+}
+
+impl Default for SpriteInstance {
+    fn default() -> Self {
+        Self {
+            position: Point2::origin(),
+            z: 0.0,
+            angle: 0.0,
+            scale: Vector2::new(1.0, 1.0),
+            tex_rect: Vector4::new(0.0, 0.0, 1.0, 1.0),
+            color: Color4::new(1.0, 1.0, 1.0, 1.0),
+            pivot: Vector2::new(0.5, 0.5),
+        }
+    }
+}
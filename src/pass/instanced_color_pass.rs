@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use crate::{
     data::ColorVertex,
-    gl::{self, DrawParams, Element, InstancedDrawUnit, Uniform},
+    gl::{self, DrawParams, InstancedDrawUnit, Uniform},
     program,
 };
 
@@ -57,14 +57,12 @@ impl InstancedColorPass {
         Ok(Self { program })
     }
 
-    pub fn draw<E>(
+    pub fn draw(
         &self,
         matrices: &Uniform<ViewMatrices>,
-        draw_unit: InstancedDrawUnit<(ColorVertex, ColorInstance), E>,
+        draw_unit: InstancedDrawUnit<(ColorVertex, ColorInstance)>,
         params: &DrawParams,
-    ) where
-        E: Element,
-    {
+    ) {
         gl::draw_instanced(&self.program, matrices, [], draw_unit, params);
     }
 }
@@ -0,0 +1,77 @@
+use std::rc::Rc;
+
+use crate::{
+    data::SpriteVertex,
+    gl::{self, DrawParams, InstancedDrawUnit, Texture, Uniform},
+    program,
+};
+
+use super::{SpriteInstance, ViewMatrices, MATRICES_BLOCK_BINDING};
+
+program! {
+    program InstancedSpriteProgram
+    uniforms {
+        matrices: ViewMatrices = MATRICES_BLOCK_BINDING,
+    }
+    samplers {
+        sprite: Sampler2,
+    }
+    attributes {
+        a: SpriteVertex,
+        i: SpriteInstance,
+    }
+    vertex glsl! {
+        out vec2 v_uv;
+        out vec4 v_color;
+
+        void main() {
+            mat2 i_rot = mat2(
+                cos(i_angle), -sin(i_angle),
+                sin(i_angle), cos(i_angle)
+            );
+            vec2 world_pos = i_rot * (i_scale * (a_position.xy - i_pivot)) + i_position;
+
+            vec3 position = matrices.projection
+                * matrices.view
+                * vec3(world_pos, 1.0);
+
+            gl_Position = vec4(position.xy, a_position.z + i_z, 1.0);
+
+            v_uv = (i_tex_rect.xy + a_tex_coords * i_tex_rect.zw)
+                / vec2(textureSize(sprite, 0));
+            v_uv.y = 1.0 - v_uv.y;
+            v_color = a_color * i_color;
+        }
+    }
+    fragment glsl! {
+        in vec2 v_uv;
+        in vec4 v_color;
+        out vec4 f_color;
+
+        void main() {
+            f_color = texture(sprite, v_uv) * v_color;
+        }
+    }
+}
+
+pub struct InstancedSpritePass {
+    program: InstancedSpriteProgram,
+}
+
+impl InstancedSpritePass {
+    pub fn new(gl: Rc<gl::Context>) -> Result<Self, gl::Error> {
+        let program = InstancedSpriteProgram::new(gl)?;
+
+        Ok(Self { program })
+    }
+
+    pub fn draw(
+        &self,
+        matrices: &Uniform<ViewMatrices>,
+        texture: &Texture,
+        draw_unit: InstancedDrawUnit<(SpriteVertex, SpriteInstance)>,
+        params: &DrawParams,
+    ) {
+        gl::draw_instanced(&self.program, matrices, [texture], draw_unit, params);
+    }
+}
@@ -1,10 +1,15 @@
 mod bindings;
 mod blur_pass;
 mod color_pass;
+mod decal_layer;
+mod fog_of_war;
 mod gaussian_mipmap_stack;
 mod instance;
 mod instanced_color_pass;
+mod instanced_sprite_pass;
+mod render_layers;
 mod sprite_pass;
+mod tiling_background_pass;
 mod view_matrices;
 
 pub use bindings::{
@@ -13,8 +18,13 @@ pub use bindings::{
 };
 pub use blur_pass::{BlurBuffer, BlurParams, BlurPass};
 pub use color_pass::ColorPass;
+pub use decal_layer::{DecalChunk, DecalLayer, DecalLayerParams};
+pub use fog_of_war::{FogOfWar, FogOfWarParams};
 pub use gaussian_mipmap_stack::GaussianMipmapStack;
-pub use instance::ColorInstance;
+pub use instance::{ColorInstance, SpriteInstance};
 pub use instanced_color_pass::InstancedColorPass;
+pub use instanced_sprite_pass::InstancedSpritePass;
+pub use render_layers::{RenderLayer, RenderLayerDef, RenderLayers};
 pub use sprite_pass::SpritePass;
+pub use tiling_background_pass::TilingBackgroundPass;
 pub use view_matrices::ViewMatrices;
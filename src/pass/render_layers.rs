@@ -0,0 +1,178 @@
+use std::rc::Rc;
+
+use nalgebra::{Matrix3, Point2, Vector2};
+
+use crate::{
+    geom::{Camera, Rect, Screen},
+    gl::{self, Uniform},
+};
+
+use super::ViewMatrices;
+
+/// Defines how a render layer derives its camera from the main camera.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderLayerDef {
+    pub name: String,
+
+    /// Scrolling speed relative to the main camera, see `Camera::parallax`.
+    pub parallax: Vector2<f32>,
+
+    /// If set, the layer uses this zoom instead of the main camera's zoom.
+    pub zoom_lock: Option<f32>,
+
+    /// If set, the layer ignores the main camera and is drawn in logical
+    /// screen coordinates, e.g. for a HUD.
+    pub screen_space: bool,
+}
+
+pub struct RenderLayer {
+    def: RenderLayerDef,
+    camera: Camera,
+    matrices: Uniform<ViewMatrices>,
+}
+
+/// An ordered list of render layers, each with its own `ViewMatrices`.
+pub struct RenderLayers {
+    layers: Vec<RenderLayer>,
+}
+
+impl RenderLayerDef {
+    pub fn world(name: &str, parallax: f32) -> Self {
+        Self {
+            name: name.into(),
+            parallax: Vector2::new(parallax, parallax),
+            zoom_lock: None,
+            screen_space: false,
+        }
+    }
+
+    pub fn screen(name: &str) -> Self {
+        Self {
+            screen_space: true,
+            ..Self::world(name, 0.0)
+        }
+    }
+
+    pub fn camera(&self, main_camera: &Camera, screen: Screen) -> Camera {
+        if self.screen_space {
+            Camera {
+                center: Point2::from(screen.logical_size / 2.0),
+                zoom: 1.0,
+                angle: 0.0,
+            }
+        } else {
+            Camera {
+                zoom: self.zoom_lock.unwrap_or(main_camera.zoom),
+                ..main_camera.parallax(self.parallax)
+            }
+        }
+    }
+}
+
+impl RenderLayer {
+    pub fn def(&self) -> &RenderLayerDef {
+        &self.def
+    }
+
+    pub fn name(&self) -> &str {
+        &self.def.name
+    }
+
+    /// Returns the camera as of the last call to `RenderLayers::update`.
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn matrices(&self) -> &Uniform<ViewMatrices> {
+        &self.matrices
+    }
+
+    pub fn visible_rect(&self, screen: Screen) -> Rect {
+        self.camera.visible_world_rect(screen)
+    }
+}
+
+impl RenderLayers {
+    /// Creates layers that are drawn in the order of `defs`.
+    pub fn new(gl: Rc<gl::Context>, defs: Vec<RenderLayerDef>) -> Result<Self, gl::Error> {
+        let layers = defs
+            .into_iter()
+            .map(|def| {
+                Ok(RenderLayer {
+                    def,
+                    camera: Camera {
+                        center: Point2::origin(),
+                        zoom: 1.0,
+                        angle: 0.0,
+                    },
+                    matrices: Uniform::new(gl.clone(), ViewMatrices::default())?,
+                })
+            })
+            .collect::<Result<Vec<_>, gl::Error>>()?;
+
+        Ok(Self { layers })
+    }
+
+    /// Creates the layers `background`, `world`, `foreground` and `hud`.
+    ///
+    /// The background scrolls at half speed, and the foreground slightly
+    /// faster than the world.
+    pub fn with_default_layers(gl: Rc<gl::Context>) -> Result<Self, gl::Error> {
+        Self::new(
+            gl,
+            vec![
+                RenderLayerDef::world("background", 0.5),
+                RenderLayerDef::world("world", 1.0),
+                RenderLayerDef::world("foreground", 1.2),
+                RenderLayerDef::screen("hud"),
+            ],
+        )
+    }
+
+    /// Updates the cameras and uniforms of all layers. Needs to be called
+    /// once per frame before drawing.
+    pub fn update(&mut self, main_camera: &Camera, screen: Screen) {
+        let projection = screen.project_logical_to_ndc();
+
+        for layer in self.layers.iter_mut() {
+            layer.camera = layer.def.camera(main_camera, screen);
+            layer.matrices.set(ViewMatrices {
+                projection,
+                view: if layer.def.screen_space {
+                    Matrix3::identity()
+                } else {
+                    layer.camera.matrix(screen)
+                },
+            });
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Iterates over the layers in drawing order.
+    pub fn iter(&self) -> impl Iterator<Item = &RenderLayer> {
+        self.layers.iter()
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.name() == name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RenderLayer> {
+        self.index(name).map(|index| &self.layers[index])
+    }
+
+    /// Returns the matrices of a layer. Panics if there is no layer called
+    /// `name`.
+    pub fn matrices(&self, name: &str) -> &Uniform<ViewMatrices> {
+        self.get(name)
+            .unwrap_or_else(|| panic!("no render layer called `{}`", name))
+            .matrices()
+    }
+}
@@ -1,8 +1,8 @@
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 use crate::{
-    data::SpriteVertex,
-    gl::{self, DrawParams, DrawUnit, Element, Texture, Uniform},
+    data::{PackedSpriteVertex, SpriteVertex},
+    gl::{self, DrawParams, DrawUnit, Texture, Uniform},
     program,
 };
 
@@ -46,26 +46,92 @@ program! {
     }
 }
 
+program! {
+    program PackedSpriteProgram
+    uniforms {
+        matrices: ViewMatrices = MATRICES_BLOCK_BINDING,
+    }
+    samplers {
+        sprite: Sampler2,
+    }
+    attributes {
+        a: PackedSpriteVertex,
+    }
+    vertex glsl! {
+        out vec2 v_uv;
+        out vec4 v_color;
+
+        void main() {
+            vec3 position = matrices.projection
+                * matrices.view
+                * vec3(a_position.xy, 1.0);
+
+            gl_Position = vec4(position.xy, a_position.z, 1.0);
+
+            // Already normalized to the texture size.
+            v_uv = a_tex_coords;
+            v_uv.y = 1.0 - v_uv.y;
+            v_color = a_color;
+        }
+    }
+    fragment glsl! {
+        in vec2 v_uv;
+        in vec4 v_color;
+        out vec4 f_color;
+
+        void main() {
+            f_color = texture(sprite, v_uv) * v_color;
+        }
+    }
+}
+
 pub struct SpritePass {
     program: SpriteProgram,
+
+    // Compiled on first use, since most users never draw packed vertices.
+    packed_program: RefCell<Option<PackedSpriteProgram>>,
 }
 
 impl SpritePass {
     pub fn new(gl: Rc<gl::Context>) -> Result<Self, gl::Error> {
         let program = SpriteProgram::new(gl)?;
 
-        Ok(Self { program })
+        Ok(Self {
+            program,
+            packed_program: RefCell::new(None),
+        })
     }
 
-    pub fn draw<E>(
+    pub fn draw(
         &self,
         matrices: &Uniform<ViewMatrices>,
         texture: &Texture,
-        draw_unit: DrawUnit<SpriteVertex, E>,
+        draw_unit: DrawUnit<SpriteVertex>,
         params: &DrawParams,
-    ) where
-        E: Element,
-    {
+    ) {
         gl::draw(&self.program, matrices, [texture], draw_unit, params);
     }
+
+    pub fn draw_packed(
+        &self,
+        matrices: &Uniform<ViewMatrices>,
+        texture: &Texture,
+        draw_unit: DrawUnit<PackedSpriteVertex>,
+        params: &DrawParams,
+    ) -> Result<(), gl::Error> {
+        let mut packed_program = self.packed_program.borrow_mut();
+        if packed_program.is_none() {
+            *packed_program = Some(PackedSpriteProgram::new(self.program.gl())?);
+        }
+
+        gl::draw(
+            packed_program.as_ref().unwrap(),
+            matrices,
+            [texture],
+            draw_unit,
+            params,
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,44 @@
+use std::rc::Rc;
+
+use crate::{
+    data::{SpriteBatch, TilingBackground},
+    gl::{self, DrawParams, Texture, TextureWrap, Uniform},
+};
+
+use super::{SpritePass, ViewMatrices};
+
+/// Draws `TilingBackground`s, reusing a single batch across frames.
+pub struct TilingBackgroundPass {
+    sprite_pass: Rc<SpritePass>,
+    batch: SpriteBatch,
+}
+
+impl TilingBackgroundPass {
+    pub fn new(gl: Rc<gl::Context>, sprite_pass: Rc<SpritePass>) -> Result<Self, gl::Error> {
+        let batch = SpriteBatch::new(gl)?;
+
+        Ok(Self { sprite_pass, batch })
+    }
+
+    /// Draws a background with `matrices` that need to use the same camera as
+    /// `background`.
+    pub fn draw(
+        &mut self,
+        matrices: &Uniform<ViewMatrices>,
+        texture: &Texture,
+        background: TilingBackground,
+        params: &DrawParams,
+    ) {
+        debug_assert!(
+            texture.params().wrap_horizontal != TextureWrap::ClampToEdge
+                && texture.params().wrap_vertical != TextureWrap::ClampToEdge,
+            "tiling backgrounds need a repeating texture",
+        );
+
+        self.batch.clear();
+        self.batch.push(background);
+
+        self.sprite_pass
+            .draw(matrices, texture, self.batch.draw_unit(), params);
+    }
+}
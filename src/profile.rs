@@ -6,7 +6,7 @@ use nalgebra::{Matrix3, Point2, Vector2};
 use crate::{
     data::ColorRect,
     geom::{Rect, Screen},
-    gl::{DrawTimer, Uniform},
+    gl::{self, DrawTimer, Uniform},
     pass::ViewMatrices,
     plot::{Axis, LineGraph, Plot, PlotBatch, PlotPass, PlotStyle},
     text::{Font, Text},
@@ -40,6 +40,7 @@ impl Default for ProfileParams {
 }
 
 pub struct Profile {
+    gl: Rc<gl::Context>,
     font: Font,
     params: ProfileParams,
 
@@ -53,6 +54,7 @@ pub struct Profile {
 }
 
 pub struct FrameGuard {
+    gl: Rc<gl::Context>,
     start_time: Instant,
     _profile_guard: coarse_prof::Guard,
     frame_times: FrameTimes,
@@ -73,6 +75,7 @@ impl Profile {
         )));
 
         Ok(Self {
+            gl: context.gl(),
             font,
             params,
             screen_matrices,
@@ -108,6 +111,7 @@ impl Profile {
         self.draw_times.borrow_mut().start_draw();
 
         FrameGuard {
+            gl: self.gl.clone(),
             start_time: Instant::now(),
             _profile_guard: coarse_prof::enter("frame"),
             frame_times: self.frame_times.clone(),
@@ -131,7 +135,11 @@ impl Profile {
 
         self.batch.clear();
 
-        let prof_string = coarse_prof::to_string();
+        let prof_string = format!(
+            "{}\n{}",
+            coarse_prof::to_string().trim_end(),
+            self.gl.last_frame_stats(),
+        );
         let prof_size =
             self.font.text_size(self.params.text_size, &prof_string) + 2.0 * self.params.padding;
         let prof_pos = Point2::from(screen.logical_size) - prof_size - self.params.margin;
@@ -227,5 +235,7 @@ impl Drop for FrameGuard {
 
         let mut draw_times = self.draw_times.borrow_mut();
         draw_times.end_draw();
+
+        self.gl.end_frame_stats();
     }
 }
@@ -0,0 +1,156 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use nalgebra::Vector2;
+use thiserror::Error;
+
+use super::{ImageDiff, RgbaImage};
+
+pub const UPDATE_GOLDENS_VAR: &str = "MALEN_UPDATE_GOLDENS";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoldenTolerance {
+    /// Channels may differ by this much without the pixel counting as
+    /// different.
+    pub max_channel_diff: u8,
+
+    /// Number of pixels that may be different.
+    pub max_differing_pixels: usize,
+}
+
+#[derive(Error, Debug)]
+pub enum GoldenError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("PNG decoding error: {0}")]
+    Decoding(#[from] png::DecodingError),
+
+    #[error("PNG encoding error: {0}")]
+    Encoding(#[from] png::EncodingError),
+
+    #[error("unsupported PNG format: {0:?}, {1:?}")]
+    UnsupportedFormat(png::ColorType, png::BitDepth),
+
+    #[error("golden image {0} does not exist, run with {UPDATE_GOLDENS_VAR}=1 to create it")]
+    Missing(PathBuf),
+
+    #[error("image size {actual:?} differs from golden size {golden:?}")]
+    SizeMismatch {
+        actual: Vector2<u32>,
+        golden: Vector2<u32>,
+    },
+
+    #[error("{} pixels differ from golden image {path} (max channel diff: {}), see {actual_path}", .diff.num_differing_pixels, .diff.max_channel_diff)]
+    Mismatch {
+        path: PathBuf,
+        actual_path: PathBuf,
+        diff: ImageDiff,
+    },
+}
+
+impl Default for GoldenTolerance {
+    fn default() -> Self {
+        Self {
+            max_channel_diff: 2,
+            max_differing_pixels: 0,
+        }
+    }
+}
+
+pub fn load_png(path: impl AsRef<Path>) -> Result<RgbaImage, GoldenError> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::EXPAND);
+
+    let mut reader = decoder.read_info()?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data)?;
+    data.truncate(info.buffer_size());
+
+    let data = match (info.color_type, info.bit_depth) {
+        (png::ColorType::Rgba, png::BitDepth::Eight) => data,
+        (png::ColorType::Rgb, png::BitDepth::Eight) => data
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        (color_type, bit_depth) => {
+            return Err(GoldenError::UnsupportedFormat(color_type, bit_depth));
+        }
+    };
+
+    Ok(RgbaImage::from_data(
+        Vector2::new(info.width, info.height),
+        data,
+    ))
+}
+
+pub fn save_png(image: &RgbaImage, path: impl AsRef<Path>) -> Result<(), GoldenError> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, image.size().x, image.size().y);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(image.data())?;
+
+    Ok(())
+}
+
+/// Compares `image` with the PNG stored at `path`.
+///
+/// If the environment variable `MALEN_UPDATE_GOLDENS` is set to `1`, the
+/// stored image is overwritten with `image` instead. On mismatch, `image` is
+/// written next to the golden image with the extension `.actual.png`, so that
+/// the two can be compared.
+pub fn check_golden(
+    image: &RgbaImage,
+    path: impl AsRef<Path>,
+    tolerance: GoldenTolerance,
+) -> Result<(), GoldenError> {
+    let path = path.as_ref();
+
+    if matches!(std::env::var(UPDATE_GOLDENS_VAR).as_deref(), Ok("1")) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        return save_png(image, path);
+    }
+
+    if !path.exists() {
+        return Err(GoldenError::Missing(path.to_owned()));
+    }
+
+    let golden = load_png(path)?;
+    if golden.size() != image.size() {
+        return Err(GoldenError::SizeMismatch {
+            actual: image.size(),
+            golden: golden.size(),
+        });
+    }
+
+    let diff = image.diff(&golden, tolerance.max_channel_diff);
+    if diff.num_differing_pixels > tolerance.max_differing_pixels {
+        let actual_path = path.with_extension("actual.png");
+        save_png(image, &actual_path)?;
+
+        return Err(GoldenError::Mismatch {
+            path: path.to_owned(),
+            actual_path,
+            diff,
+        });
+    }
+
+    Ok(())
+}
+
+/// Like `check_golden`, but panics on failure. Meant for use in tests.
+#[track_caller]
+pub fn assert_golden(image: &RgbaImage, path: impl AsRef<Path>, tolerance: GoldenTolerance) {
+    if let Err(err) = check_golden(image, path, tolerance) {
+        panic!("{}", err);
+    }
+}
@@ -0,0 +1,135 @@
+use nalgebra::Vector2;
+
+use crate::Color4;
+
+/// An 8-bit RGBA image in CPU memory.
+///
+/// Rows are stored one after another, starting with the row at `y = 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    size: Vector2<u32>,
+    data: Vec<u8>,
+}
+
+/// Result of comparing two images of the same size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImageDiff {
+    /// Largest difference in any channel of any pixel.
+    pub max_channel_diff: u8,
+
+    /// Number of pixels that differ by more than the tolerance that was given
+    /// to `RgbaImage::diff`.
+    pub num_differing_pixels: usize,
+}
+
+impl RgbaImage {
+    pub fn new(size: Vector2<u32>, color: Color4) -> Self {
+        let pixel = color_to_rgba(color);
+        let data = pixel
+            .iter()
+            .copied()
+            .cycle()
+            .take(size.x as usize * size.y as usize * 4)
+            .collect();
+
+        Self { size, data }
+    }
+
+    pub fn from_data(size: Vector2<u32>, data: Vec<u8>) -> Self {
+        assert_eq!(data.len(), size.x as usize * size.y as usize * 4);
+
+        Self { size, data }
+    }
+
+    pub fn size(&self) -> Vector2<u32> {
+        self.size
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = self.index(x, y);
+        [
+            self.data[i],
+            self.data[i + 1],
+            self.data[i + 2],
+            self.data[i + 3],
+        ]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, pixel: [u8; 4]) {
+        let i = self.index(x, y);
+        self.data[i..i + 4].copy_from_slice(&pixel);
+    }
+
+    pub fn get_color(&self, x: u32, y: u32) -> Color4 {
+        let [r, g, b, a] = self.get(x, y);
+        Color4::from_u8(r, g, b, a)
+    }
+
+    /// Returns the image with its rows in reverse order, e.g. for converting
+    /// the result of `Framebuffer::read_pixels` into an image with the top
+    /// row first.
+    pub fn flip_y(&self) -> Self {
+        let row_len = self.size.x as usize * 4;
+        let data = self
+            .data
+            .chunks_exact(row_len.max(1))
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
+        Self {
+            size: self.size,
+            data,
+        }
+    }
+
+    /// Compares two images of the same size. Pixels count as differing if
+    /// any of their channels differs by more than `tolerance`.
+    pub fn diff(&self, other: &RgbaImage, tolerance: u8) -> ImageDiff {
+        assert_eq!(self.size, other.size, "images must have the same size");
+
+        let mut diff = ImageDiff::default();
+
+        for (a, b) in self.data.chunks_exact(4).zip(other.data.chunks_exact(4)) {
+            let pixel_diff = a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| (*a as i32 - *b as i32).unsigned_abs() as u8)
+                .max()
+                .unwrap();
+
+            diff.max_channel_diff = diff.max_channel_diff.max(pixel_diff);
+            if pixel_diff > tolerance {
+                diff.num_differing_pixels += 1;
+            }
+        }
+
+        diff
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        assert!(x < self.size.x && y < self.size.y);
+
+        (y as usize * self.size.x as usize + x as usize) * 4
+    }
+}
+
+pub(super) fn color_to_rgba(color: Color4) -> [u8; 4] {
+    let to_u8 = |x: f32| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    [
+        to_u8(color.r),
+        to_u8(color.g),
+        to_u8(color.b),
+        to_u8(color.a),
+    ]
+}
@@ -0,0 +1,7 @@
+pub mod golden;
+
+mod image;
+mod rasterizer;
+
+pub use image::{ImageDiff, RgbaImage};
+pub use rasterizer::Rasterizer;
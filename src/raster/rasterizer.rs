@@ -0,0 +1,366 @@
+use nalgebra::{Matrix3, Point2, Point3, Vector2, Vector3, Vector4};
+
+use crate::{
+    data::{ColorVertex, GeometryBuffer, PrimitiveTag, SpriteVertex},
+    gl::{Blend, BlendFactor, BlendOp, PrimitiveMode},
+    pass::ViewMatrices,
+    Color4,
+};
+
+use super::{image::color_to_rgba, RgbaImage};
+
+/// A software renderer that mimics `ColorPass` and `SpritePass`.
+///
+/// It is meant for testing geometry generation without a GPU, not for speed.
+/// Pixels are sampled at their center, and triangles that share an edge do
+/// not overlap. There is no depth test, so later geometry always covers
+/// earlier geometry. Lines are one pixel wide.
+pub struct Rasterizer {
+    size: Vector2<u32>,
+    matrix: Matrix3<f32>,
+    blend: Option<Blend>,
+    pixels: Vec<Color4>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Fragment {
+    color: Color4,
+    tex_coords: Point2<f32>,
+}
+
+impl Rasterizer {
+    pub fn new(size: Vector2<u32>, clear_color: Color4) -> Self {
+        Self {
+            size,
+            matrix: Matrix3::identity(),
+            blend: None,
+            pixels: vec![clear_color; size.x as usize * size.y as usize],
+        }
+    }
+
+    pub fn size(&self) -> Vector2<u32> {
+        self.size
+    }
+
+    pub fn clear(&mut self, color: Color4) {
+        self.pixels.iter_mut().for_each(|pixel| *pixel = color);
+    }
+
+    pub fn set_matrices(&mut self, matrices: &ViewMatrices) {
+        self.matrix = matrices.projection * matrices.view;
+    }
+
+    /// Sets blending as in `DrawParams::blend`.
+    pub fn set_blend(&mut self, blend: Option<Blend>) {
+        self.blend = blend;
+    }
+
+    pub fn draw_colors<P: PrimitiveTag>(&mut self, buffer: &GeometryBuffer<P, ColorVertex>) {
+        self.draw(
+            P::primitive_mode(),
+            buffer.elements(),
+            buffer.vertices(),
+            |v| {
+                (
+                    v.position,
+                    Fragment {
+                        color: v.color,
+                        tex_coords: Point2::origin(),
+                    },
+                )
+            },
+            |fragment| fragment.color,
+        );
+    }
+
+    /// Draws sprites with `texture`, which needs to be given in the layout
+    /// that would be uploaded to `gl::Texture`. Texels are sampled with the
+    /// nearest filter.
+    pub fn draw_sprites<P: PrimitiveTag>(
+        &mut self,
+        texture: &RgbaImage,
+        buffer: &GeometryBuffer<P, SpriteVertex>,
+    ) {
+        self.draw(
+            P::primitive_mode(),
+            buffer.elements(),
+            buffer.vertices(),
+            |v| {
+                (
+                    v.position,
+                    Fragment {
+                        color: v.color,
+                        tex_coords: v.tex_coords,
+                    },
+                )
+            },
+            |fragment| {
+                let texel = sample_nearest(texture, fragment.tex_coords);
+                Color4::new(
+                    texel.r * fragment.color.r,
+                    texel.g * fragment.color.g,
+                    texel.b * fragment.color.b,
+                    texel.a * fragment.color.a,
+                )
+            },
+        );
+    }
+
+    /// Returns the rendered image, with the top row first.
+    pub fn image(&self) -> RgbaImage {
+        let data = self
+            .pixels
+            .iter()
+            .flat_map(|color| color_to_rgba(*color))
+            .collect();
+
+        RgbaImage::from_data(self.size, data)
+    }
+
+    fn draw<V>(
+        &mut self,
+        mode: PrimitiveMode,
+        elements: &[u32],
+        vertices: &[V],
+        vertex: impl Fn(&V) -> (Point3<f32>, Fragment),
+        shade: impl Fn(&Fragment) -> Color4,
+    ) {
+        let (matrix, size) = (self.matrix, self.size);
+        let transform = |v: &V| {
+            let (position, fragment) = vertex(v);
+            (to_pixel(&matrix, size, position), fragment)
+        };
+
+        match mode {
+            PrimitiveMode::Triangle => {
+                for triangle in elements.chunks_exact(3) {
+                    let a = transform(&vertices[triangle[0] as usize]);
+                    let b = transform(&vertices[triangle[1] as usize]);
+                    let c = transform(&vertices[triangle[2] as usize]);
+                    self.triangle([a, b, c], &shade);
+                }
+            }
+            PrimitiveMode::Line => {
+                for line in elements.chunks_exact(2) {
+                    let a = transform(&vertices[line[0] as usize]);
+                    let b = transform(&vertices[line[1] as usize]);
+                    self.line([a, b], &shade);
+                }
+            }
+        }
+    }
+
+    fn triangle(
+        &mut self,
+        mut vertices: [(Point2<f32>, Fragment); 3],
+        shade: &impl Fn(&Fragment) -> Color4,
+    ) {
+        let mut area = edge(vertices[0].0, vertices[1].0, vertices[2].0);
+        if area == 0.0 {
+            return;
+        }
+        if area < 0.0 {
+            vertices.swap(1, 2);
+            area = -area;
+        }
+
+        let [(a, fa), (b, fb), (c, fc)] = vertices;
+
+        let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as u32;
+        let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as u32;
+        let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.0) as u32).min(self.size.x);
+        let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.0) as u32).min(self.size.y);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let p = Point2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let w = [edge(b, c, p), edge(c, a, p), edge(a, b, p)];
+                let edges = [(b, c), (c, a), (a, b)];
+
+                let inside = w
+                    .iter()
+                    .zip(edges.iter())
+                    .all(|(w, (from, to))| *w > 0.0 || (*w == 0.0 && is_top_left(*from, *to)));
+                if !inside {
+                    continue;
+                }
+
+                let fragment = interpolate3([fa, fb, fc], [w[0] / area, w[1] / area, w[2] / area]);
+                self.write(x, y, shade(&fragment));
+            }
+        }
+    }
+
+    fn line(
+        &mut self,
+        vertices: [(Point2<f32>, Fragment); 2],
+        shade: &impl Fn(&Fragment) -> Color4,
+    ) {
+        let [(a, fa), (b, fb)] = vertices;
+        let delta = b - a;
+        let steps = delta.x.abs().max(delta.y.abs()).round() as u32;
+
+        // As in OpenGL, the last pixel of a line is not drawn, so that
+        // connected lines do not overlap.
+        for i in 0..steps {
+            let t = (i as f32 + 0.5) / steps as f32;
+            let p = a + delta * t;
+
+            if p.x < 0.0 || p.y < 0.0 {
+                continue;
+            }
+
+            let (x, y) = (p.x as u32, p.y as u32);
+            if x < self.size.x && y < self.size.y {
+                let fragment = interpolate3([fa, fb, fb], [1.0 - t, t, 0.0]);
+                self.write(x, y, shade(&fragment));
+            }
+        }
+    }
+
+    fn write(&mut self, x: u32, y: u32, src: Color4) {
+        let index = y as usize * self.size.x as usize + x as usize;
+        let dst = self.pixels[index];
+
+        self.pixels[index] = match self.blend {
+            Some(blend) => blend_colors(&blend, src, dst),
+            None => src,
+        };
+    }
+}
+
+fn to_pixel(matrix: &Matrix3<f32>, size: Vector2<u32>, position: Point3<f32>) -> Point2<f32> {
+    let ndc = matrix * Vector3::new(position.x, position.y, 1.0);
+
+    Point2::new(
+        (ndc.x + 1.0) / 2.0 * size.x as f32,
+        (1.0 - ndc.y) / 2.0 * size.y as f32,
+    )
+}
+
+fn edge(a: Point2<f32>, b: Point2<f32>, p: Point2<f32>) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Decides which triangle covers pixels that lie exactly on a shared edge.
+/// The two triangles traverse the edge in opposite directions, so exactly one
+/// of them passes this test.
+fn is_top_left(from: Point2<f32>, to: Point2<f32>) -> bool {
+    let d = to - from;
+
+    d.y < 0.0 || (d.y == 0.0 && d.x > 0.0)
+}
+
+fn interpolate3(fragments: [Fragment; 3], weights: [f32; 3]) -> Fragment {
+    let mut color = Vector4::zeros();
+    let mut tex_coords = Vector2::zeros();
+
+    for (fragment, weight) in fragments.iter().zip(weights.iter()) {
+        let c = fragment.color;
+        color += Vector4::new(c.r, c.g, c.b, c.a) * *weight;
+        tex_coords += fragment.tex_coords.coords * *weight;
+    }
+
+    Fragment {
+        color: Color4::new(color.x, color.y, color.z, color.w),
+        tex_coords: Point2::from(tex_coords),
+    }
+}
+
+fn sample_nearest(texture: &RgbaImage, tex_coords: Point2<f32>) -> Color4 {
+    let size = texture.size();
+    if size.x == 0 || size.y == 0 {
+        return Color4::new(0.0, 0.0, 0.0, 0.0);
+    }
+
+    // Same as the vertex shader of `SpritePass`, which flips the y axis.
+    let u = tex_coords.x / size.x as f32;
+    let v = 1.0 - tex_coords.y / size.y as f32;
+
+    let x = ((u * size.x as f32).floor().max(0.0) as u32).min(size.x - 1);
+    let y = ((v * size.y as f32).floor().max(0.0) as u32).min(size.y - 1);
+
+    texture.get_color(x, y)
+}
+
+fn blend_colors(blend: &Blend, src: Color4, dst: Color4) -> Color4 {
+    let src_color = Vector3::new(src.r, src.g, src.b);
+    let dst_color = Vector3::new(dst.r, dst.g, dst.b);
+
+    let src_factor = blend_factor(blend, blend.func.src_color, src, dst);
+    let dst_factor = blend_factor(blend, blend.func.dst_color, src, dst);
+    let src_alpha_factor = blend_factor(blend, blend.func.src_alpha, src, dst).w;
+    let dst_alpha_factor = blend_factor(blend, blend.func.dst_alpha, src, dst).w;
+
+    let color = blend_op(
+        blend.equation.color,
+        src_color.component_mul(&src_factor.xyz()),
+        dst_color.component_mul(&dst_factor.xyz()),
+        src_color,
+        dst_color,
+    );
+    let alpha = blend_op(
+        blend.equation.alpha,
+        Vector3::repeat(src.a * src_alpha_factor),
+        Vector3::repeat(dst.a * dst_alpha_factor),
+        Vector3::repeat(src.a),
+        Vector3::repeat(dst.a),
+    )
+    .x;
+
+    Color4::new(
+        color.x.clamp(0.0, 1.0),
+        color.y.clamp(0.0, 1.0),
+        color.z.clamp(0.0, 1.0),
+        alpha.clamp(0.0, 1.0),
+    )
+}
+
+fn blend_factor(blend: &Blend, factor: BlendFactor, src: Color4, dst: Color4) -> Vector4<f32> {
+    use BlendFactor::*;
+
+    let src_v = Vector4::new(src.r, src.g, src.b, src.a);
+    let dst_v = Vector4::new(dst.r, dst.g, dst.b, dst.a);
+    let k = blend.constant_color;
+    let constant = Vector4::new(k.r, k.g, k.b, k.a);
+    let one = Vector4::repeat(1.0);
+
+    match factor {
+        Zero => Vector4::zeros(),
+        One => one,
+        SrcColor => src_v,
+        OneMinusSrcColor => one - src_v,
+        DstColor => dst_v,
+        OneMinusDstColor => one - dst_v,
+        SrcAlpha => Vector4::repeat(src.a),
+        OneMinusSrcAlpha => Vector4::repeat(1.0 - src.a),
+        DstAlpha => Vector4::repeat(dst.a),
+        OneMinusDstAlpha => Vector4::repeat(1.0 - dst.a),
+        SrcAlphaSaturate => {
+            let f = src.a.min(1.0 - dst.a);
+            Vector4::new(f, f, f, 1.0)
+        }
+        ConstantColor => constant,
+        OneMinusConstantColor => one - constant,
+        ConstantAlpha => Vector4::repeat(k.a),
+        OneMinusConstantAlpha => Vector4::repeat(1.0 - k.a),
+    }
+}
+
+/// Applies a blend operation. Min and max ignore the blend factors, as in
+/// OpenGL.
+fn blend_op(
+    op: BlendOp,
+    src: Vector3<f32>,
+    dst: Vector3<f32>,
+    src_raw: Vector3<f32>,
+    dst_raw: Vector3<f32>,
+) -> Vector3<f32> {
+    match op {
+        BlendOp::Add => src + dst,
+        BlendOp::Subtract => src - dst,
+        BlendOp::ReverseSubtract => dst - src,
+        BlendOp::Min => src_raw.inf(&dst_raw),
+        BlendOp::Max => src_raw.sup(&dst_raw),
+    }
+}
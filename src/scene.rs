@@ -0,0 +1,529 @@
+use nalgebra::{Matrix3, Point2, Point3, Vector2};
+use slab::Slab;
+use thiserror::Error;
+
+use crate::{
+    data::{Geometry, GeometryBatch, HasPosition, PrimitiveTag, Transformed},
+    geom::scale_rotate_translate,
+    light::Light,
+};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SceneError {
+    #[error("node {0:?} does not exist")]
+    UnknownNode(NodeId),
+
+    #[error("node {0:?} cannot become its own descendant")]
+    Cycle(NodeId),
+}
+
+/// Identifies a node in a `SceneGraph`.
+///
+/// Ids are not reused, so the id of a removed node stays invalid even if its
+/// slot is taken by a new node.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: usize,
+    generation: u64,
+}
+
+/// Transform of a node relative to its parent.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LocalTransform {
+    pub translation: Vector2<f32>,
+    pub angle: f32,
+    pub scale: Vector2<f32>,
+
+    /// Depth offset that is added to the depth of the parent.
+    pub z: f32,
+}
+
+/// Transform of a node relative to the world, i.e. the composition of the
+/// local transforms of the node and all of its ancestors.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct WorldTransform {
+    /// Transformation in homogeneous coordinates.
+    pub matrix: Matrix3<f32>,
+    pub z: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    generation: u64,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    local: LocalTransform,
+    world: WorldTransform,
+
+    /// Set if `world` needs to be recomputed. If a node is dirty, all of its
+    /// descendants are dirty as well.
+    dirty: bool,
+}
+
+/// A hierarchy of nodes with transforms.
+///
+/// Changing the local transform of a node marks the node and its descendants
+/// as dirty. `update` then recomputes the world transforms of only the dirty
+/// nodes.
+///
+/// Functions that modify the graph return `SceneError::UnknownNode` for ids
+/// of nodes that have been removed, while queries return `None`.
+#[derive(Debug, Clone, Default)]
+pub struct SceneGraph {
+    nodes: Slab<Node>,
+    roots: Vec<NodeId>,
+    next_generation: u64,
+}
+
+/// Geometry that is given in the local space of a node.
+#[derive(Debug, Clone)]
+pub struct Attached<G> {
+    pub transformed: Transformed<G>,
+
+    /// Depth of the node, which is added to the depth of the vertices.
+    pub z: f32,
+}
+
+impl Default for LocalTransform {
+    fn default() -> Self {
+        Self {
+            translation: Vector2::zeros(),
+            angle: 0.0,
+            scale: Vector2::new(1.0, 1.0),
+            z: 0.0,
+        }
+    }
+}
+
+impl LocalTransform {
+    pub fn from_translation(translation: Vector2<f32>) -> Self {
+        Self {
+            translation,
+            ..Self::default()
+        }
+    }
+
+    /// Returns a matrix that applies transformations in this order: first
+    /// scaling, then rotation, and finally translation.
+    pub fn matrix(&self) -> Matrix3<f32> {
+        scale_rotate_translate(self.scale, self.angle, self.translation)
+    }
+}
+
+impl Default for WorldTransform {
+    fn default() -> Self {
+        Self {
+            matrix: Matrix3::identity(),
+            z: 0.0,
+        }
+    }
+}
+
+impl WorldTransform {
+    pub fn then(&self, local: &LocalTransform) -> Self {
+        Self {
+            matrix: self.matrix * local.matrix(),
+            z: self.z + local.z,
+        }
+    }
+
+    pub fn transform_point(&self, p: Point2<f32>) -> Point2<f32> {
+        self.matrix.transform_point(&p)
+    }
+
+    pub fn transform_vector(&self, v: Vector2<f32>) -> Vector2<f32> {
+        self.matrix.transform_vector(&v)
+    }
+
+    pub fn position(&self) -> Point2<f32> {
+        self.transform_point(Point2::origin())
+    }
+
+    /// Returns the rotation of the node's x axis in the world.
+    pub fn angle(&self) -> f32 {
+        self.matrix[(1, 0)].atan2(self.matrix[(0, 0)])
+    }
+
+    /// Returns the lengths of the node's axes in the world.
+    pub fn scale(&self) -> Vector2<f32> {
+        Vector2::new(
+            self.transform_vector(Vector2::x()).norm(),
+            self.transform_vector(Vector2::y()).norm(),
+        )
+    }
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.node(node).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.roots.clear();
+    }
+
+    pub fn insert(
+        &mut self,
+        parent: Option<NodeId>,
+        local: LocalTransform,
+    ) -> Result<NodeId, SceneError> {
+        if let Some(parent) = parent {
+            self.try_node(parent)?;
+        }
+
+        let generation = self.next_generation;
+        self.next_generation += 1;
+
+        let index = self.nodes.insert(Node {
+            generation,
+            parent,
+            children: Vec::new(),
+            local,
+            world: WorldTransform::default(),
+            dirty: true,
+        });
+        let node = NodeId { index, generation };
+
+        self.children_of_mut(parent).push(node);
+
+        Ok(node)
+    }
+
+    /// Removes a node together with all of its descendants.
+    pub fn remove(&mut self, node: NodeId) -> Result<(), SceneError> {
+        let parent = self.try_node(node)?.parent;
+        self.children_of_mut(parent).retain(|child| *child != node);
+
+        let mut stack = vec![node];
+
+        while let Some(node) = stack.pop() {
+            stack.extend(self.nodes.remove(node.index).children);
+        }
+
+        Ok(())
+    }
+
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// Returns the parent of a node, which is `None` for roots.
+    pub fn parent(&self, node: NodeId) -> Result<Option<NodeId>, SceneError> {
+        Ok(self.try_node(node)?.parent)
+    }
+
+    pub fn children(&self, node: NodeId) -> Option<&[NodeId]> {
+        self.node(node).map(|node| node.children.as_slice())
+    }
+
+    /// Returns true if `ancestor` is `node` or one of its ancestors.
+    pub fn is_ancestor(&self, ancestor: NodeId, node: NodeId) -> bool {
+        let mut current = Some(node);
+
+        while let Some(node) = current {
+            if node == ancestor {
+                return true;
+            }
+
+            current = self.node(node).and_then(|node| node.parent);
+        }
+
+        false
+    }
+
+    /// Moves a node to a new parent. The local transform is kept, so the
+    /// node moves along with its new parent.
+    pub fn set_parent(&mut self, node: NodeId, parent: Option<NodeId>) -> Result<(), SceneError> {
+        let old_parent = self.try_node(node)?.parent;
+
+        if let Some(parent) = parent {
+            self.try_node(parent)?;
+
+            if self.is_ancestor(node, parent) {
+                return Err(SceneError::Cycle(node));
+            }
+        }
+
+        self.children_of_mut(old_parent)
+            .retain(|child| *child != node);
+        self.children_of_mut(parent).push(node);
+        self.nodes[node.index].parent = parent;
+
+        self.mark_dirty(node);
+
+        Ok(())
+    }
+
+    pub fn local(&self, node: NodeId) -> Option<&LocalTransform> {
+        self.node(node).map(|node| &node.local)
+    }
+
+    pub fn local_mut(&mut self, node: NodeId) -> Option<&mut LocalTransform> {
+        self.node(node)?;
+        self.mark_dirty(node);
+
+        Some(&mut self.nodes[node.index].local)
+    }
+
+    pub fn set_local(&mut self, node: NodeId, local: LocalTransform) -> Result<(), SceneError> {
+        *self.local_mut(node).ok_or(SceneError::UnknownNode(node))? = local;
+
+        Ok(())
+    }
+
+    /// Returns the world transform of a node as of the last call to `update`.
+    pub fn world(&self, node: NodeId) -> Option<WorldTransform> {
+        let node = self.node(node)?;
+        debug_assert!(!node.dirty, "SceneGraph::update needs to be called");
+
+        Some(node.world)
+    }
+
+    pub fn is_dirty(&self, node: NodeId) -> Option<bool> {
+        self.node(node).map(|node| node.dirty)
+    }
+
+    /// Recomputes the world transforms of all dirty nodes.
+    pub fn update(&mut self) {
+        let mut stack = self.roots.clone();
+
+        while let Some(node) = stack.pop() {
+            if self.nodes[node.index].dirty {
+                // Parents are visited before their children, so the parent's
+                // world transform is up to date here.
+                let parent_world = self.nodes[node.index]
+                    .parent
+                    .map_or_else(WorldTransform::default, |parent| {
+                        self.nodes[parent.index].world
+                    });
+
+                let node = &mut self.nodes[node.index];
+                node.world = parent_world.then(&node.local);
+                node.dirty = false;
+            }
+
+            stack.extend(self.nodes[node.index].children.iter().copied());
+        }
+    }
+
+    /// Places geometry that is given in the local space of a node into the
+    /// world. The node's depth is added to the depth of the vertices.
+    pub fn attach<G>(&self, node: NodeId, geometry: G) -> Option<Attached<G>> {
+        let world = self.world(node)?;
+
+        Some(Attached {
+            transformed: Transformed::new(geometry, world.matrix),
+            z: world.z,
+        })
+    }
+
+    pub fn push<P, G>(
+        &self,
+        node: NodeId,
+        batch: &mut GeometryBatch<P, G::Vertex>,
+        geometry: G,
+    ) -> Result<(), SceneError>
+    where
+        P: PrimitiveTag,
+        G: Geometry<P>,
+        G::Vertex: HasPosition,
+    {
+        batch.push(
+            self.attach(node, geometry)
+                .ok_or(SceneError::UnknownNode(node))?,
+        );
+
+        Ok(())
+    }
+
+    /// Places a light that is given in the local space of a node into the
+    /// world.
+    ///
+    /// The light's angle is rotated with the node, while its height and radius
+    /// are kept.
+    pub fn light(&self, node: NodeId, light: Light) -> Option<Light> {
+        let world = self.world(node)?;
+        let position = world.transform_point(light.position.xy());
+
+        Some(Light {
+            position: Point3::new(position.x, position.y, light.position.z),
+            angle: light.angle + world.angle(),
+            ..light
+        })
+    }
+
+    pub fn push_light(
+        &self,
+        node: NodeId,
+        lights: &mut Vec<Light>,
+        light: Light,
+    ) -> Result<(), SceneError> {
+        lights.push(
+            self.light(node, light)
+                .ok_or(SceneError::UnknownNode(node))?,
+        );
+
+        Ok(())
+    }
+
+    fn node(&self, node: NodeId) -> Option<&Node> {
+        self.nodes
+            .get(node.index)
+            .filter(|entry| entry.generation == node.generation)
+    }
+
+    fn try_node(&self, node: NodeId) -> Result<&Node, SceneError> {
+        self.node(node).ok_or(SceneError::UnknownNode(node))
+    }
+
+    fn children_of_mut(&mut self, parent: Option<NodeId>) -> &mut Vec<NodeId> {
+        match parent {
+            Some(parent) => &mut self.nodes[parent.index].children,
+            None => &mut self.roots,
+        }
+    }
+
+    fn mark_dirty(&mut self, node: NodeId) {
+        let mut stack = vec![node];
+
+        while let Some(node) = stack.pop() {
+            self.nodes[node.index].dirty = true;
+
+            // Dirty children already have dirty descendants.
+            stack.extend(
+                self.nodes[node.index]
+                    .children
+                    .iter()
+                    .copied()
+                    .filter(|child| !self.nodes[child.index].dirty),
+            );
+        }
+    }
+}
+
+impl<P, G> Geometry<P> for Attached<G>
+where
+    P: PrimitiveTag,
+    G: Geometry<P>,
+    G::Vertex: HasPosition,
+{
+    type Vertex = G::Vertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let start_index = vertices.len();
+
+        self.transformed.write(elements, vertices);
+
+        for vertex in vertices[start_index..].iter_mut() {
+            vertex.offset_depth(self.z);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use nalgebra::{Point2, Vector2};
+
+    use super::{LocalTransform, SceneError, SceneGraph};
+
+    fn assert_close(a: Point2<f32>, b: Point2<f32>) {
+        assert!((a - b).norm() < 1e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn children_move_with_parent() {
+        let mut scene = SceneGraph::new();
+        let parent = scene
+            .insert(
+                None,
+                LocalTransform {
+                    translation: Vector2::new(10.0, 0.0),
+                    angle: PI / 2.0,
+                    scale: Vector2::new(2.0, 2.0),
+                    z: 0.5,
+                },
+            )
+            .unwrap();
+        let child = scene
+            .insert(
+                Some(parent),
+                LocalTransform {
+                    z: 0.25,
+                    ..LocalTransform::from_translation(Vector2::new(1.0, 0.0))
+                },
+            )
+            .unwrap();
+
+        scene.update();
+
+        let world = scene.world(child).unwrap();
+        assert_close(world.position(), Point2::new(10.0, 2.0));
+        assert_eq!(world.z, 0.75);
+
+        scene.local_mut(parent).unwrap().translation = Vector2::new(0.0, 5.0);
+        assert_eq!(scene.is_dirty(child), Some(true));
+
+        scene.update();
+        assert_close(
+            scene.world(child).unwrap().position(),
+            Point2::new(0.0, 7.0),
+        );
+    }
+
+    #[test]
+    fn remove_removes_descendants() {
+        let mut scene = SceneGraph::new();
+        let root = scene.insert(None, LocalTransform::default()).unwrap();
+        let child = scene.insert(Some(root), LocalTransform::default()).unwrap();
+        let grandchild = scene
+            .insert(Some(child), LocalTransform::default())
+            .unwrap();
+        let other = scene.insert(None, LocalTransform::default()).unwrap();
+
+        scene.remove(child).unwrap();
+
+        assert_eq!(scene.len(), 2);
+        assert_eq!(scene.children(root), Some(&[][..]));
+        assert!(!scene.contains(grandchild));
+        assert_eq!(scene.remove(child), Err(SceneError::UnknownNode(child)));
+
+        // The slot of the removed node is reused, but its id stays invalid.
+        let new = scene
+            .insert(Some(other), LocalTransform::default())
+            .unwrap();
+        assert!(scene.contains(new));
+        assert!(!scene.contains(child));
+        assert_eq!(scene.local(child), None);
+        assert_eq!(scene.parent(child), Err(SceneError::UnknownNode(child)));
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles() {
+        let mut scene = SceneGraph::new();
+        let root = scene.insert(None, LocalTransform::default()).unwrap();
+        let child = scene.insert(Some(root), LocalTransform::default()).unwrap();
+
+        assert_eq!(
+            scene.set_parent(root, Some(child)),
+            Err(SceneError::Cycle(root))
+        );
+        assert_eq!(scene.set_parent(child, None), Ok(()));
+        assert_eq!(scene.roots(), &[root, child]);
+        assert_eq!(scene.parent(child), Ok(None));
+    }
+}
@@ -13,8 +13,10 @@ use crate::{
     geom::Rect,
     gl::{self, Blend, DrawParams, NewTextureError, Texture, Uniform},
     pass::{SpritePass, ViewMatrices},
-    util, Color4, Context, FetchError,
+    util, Color4,
 };
+#[cfg(feature = "web")]
+use crate::{Context, FetchError};
 
 #[derive(Error, Debug)]
 pub enum LoadFontError {
@@ -24,6 +26,7 @@ pub enum LoadFontError {
     #[error("fontdue error: {0}")]
     Fontdue(&'static str),
 
+    #[cfg(feature = "web")]
     #[error("fetch error: {0}")]
     Fetch(#[from] FetchError),
 }
@@ -69,6 +72,9 @@ pub struct Font {
     glyph_locs: HashMap<GlyphRasterConfig, GlyphLoc>,
     bitmap_buffer: Vec<u8>,
 
+    /// Context generation that the atlases have been filled in.
+    generation: u64,
+
     sprite_pass: Rc<SpritePass>,
 }
 
@@ -89,12 +95,23 @@ impl TextBatch {
 }
 
 impl Font {
+    #[cfg(feature = "web")]
     pub async fn load(context: &Context, path: &str, scale: f32) -> Result<Self, LoadFontError> {
         let data = crate::fetch_data(path).await?;
         Self::from_data(context, &data, scale)
     }
 
+    #[cfg(feature = "web")]
     pub fn from_data(context: &Context, data: &[u8], scale: f32) -> Result<Self, LoadFontError> {
+        Self::new(context.gl(), context.sprite_pass(), data, scale)
+    }
+
+    pub fn new(
+        gl: Rc<gl::Context>,
+        sprite_pass: Rc<SpritePass>,
+        data: &[u8],
+        scale: f32,
+    ) -> Result<Self, LoadFontError> {
         let settings = FontSettings {
             scale,
             ..Default::default()
@@ -102,18 +119,20 @@ impl Font {
 
         let font = fontdue::Font::from_bytes(data, settings).map_err(LoadFontError::Fontdue)?;
 
-        let atlas_size = Texture::max_size(&*context.gl()).min(MAX_ATLAS_SIZE);
-        let atlas = Atlas::new(context.gl(), Vector2::new(atlas_size, atlas_size))?;
+        let atlas_size = Texture::max_size(&gl).min(MAX_ATLAS_SIZE);
+        let atlas = Atlas::new(gl.clone(), Vector2::new(atlas_size, atlas_size))?;
         let layout = Layout::new(CoordinateSystem::PositiveYDown);
+        let generation = gl.generation();
 
         Ok(Font {
-            gl: context.gl(),
+            gl,
             font,
             layout,
             atlases: vec![atlas],
             glyph_locs: HashMap::new(),
             bitmap_buffer: Vec::new(),
-            sprite_pass: context.sprite_pass(),
+            generation,
+            sprite_pass,
         })
     }
 
@@ -149,6 +168,12 @@ impl Font {
         text: Text,
         batch: &mut TextBatch,
     ) -> Result<Vector2<f32>, WriteTextError> {
+        if self.gl.generation() != self.generation {
+            // Glyphs are written into the atlases with `set_sub_image`, so
+            // they are gone after context loss. Start over with empty atlases.
+            self.reset_atlases()?;
+        }
+
         let dpr = util::device_pixel_ratio() as f32;
 
         self.layout.reset(&LayoutSettings {
@@ -222,6 +247,19 @@ impl Font {
         }
     }
 
+    fn reset_atlases(&mut self) -> Result<(), WriteTextError> {
+        let atlas_size = Texture::max_size(&self.gl).min(MAX_ATLAS_SIZE);
+
+        self.atlases = vec![Atlas::new(
+            self.gl.clone(),
+            Vector2::new(atlas_size, atlas_size),
+        )?];
+        self.glyph_locs.clear();
+        self.generation = self.gl.generation();
+
+        Ok(())
+    }
+
     fn get_glyph_loc(
         gl: Rc<gl::Context>,
         font: &fontdue::Font,
@@ -0,0 +1,455 @@
+use nalgebra::{Point2, Vector2};
+use thiserror::Error;
+
+use super::{TileId, TileLayerDef};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AutotileError {
+    #[error("terrain {0} is in use, but there are only {1} rule sets")]
+    TerrainWithoutRules(usize, usize),
+
+    #[error("position ({0}, {1}) is outside of the grid")]
+    OutOfBounds(u32, u32),
+
+    #[error("expected {0} cells, but got {1}")]
+    InvalidCellCount(usize, usize),
+}
+
+/// How the neighbourhood of a cell is turned into a tile.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AutotileMode {
+    /// Looks at the four edge neighbours, resulting in 16 tiles.
+    ///
+    /// Mask bits: N = 1, E = 2, S = 4, W = 8.
+    FourBit,
+
+    /// Looks at all eight neighbours, where diagonal neighbours only count if
+    /// both adjacent edge neighbours match, resulting in 47 tiles.
+    ///
+    /// Mask bits: N = 1, NE = 2, E = 4, SE = 8, S = 16, SW = 32, W = 64,
+    /// NW = 128.
+    Blob,
+
+    /// Like `Blob`, but only looks at the four corners of a cell, where a
+    /// corner matches if all three cells touching it match, resulting in 16
+    /// tiles.
+    ///
+    /// Mask bits: NE = 1, SE = 2, SW = 4, NW = 8.
+    CornerBlob,
+}
+
+/// A rule set that resolves the neighbour mask of a terrain to a tile.
+#[derive(Debug, Clone)]
+pub struct AutotileRules {
+    pub mode: AutotileMode,
+
+    /// Tiles indexed by mask for `FourBit` and `CornerBlob`, or by the index
+    /// of the mask in `blob_masks()` for `Blob`.
+    pub tiles: Vec<TileId>,
+
+    /// Whether cells outside of the grid count as matching neighbours.
+    pub border_matches: bool,
+}
+
+/// A grid of terrain ids that keeps its resolved tiles up to date.
+///
+/// Each terrain id refers to an entry in the rule sets. Cells of different
+/// terrains do not connect to each other.
+#[derive(Debug, Clone)]
+pub struct Autotiler {
+    size: Vector2<u32>,
+    rules: Vec<AutotileRules>,
+    terrain: Vec<Option<usize>>,
+    tiles: Vec<TileId>,
+}
+
+const N: u8 = 1;
+const NE: u8 = 2;
+const E: u8 = 4;
+const SE: u8 = 8;
+const S: u8 = 16;
+const SW: u8 = 32;
+const W: u8 = 64;
+const NW: u8 = 128;
+
+/// Offsets of the eight neighbours, in the order of the blob mask bits.
+const NEIGHBOURS: [(i64, i64); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// Returns the 47 blob masks that remain after dropping diagonal neighbours
+/// without both adjacent edge neighbours, in ascending order.
+pub fn blob_masks() -> Vec<u8> {
+    (0..=255u8)
+        .filter(|mask| canonical_blob_mask(*mask) == *mask)
+        .collect()
+}
+
+fn canonical_blob_mask(mask: u8) -> u8 {
+    let mut result = mask & (N | E | S | W);
+
+    for (corner, a, b) in [(NE, N, E), (SE, S, E), (SW, S, W), (NW, N, W)] {
+        if mask & corner != 0 && mask & a != 0 && mask & b != 0 {
+            result |= corner;
+        }
+    }
+
+    result
+}
+
+impl AutotileRules {
+    pub fn four_bit(tiles: [TileId; 16]) -> Self {
+        Self {
+            mode: AutotileMode::FourBit,
+            tiles: tiles.to_vec(),
+            border_matches: true,
+        }
+    }
+
+    pub fn blob(tiles: [TileId; 47]) -> Self {
+        Self {
+            mode: AutotileMode::Blob,
+            tiles: tiles.to_vec(),
+            border_matches: true,
+        }
+    }
+
+    pub fn corner_blob(tiles: [TileId; 16]) -> Self {
+        Self {
+            mode: AutotileMode::CornerBlob,
+            tiles: tiles.to_vec(),
+            border_matches: true,
+        }
+    }
+
+    /// Resolves a mask of all eight neighbours, given in the bit layout of
+    /// `AutotileMode::Blob`.
+    pub fn resolve(&self, neighbours: u8) -> TileId {
+        let mask = match self.mode {
+            AutotileMode::FourBit => {
+                let bit = |b: u8, to: u8| if neighbours & b != 0 { to } else { 0 };
+
+                bit(N, 1) | bit(E, 2) | bit(S, 4) | bit(W, 8)
+            }
+            AutotileMode::Blob => {
+                let mask = canonical_blob_mask(neighbours);
+
+                // Index of the mask in `blob_masks()`.
+                (0..mask).filter(|m| canonical_blob_mask(*m) == *m).count() as u8
+            }
+            AutotileMode::CornerBlob => {
+                let corner = |c: u8, a: u8, b: u8, to: u8| {
+                    if neighbours & (c | a | b) == c | a | b {
+                        to
+                    } else {
+                        0
+                    }
+                };
+
+                corner(NE, N, E, 1)
+                    | corner(SE, S, E, 2)
+                    | corner(SW, S, W, 4)
+                    | corner(NW, N, W, 8)
+            }
+        };
+
+        self.tiles.get(mask as usize).copied().unwrap_or_default()
+    }
+}
+
+impl Autotiler {
+    pub fn new(size: Vector2<u32>, rules: Vec<AutotileRules>) -> Self {
+        let num_cells = size.x as usize * size.y as usize;
+
+        Self {
+            size,
+            rules,
+            terrain: vec![None; num_cells],
+            tiles: vec![TileId::default(); num_cells],
+        }
+    }
+
+    /// Creates an autotiler with a single terrain from a boolean grid in
+    /// row-major order.
+    pub fn from_bools(
+        size: Vector2<u32>,
+        rules: AutotileRules,
+        cells: &[bool],
+    ) -> Result<Self, AutotileError> {
+        let num_cells = size.x as usize * size.y as usize;
+        if cells.len() != num_cells {
+            return Err(AutotileError::InvalidCellCount(num_cells, cells.len()));
+        }
+
+        let mut result = Self::new(size, vec![rules]);
+        result.terrain = cells.iter().map(|cell| cell.then(|| 0)).collect();
+        result.resolve_all();
+
+        Ok(result)
+    }
+
+    pub fn size(&self) -> Vector2<u32> {
+        self.size
+    }
+
+    pub fn terrain(&self, pos: Point2<u32>) -> Option<usize> {
+        self.index(pos).and_then(|index| self.terrain[index])
+    }
+
+    pub fn tile(&self, pos: Point2<u32>) -> TileId {
+        self.index(pos)
+            .map_or(TileId::default(), |index| self.tiles[index])
+    }
+
+    /// Resolved tiles in row-major order.
+    pub fn tiles(&self) -> &[TileId] {
+        &self.tiles
+    }
+
+    /// Changes the terrain of a cell, updating only the tiles of the cell and
+    /// its neighbours.
+    ///
+    /// Returns the positions whose tile changed.
+    pub fn set(
+        &mut self,
+        pos: Point2<u32>,
+        terrain: Option<usize>,
+    ) -> Result<Vec<Point2<u32>>, AutotileError> {
+        if let Some(terrain) = terrain.filter(|terrain| *terrain >= self.rules.len()) {
+            return Err(AutotileError::TerrainWithoutRules(
+                terrain,
+                self.rules.len(),
+            ));
+        }
+
+        let index = self
+            .index(pos)
+            .ok_or(AutotileError::OutOfBounds(pos.x, pos.y))?;
+
+        if self.terrain[index] == terrain {
+            return Ok(Vec::new());
+        }
+        self.terrain[index] = terrain;
+
+        let affected = std::iter::once((0, 0))
+            .chain(NEIGHBOURS.iter().copied())
+            .filter_map(|(dx, dy)| self.offset(pos, dx, dy))
+            .collect::<Vec<_>>();
+
+        Ok(affected
+            .into_iter()
+            .filter(|pos| self.resolve(*pos))
+            .collect())
+    }
+
+    /// Recomputes all tiles, e.g. after the rules have been changed.
+    pub fn resolve_all(&mut self) {
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                self.resolve(Point2::new(x, y));
+            }
+        }
+    }
+
+    pub fn rules(&self) -> &[AutotileRules] {
+        &self.rules
+    }
+
+    /// Replaces the rule sets and recomputes all tiles.
+    ///
+    /// Fails without changing anything if a terrain that is in use has no
+    /// rule set in `rules`.
+    pub fn set_rules(&mut self, rules: Vec<AutotileRules>) -> Result<(), AutotileError> {
+        if let Some(terrain) = self
+            .terrain
+            .iter()
+            .flatten()
+            .copied()
+            .find(|terrain| *terrain >= rules.len())
+        {
+            return Err(AutotileError::TerrainWithoutRules(terrain, rules.len()));
+        }
+
+        self.rules = rules;
+        self.resolve_all();
+
+        Ok(())
+    }
+
+    /// Builds a tile layer from the resolved tiles.
+    pub fn to_tile_layer(&self, name: &str) -> TileLayerDef {
+        TileLayerDef {
+            name: name.to_string(),
+            size: self.size,
+            tiles: self.tiles.clone(),
+            visible: true,
+            opacity: 1.0,
+            properties: Default::default(),
+        }
+    }
+
+    fn index(&self, pos: Point2<u32>) -> Option<usize> {
+        if pos.x < self.size.x && pos.y < self.size.y {
+            Some((pos.y * self.size.x + pos.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn offset(&self, pos: Point2<u32>, dx: i64, dy: i64) -> Option<Point2<u32>> {
+        let x = pos.x as i64 + dx;
+        let y = pos.y as i64 + dy;
+
+        if x >= 0 && y >= 0 && x < self.size.x as i64 && y < self.size.y as i64 {
+            Some(Point2::new(x as u32, y as u32))
+        } else {
+            None
+        }
+    }
+
+    /// Recomputes the tile at `pos`, returning true if it changed.
+    fn resolve(&mut self, pos: Point2<u32>) -> bool {
+        let index = self.index(pos).unwrap();
+
+        let tile = match self.terrain[index] {
+            Some(terrain) => {
+                let rules = &self.rules[terrain];
+                let mut neighbours = 0;
+
+                for (bit, (dx, dy)) in NEIGHBOURS.iter().enumerate() {
+                    let matches = match self.offset(pos, *dx, *dy) {
+                        Some(other) => self.terrain(other) == Some(terrain),
+                        None => rules.border_matches,
+                    };
+
+                    if matches {
+                        neighbours |= 1 << bit;
+                    }
+                }
+
+                rules.resolve(neighbours)
+            }
+            None => TileId::default(),
+        };
+
+        let changed = self.tiles[index] != tile;
+        self.tiles[index] = tile;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Vector2};
+
+    use super::{blob_masks, AutotileError, AutotileRules, Autotiler};
+    use crate::tilemap::TileId;
+
+    /// Rules that resolve every mask to the tile with the mask as its id.
+    fn identity_rules(rules: fn([TileId; 16]) -> AutotileRules) -> AutotileRules {
+        let mut tiles = [TileId::default(); 16];
+        for (mask, tile) in tiles.iter_mut().enumerate() {
+            *tile = TileId(mask as u32);
+        }
+
+        AutotileRules {
+            border_matches: false,
+            ..rules(tiles)
+        }
+    }
+
+    #[test]
+    fn blob_has_47_masks() {
+        assert_eq!(blob_masks().len(), 47);
+    }
+
+    #[test]
+    fn four_bit() {
+        #[rustfmt::skip]
+        let cells = [
+            false, true, false,
+            true, true, true,
+            false, false, false,
+        ];
+        let autotiler = Autotiler::from_bools(
+            Vector2::new(3, 3),
+            identity_rules(AutotileRules::four_bit),
+            &cells,
+        )
+        .unwrap();
+
+        // N = 1, E = 2, S = 4, W = 8.
+        assert_eq!(autotiler.tile(Point2::new(1, 1)), TileId(1 | 2 | 8));
+        assert_eq!(autotiler.tile(Point2::new(1, 0)), TileId(4));
+        assert_eq!(autotiler.tile(Point2::new(0, 1)), TileId(2));
+        assert_eq!(autotiler.tile(Point2::new(1, 2)), TileId::default());
+    }
+
+    #[test]
+    fn corner_blob() {
+        let mut autotiler = Autotiler::from_bools(
+            Vector2::new(2, 2),
+            identity_rules(AutotileRules::corner_blob),
+            &[true; 4],
+        )
+        .unwrap();
+
+        // NE = 1, SE = 2, SW = 4, NW = 8.
+        assert_eq!(autotiler.tile(Point2::new(0, 0)), TileId(2));
+        assert_eq!(autotiler.tile(Point2::new(1, 1)), TileId(8));
+
+        autotiler.set(Point2::new(1, 0), None).unwrap();
+        assert_eq!(autotiler.tile(Point2::new(0, 0)), TileId(0));
+        assert_eq!(autotiler.tile(Point2::new(1, 1)), TileId(0));
+    }
+
+    #[test]
+    fn set_updates_neighbours() {
+        let mut autotiler = Autotiler::from_bools(
+            Vector2::new(4, 1),
+            identity_rules(AutotileRules::four_bit),
+            &[true, true, false, false],
+        )
+        .unwrap();
+
+        let changed = autotiler.set(Point2::new(2, 0), Some(0)).unwrap();
+        assert_eq!(changed, vec![Point2::new(2, 0), Point2::new(1, 0)]);
+        assert_eq!(autotiler.tile(Point2::new(1, 0)), TileId(2 | 8));
+        assert_eq!(autotiler.tile(Point2::new(2, 0)), TileId(8));
+
+        assert_eq!(autotiler.set(Point2::new(2, 0), Some(0)), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn errors() {
+        let mut autotiler = Autotiler::new(
+            Vector2::new(2, 2),
+            vec![identity_rules(AutotileRules::four_bit)],
+        );
+
+        assert_eq!(
+            autotiler.set(Point2::new(0, 0), Some(1)),
+            Err(AutotileError::TerrainWithoutRules(1, 1))
+        );
+        assert_eq!(
+            autotiler.set(Point2::new(2, 0), Some(0)),
+            Err(AutotileError::OutOfBounds(2, 0))
+        );
+        assert_eq!(
+            Autotiler::from_bools(
+                Vector2::new(2, 2),
+                identity_rules(AutotileRules::four_bit),
+                &[true; 3]
+            )
+            .unwrap_err(),
+            AutotileError::InvalidCellCount(4, 3)
+        );
+    }
+}
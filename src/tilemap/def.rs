@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use nalgebra::{Point2, Vector2};
+
+use crate::geom::{Circle, Rect, RotatedRect, Shape};
+
+/// Name of the boolean property that marks tiles, tile layers, objects or
+/// object layers as solid.
+pub const SOLID_PROPERTY: &str = "solid";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+pub type Properties = HashMap<String, PropertyValue>;
+
+/// A global tile id together with its flip flags, as stored in tile layers.
+///
+/// The id zero stands for an empty cell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TileId(pub u32);
+
+#[derive(Debug, Clone)]
+pub struct TilesetDef {
+    pub name: String,
+    pub first_gid: u32,
+
+    /// Path of the tileset image, relative to the map file.
+    pub image: String,
+    pub image_size: Vector2<u32>,
+    pub tile_size: Vector2<u32>,
+    pub margin: u32,
+    pub spacing: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+
+    /// Properties of individual tiles, indexed by the local tile id.
+    pub tile_properties: HashMap<u32, Properties>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TileLayerDef {
+    pub name: String,
+    pub size: Vector2<u32>,
+
+    /// Tile ids in row-major order.
+    pub tiles: Vec<TileId>,
+    pub visible: bool,
+    pub opacity: f32,
+    pub properties: Properties,
+}
+
+#[derive(Debug, Clone)]
+pub enum ObjectShape {
+    Rect(Rect),
+    RotatedRect(RotatedRect),
+    Ellipse(RotatedRect),
+    Point(Point2<f32>),
+    Polygon(Vec<Point2<f32>>),
+    Polyline(Vec<Point2<f32>>),
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectDef {
+    pub id: u32,
+    pub name: String,
+    pub class: String,
+    pub shape: ObjectShape,
+    pub visible: bool,
+    pub properties: Properties,
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectLayerDef {
+    pub name: String,
+    pub objects: Vec<ObjectDef>,
+    pub visible: bool,
+    pub properties: Properties,
+}
+
+#[derive(Debug, Clone)]
+pub enum LayerDef {
+    Tiles(TileLayerDef),
+    Objects(ObjectLayerDef),
+}
+
+/// An orthogonal tilemap, independent of any GPU resources.
+#[derive(Debug, Clone)]
+pub struct TilemapDef {
+    /// Size of the map in tiles.
+    pub size: Vector2<u32>,
+
+    /// Size of a single tile in world units.
+    pub tile_size: Vector2<u32>,
+
+    pub tilesets: Vec<TilesetDef>,
+
+    /// Layers in drawing order, with groups flattened.
+    pub layers: Vec<LayerDef>,
+    pub properties: Properties,
+}
+
+impl PropertyValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl TileId {
+    const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+    const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+    const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+    const FLAGS: u32 = 0xF000_0000;
+
+    pub fn gid(self) -> u32 {
+        self.0 & !Self::FLAGS
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.gid() == 0
+    }
+
+    pub fn flipped_horizontally(self) -> bool {
+        self.0 & Self::FLIPPED_HORIZONTALLY != 0
+    }
+
+    pub fn flipped_vertically(self) -> bool {
+        self.0 & Self::FLIPPED_VERTICALLY != 0
+    }
+
+    pub fn flipped_diagonally(self) -> bool {
+        self.0 & Self::FLIPPED_DIAGONALLY != 0
+    }
+}
+
+impl TilesetDef {
+    pub fn contains(&self, gid: u32) -> bool {
+        gid >= self.first_gid && gid < self.first_gid + self.tile_count
+    }
+
+    /// Returns the source rectangle of a tile in texels, with the origin at
+    /// the top-left of the image and Y pointing down.
+    pub fn tile_tex_rect(&self, local_id: u32) -> Rect {
+        let columns = self.columns.max(1);
+        let x = self.margin + (local_id % columns) * (self.tile_size.x + self.spacing);
+        let y = self.margin + (local_id / columns) * (self.tile_size.y + self.spacing);
+
+        Rect::from_top_left(
+            Point2::new(x as f32, y as f32),
+            Vector2::new(self.tile_size.x as f32, self.tile_size.y as f32),
+        )
+    }
+
+    pub fn is_solid(&self, local_id: u32) -> bool {
+        self.tile_properties.get(&local_id).map_or(false, is_solid)
+    }
+}
+
+impl TileLayerDef {
+    pub fn get(&self, pos: Point2<u32>) -> TileId {
+        if pos.x >= self.size.x || pos.y >= self.size.y {
+            return TileId::default();
+        }
+
+        // The fields are public, so `tiles` may be shorter than `size` says.
+        let index = pos.y as usize * self.size.x as usize + pos.x as usize;
+        self.tiles.get(index).copied().unwrap_or_default()
+    }
+
+    pub fn is_solid(&self) -> bool {
+        is_solid(&self.properties)
+    }
+}
+
+impl ObjectShape {
+    /// Converts the object to a shape for collision detection.
+    ///
+    /// Ellipses are approximated by a circle with their mean radius. Points,
+    /// polygons and polylines have no corresponding `Shape`.
+    pub fn to_shape(&self) -> Option<Shape> {
+        match self {
+            ObjectShape::Rect(rect) => Some(Shape::Rect(*rect)),
+            ObjectShape::RotatedRect(rect) => Some(Shape::RotatedRect(*rect)),
+            ObjectShape::Ellipse(rect) => Some(Shape::Circle(Circle {
+                center: rect.center,
+                radius: (rect.size.x + rect.size.y) / 4.0,
+            })),
+            ObjectShape::Point(_) | ObjectShape::Polygon(_) | ObjectShape::Polyline(_) => None,
+        }
+    }
+}
+
+impl ObjectDef {
+    pub fn is_solid(&self) -> bool {
+        is_solid(&self.properties)
+    }
+}
+
+impl ObjectLayerDef {
+    pub fn is_solid(&self) -> bool {
+        is_solid(&self.properties)
+    }
+}
+
+impl TilemapDef {
+    pub fn tileset_index(&self, gid: u32) -> Option<usize> {
+        self.tilesets
+            .iter()
+            .position(|tileset| tileset.contains(gid))
+    }
+
+    /// Returns the world rectangle that is covered by the given tile.
+    pub fn tile_rect(&self, pos: Point2<u32>) -> Rect {
+        let tile_size = Vector2::new(self.tile_size.x as f32, self.tile_size.y as f32);
+
+        Rect::from_top_left(
+            Point2::new(pos.x as f32 * tile_size.x, pos.y as f32 * tile_size.y),
+            tile_size,
+        )
+    }
+
+    /// Returns the world rectangle that is covered by the whole map.
+    pub fn rect(&self) -> Rect {
+        Rect::from_top_left(
+            Point2::origin(),
+            Vector2::new(
+                self.size.x as f32 * self.tile_size.x as f32,
+                self.size.y as f32 * self.tile_size.y as f32,
+            ),
+        )
+    }
+
+    /// Returns true if the tile at `pos` is solid in any tile layer.
+    ///
+    /// A tile is solid if its layer or its tileset entry has the boolean
+    /// property `solid` set.
+    pub fn is_solid(&self, pos: Point2<u32>) -> bool {
+        self.layers.iter().any(|layer| match layer {
+            LayerDef::Tiles(layer) => {
+                let id = layer.get(pos);
+
+                !id.is_empty()
+                    && (layer.is_solid()
+                        || self.tileset_index(id.gid()).map_or(false, |index| {
+                            let tileset = &self.tilesets[index];
+                            tileset.is_solid(id.gid() - tileset.first_gid)
+                        }))
+            }
+            LayerDef::Objects(_) => false,
+        })
+    }
+}
+
+fn is_solid(properties: &Properties) -> bool {
+    properties
+        .get(SOLID_PROPERTY)
+        .and_then(PropertyValue::as_bool)
+        .unwrap_or(false)
+}
@@ -0,0 +1,445 @@
+use std::rc::Rc;
+
+use nalgebra::{Point2, Point3, Vector2};
+use thiserror::Error;
+
+use crate::{
+    data::{quad_triangle_indices, Geometry, Mesh, SpriteBatch, SpriteVertex, TriangleTag},
+    geom::{self, Camera, Circle, Grid, Line, Rect, Screen, Shape},
+    gl::{self, DrawParams, DrawUnit, Texture, TextureParams, Uniform},
+    light::{
+        OccluderBatch, OccluderCircle, OccluderLine, OccluderOutline, OccluderRect,
+        OccluderRotatedRect,
+    },
+    pass::{SpritePass, ViewMatrices},
+    Color4,
+};
+#[cfg(feature = "web")]
+use crate::{gl::LoadTextureError, Context, FetchError};
+
+use super::{LayerDef, ObjectShape, TileId, TiledError, TilemapDef, TilesetDef};
+
+#[derive(Error, Debug)]
+pub enum LoadTilemapError {
+    #[cfg(feature = "web")]
+    #[error("fetch error: {0}")]
+    Fetch(#[from] FetchError),
+
+    #[error("map is not valid UTF-8")]
+    InvalidUtf8,
+
+    #[error("Tiled error: {0}")]
+    Tiled(#[from] TiledError),
+
+    #[cfg(feature = "web")]
+    #[error("load texture error: {0}")]
+    LoadTexture(#[from] LoadTextureError),
+
+    #[error("tilemap error: {0}")]
+    NewTilemap(#[from] NewTilemapError),
+}
+
+#[derive(Error, Debug)]
+pub enum NewTilemapError {
+    #[error("OpenGL error: {0}")]
+    OpenGL(#[from] gl::Error),
+
+    #[error("map has {0} tilesets, but {1} textures were given")]
+    TextureCountMismatch(usize, usize),
+
+    #[error("chunk size must be positive")]
+    ZeroChunkSize,
+}
+
+#[derive(Debug, Clone)]
+pub struct TilemapParams {
+    /// Width and height of a chunk in tiles.
+    pub chunk_size: u32,
+
+    /// Parameters for loading the tileset textures.
+    pub texture_params: TextureParams,
+}
+
+impl Default for TilemapParams {
+    fn default() -> Self {
+        Self {
+            chunk_size: 32,
+            texture_params: TextureParams::nearest_rgbau8(),
+        }
+    }
+}
+
+/// Static meshes of one tile layer for all tiles that use one tileset.
+struct LayerMeshes {
+    tileset_index: usize,
+
+    /// One mesh per chunk, sharing a single vertex array.
+    chunks: Vec<Mesh<SpriteVertex>>,
+}
+
+/// A tilemap whose tile layers are split into chunks of static meshes, so that
+/// only the visible part of the map needs to be drawn.
+pub struct Tilemap {
+    def: TilemapDef,
+    textures: Vec<Rc<Texture>>,
+    num_chunks: Vector2<u32>,
+    chunk_rects: Vec<Rect>,
+    layers: Vec<LayerMeshes>,
+}
+
+impl Tilemap {
+    /// Loads a map in Tiled's JSON format, together with its tileset images.
+    #[cfg(feature = "web")]
+    pub async fn load(
+        context: &Context,
+        path: &str,
+        params: TilemapParams,
+    ) -> Result<Self, LoadTilemapError> {
+        let data = crate::fetch_data(path).await?;
+        let json = std::str::from_utf8(&data).map_err(|_| LoadTilemapError::InvalidUtf8)?;
+        let def = TilemapDef::from_tiled_json(json)?;
+
+        // Tileset images are given relative to the map file.
+        let dir = path.rfind('/').map_or("", |index| &path[..=index]);
+
+        let mut textures = Vec::new();
+        for tileset in def.tilesets.iter() {
+            let image_path = format!("{}{}", dir, tileset.image);
+            let texture =
+                Texture::load(context.gl(), &image_path, params.texture_params.clone()).await?;
+            textures.push(Rc::new(texture));
+        }
+
+        Ok(Self::new(context.gl(), def, textures, params)?)
+    }
+
+    /// Builds the meshes of a tilemap. There must be one texture per tileset.
+    pub fn new(
+        gl: Rc<gl::Context>,
+        def: TilemapDef,
+        textures: Vec<Rc<Texture>>,
+        params: TilemapParams,
+    ) -> Result<Self, NewTilemapError> {
+        if def.tilesets.len() != textures.len() {
+            return Err(NewTilemapError::TextureCountMismatch(
+                def.tilesets.len(),
+                textures.len(),
+            ));
+        }
+        if params.chunk_size == 0 {
+            return Err(NewTilemapError::ZeroChunkSize);
+        }
+
+        let num_chunks = def
+            .size
+            .map(|size| size / params.chunk_size + u32::from(size % params.chunk_size != 0));
+        let chunk_origins = (0..num_chunks.y)
+            .flat_map(|y| (0..num_chunks.x).map(move |x| Point2::new(x, y) * params.chunk_size))
+            .collect::<Vec<_>>();
+        let chunk_rects = chunk_origins
+            .iter()
+            .map(|origin| chunk_rect(&def, *origin, params.chunk_size))
+            .collect();
+
+        let mut layers = Vec::new();
+
+        for layer in def.layers.iter() {
+            let layer = match layer {
+                LayerDef::Tiles(layer) if layer.visible => layer,
+                _ => continue,
+            };
+
+            for (tileset_index, tileset) in def.tilesets.iter().enumerate() {
+                let mut batch = SpriteBatch::new(gl.clone())?;
+                let mut ranges = Vec::with_capacity(chunk_origins.len());
+
+                for origin in chunk_origins.iter() {
+                    let start = batch.num_elements();
+
+                    for y in origin.y..origin.y.saturating_add(params.chunk_size).min(def.size.y) {
+                        for x in
+                            origin.x..origin.x.saturating_add(params.chunk_size).min(def.size.x)
+                        {
+                            let pos = Point2::new(x, y);
+                            let id = layer.get(pos);
+
+                            if !id.is_empty() && tileset.contains(id.gid()) {
+                                batch.push(TileSprite {
+                                    rect: def.tile_rect(pos),
+                                    depth: 0.0,
+                                    tileset,
+                                    id,
+                                    color: Color4::new(1.0, 1.0, 1.0, layer.opacity),
+                                });
+                            }
+                        }
+                    }
+
+                    ranges.push(start..batch.num_elements());
+                }
+
+                if batch.num_elements() == 0 {
+                    continue;
+                }
+
+                let mesh = batch.into_mesh();
+                let chunks = ranges
+                    .into_iter()
+                    .map(|range| Mesh::new(mesh.vertex_array(), mesh.primitive_mode(), range))
+                    .collect();
+
+                layers.push(LayerMeshes {
+                    tileset_index,
+                    chunks,
+                });
+            }
+        }
+
+        Ok(Self {
+            def,
+            textures,
+            num_chunks,
+            chunk_rects,
+            layers,
+        })
+    }
+
+    pub fn def(&self) -> &TilemapDef {
+        &self.def
+    }
+
+    pub fn textures(&self) -> &[Rc<Texture>] {
+        &self.textures
+    }
+
+    pub fn num_chunks(&self) -> Vector2<u32> {
+        self.num_chunks
+    }
+
+    /// Returns the draw units of all chunks that overlap `visible_rect`, in
+    /// drawing order.
+    ///
+    /// Usually, `visible_rect` is given by `Camera::visible_world_rect`.
+    pub fn visible_draw_units(
+        &self,
+        visible_rect: Rect,
+    ) -> impl Iterator<Item = (&Texture, DrawUnit<SpriteVertex>)> + '_ {
+        let visible_chunks = self
+            .chunk_rects
+            .iter()
+            .enumerate()
+            .filter(|(_, rect)| geom::rect_rect_overlap(visible_rect, **rect).is_some())
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        self.layers.iter().flat_map(move |layer| {
+            let texture = &*self.textures[layer.tileset_index];
+
+            visible_chunks
+                .clone()
+                .into_iter()
+                .map(move |index| &layer.chunks[index])
+                .filter(|mesh| !mesh.element_range().is_empty())
+                .map(move |mesh| (texture, mesh.draw_unit()))
+        })
+    }
+
+    /// Draws the chunks that are visible from `camera`.
+    pub fn draw(
+        &self,
+        pass: &SpritePass,
+        matrices: &Uniform<ViewMatrices>,
+        camera: &Camera,
+        screen: Screen,
+        params: &DrawParams,
+    ) {
+        for (texture, draw_unit) in self.visible_draw_units(camera.visible_world_rect(screen)) {
+            pass.draw(matrices, texture, draw_unit, params);
+        }
+    }
+
+    /// Returns collision shapes for all solid tiles and solid objects.
+    ///
+    /// Horizontally adjacent solid tiles are merged into a single rectangle.
+    pub fn collision_shapes(&self) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+
+        for y in 0..self.def.size.y {
+            let mut x = 0;
+
+            while x < self.def.size.x {
+                if !self.def.is_solid(Point2::new(x, y)) {
+                    x += 1;
+                    continue;
+                }
+
+                let start = x;
+                while x < self.def.size.x && self.def.is_solid(Point2::new(x, y)) {
+                    x += 1;
+                }
+
+                let first = self.def.tile_rect(Point2::new(start, y));
+                let last = self.def.tile_rect(Point2::new(x - 1, y));
+
+                shapes.push(Shape::Rect(Rect::from_top_left(
+                    first.top_left(),
+                    last.bottom_right() - first.top_left(),
+                )));
+            }
+        }
+
+        shapes.extend(self.solid_objects().filter_map(|shape| shape.to_shape()));
+
+        shapes
+    }
+
+    /// Inserts the collision shapes into `grid`, returning their keys.
+    pub fn insert_collision_shapes<T>(&self, grid: &mut Grid<T>, data: T) -> Vec<usize>
+    where
+        T: Clone,
+    {
+        self.collision_shapes()
+            .into_iter()
+            .map(|shape| grid.insert(shape, data.clone()))
+            .collect()
+    }
+
+    /// Pushes occluders for the outlines of solid tiles and for solid objects.
+    ///
+    /// Only edges between solid and non-solid tiles produce occluders, and
+    /// collinear edges are merged.
+    pub fn push_occluders(&self, batch: &mut OccluderBatch, height: f32) {
+        batch.push(OccluderOutline {
+            lines: geom::grid_outline(self.def.rect(), self.def.size, |pos| self.def.is_solid(pos)),
+            height,
+            ignore_light_index1: None,
+            ignore_light_index2: None,
+        });
+
+        for shape in self.solid_objects() {
+            match shape {
+                ObjectShape::Rect(rect) => batch.push(OccluderRect {
+                    rect: *rect,
+                    height,
+                    ignore_light_index1: None,
+                    ignore_light_index2: None,
+                }),
+                ObjectShape::RotatedRect(rect) => batch.push(OccluderRotatedRect {
+                    rect: *rect,
+                    height,
+                    ignore_light_index1: None,
+                    ignore_light_index2: None,
+                }),
+                ObjectShape::Ellipse(rect) => batch.push(OccluderCircle {
+                    circle: Circle {
+                        center: rect.center,
+                        radius: (rect.size.x + rect.size.y) / 4.0,
+                    },
+                    angle: rect.angle,
+                    num_segments: 16,
+                    height,
+                    ignore_light_index1: None,
+                    ignore_light_index2: None,
+                }),
+                ObjectShape::Point(_) => (),
+                ObjectShape::Polygon(points) | ObjectShape::Polyline(points) => {
+                    let closed = matches!(shape, ObjectShape::Polygon(_));
+                    let num_lines = if closed {
+                        points.len()
+                    } else {
+                        points.len().saturating_sub(1)
+                    };
+
+                    for i in 0..num_lines {
+                        batch.push(OccluderLine {
+                            line: Line(points[i], points[(i + 1) % points.len()]),
+                            height,
+                            ignore_light_index1: None,
+                            ignore_light_index2: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn solid_objects(&self) -> impl Iterator<Item = &ObjectShape> {
+        self.def
+            .layers
+            .iter()
+            .filter_map(|layer| match layer {
+                LayerDef::Objects(layer) => Some(layer),
+                LayerDef::Tiles(_) => None,
+            })
+            .flat_map(|layer| {
+                let layer_solid = layer.is_solid();
+
+                layer
+                    .objects
+                    .iter()
+                    .filter(move |object| layer_solid || object.is_solid())
+                    .map(|object| &object.shape)
+            })
+    }
+}
+
+fn chunk_rect(def: &TilemapDef, origin: Point2<u32>, chunk_size: u32) -> Rect {
+    let end = Point2::new(
+        origin.x.saturating_add(chunk_size).min(def.size.x),
+        origin.y.saturating_add(chunk_size).min(def.size.y),
+    );
+    let top_left = def.tile_rect(origin).top_left();
+    let bottom_right = def.tile_rect(end).top_left();
+
+    Rect::from_top_left(top_left, bottom_right - top_left)
+}
+
+/// A single tile, taking Tiled's flip flags into account.
+struct TileSprite<'a> {
+    rect: Rect,
+    depth: f32,
+    tileset: &'a TilesetDef,
+    id: TileId,
+    color: Color4,
+}
+
+impl<'a> Geometry<TriangleTag> for TileSprite<'a> {
+    type Vertex = SpriteVertex;
+
+    fn write(&self, elements: &mut Vec<u32>, vertices: &mut Vec<Self::Vertex>) {
+        let tex_rect = self
+            .tileset
+            .tile_tex_rect(self.id.gid() - self.tileset.first_gid);
+
+        // Texture corners in the order of `Rect::corners`. Tiled applies the
+        // diagonal flip first, followed by the horizontal and vertical flips.
+        let mut tex_corners = tex_rect.corners();
+        if self.id.flipped_diagonally() {
+            tex_corners.swap(1, 3);
+        }
+        if self.id.flipped_horizontally() {
+            tex_corners.swap(0, 1);
+            tex_corners.swap(2, 3);
+        }
+        if self.id.flipped_vertically() {
+            tex_corners.swap(0, 3);
+            tex_corners.swap(1, 2);
+        }
+
+        elements.extend_from_slice(&quad_triangle_indices(vertices.len() as u32));
+
+        for (p, tex_corner) in self.rect.corners().iter().zip(tex_corners) {
+            // Fix up for OpenGL Y flip (necessary since we draw with positive Y down.)
+            let tex_coords = Point2::new(
+                tex_corner.x,
+                self.tileset.image_size.y as f32 - tex_corner.y,
+            );
+
+            vertices.push(SpriteVertex {
+                position: Point3::new(p.x, p.y, self.depth),
+                tex_coords,
+                color: self.color,
+            });
+        }
+    }
+}
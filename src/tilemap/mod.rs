@@ -0,0 +1,12 @@
+mod autotile;
+mod def;
+mod map;
+mod tiled;
+
+pub use autotile::{blob_masks, AutotileError, AutotileMode, AutotileRules, Autotiler};
+pub use def::{
+    LayerDef, ObjectDef, ObjectLayerDef, ObjectShape, Properties, PropertyValue, TileId,
+    TileLayerDef, TilemapDef, TilesetDef, SOLID_PROPERTY,
+};
+pub use map::{LoadTilemapError, NewTilemapError, Tilemap, TilemapParams};
+pub use tiled::TiledError;
@@ -0,0 +1,564 @@
+//! Import of maps in the JSON format of the Tiled map editor.
+//!
+//! Only orthogonal, finite maps with embedded tilesets and CSV-encoded tile
+//! layers are supported.
+
+use std::collections::HashMap;
+
+use nalgebra::{Point2, Vector2};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::geom::{Rect, RotatedRect};
+
+use super::{
+    LayerDef, ObjectDef, ObjectLayerDef, ObjectShape, Properties, PropertyValue, TileId,
+    TileLayerDef, TilemapDef, TilesetDef,
+};
+
+#[derive(Error, Debug)]
+pub enum TiledError {
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("unsupported map orientation `{0}`")]
+    UnsupportedOrientation(String),
+
+    #[error("infinite maps are not supported")]
+    InfiniteMap,
+
+    #[error("external tileset `{0}` is not supported")]
+    ExternalTileset(String),
+
+    #[error("tileset `{0}` has no single image")]
+    TilesetWithoutImage(String),
+
+    #[error("layer `{0}` has unsupported encoding `{1}`")]
+    UnsupportedEncoding(String, String),
+
+    #[error("layer `{0}` has {1} tiles, but expected {2}")]
+    InvalidLayerSize(String, usize, usize),
+
+    #[error("layer `{0}` of size {1}x{2} is too large")]
+    LayerTooLarge(String, u32, u32),
+}
+
+#[derive(Deserialize)]
+struct Map {
+    orientation: String,
+    #[serde(default)]
+    infinite: bool,
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    #[serde(default)]
+    tilesets: Vec<Tileset>,
+    #[serde(default)]
+    layers: Vec<Layer>,
+    #[serde(default)]
+    properties: Vec<Property>,
+}
+
+#[derive(Deserialize)]
+struct Tileset {
+    firstgid: u32,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    imagewidth: u32,
+    #[serde(default)]
+    imageheight: u32,
+    #[serde(default)]
+    tilewidth: u32,
+    #[serde(default)]
+    tileheight: u32,
+    #[serde(default)]
+    margin: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(default)]
+    columns: u32,
+    #[serde(default)]
+    tilecount: u32,
+    #[serde(default)]
+    tiles: Vec<Tile>,
+}
+
+#[derive(Deserialize)]
+struct Tile {
+    id: u32,
+    #[serde(default)]
+    properties: Vec<Property>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum Layer {
+    #[serde(rename = "tilelayer")]
+    Tiles {
+        name: String,
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        data: LayerData,
+        #[serde(default)]
+        encoding: Option<String>,
+        #[serde(default = "default_true")]
+        visible: bool,
+        #[serde(default = "default_one")]
+        opacity: f32,
+        #[serde(default)]
+        properties: Vec<Property>,
+    },
+
+    #[serde(rename = "objectgroup")]
+    Objects {
+        name: String,
+        #[serde(default)]
+        objects: Vec<Object>,
+        #[serde(default = "default_true")]
+        visible: bool,
+        #[serde(default)]
+        properties: Vec<Property>,
+    },
+
+    #[serde(rename = "group")]
+    Group {
+        #[serde(default)]
+        layers: Vec<Layer>,
+    },
+
+    #[serde(rename = "imagelayer")]
+    Image {},
+}
+
+/// Tile layer data is an array for CSV encoding, but a string for base64.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LayerData {
+    Tiles(Vec<u32>),
+    Encoded(serde::de::IgnoredAny),
+}
+
+impl Default for LayerData {
+    fn default() -> Self {
+        LayerData::Tiles(Vec::new())
+    }
+}
+
+#[derive(Deserialize)]
+struct Object {
+    id: u32,
+    #[serde(default)]
+    name: String,
+    // Tiled 1.9 renamed `type` to `class`.
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default)]
+    ellipse: bool,
+    #[serde(default)]
+    point: bool,
+    #[serde(default)]
+    polygon: Option<Vec<Position>>,
+    #[serde(default)]
+    polyline: Option<Vec<Position>>,
+    #[serde(default = "default_true")]
+    visible: bool,
+    #[serde(default)]
+    properties: Vec<Property>,
+}
+
+#[derive(Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+struct Property {
+    name: String,
+    value: serde_json::Value,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_one() -> f32 {
+    1.0
+}
+
+impl TilemapDef {
+    pub fn from_tiled_json(json: &str) -> Result<Self, TiledError> {
+        let map: Map = serde_json::from_str(json)?;
+
+        if map.orientation != "orthogonal" {
+            return Err(TiledError::UnsupportedOrientation(map.orientation));
+        }
+        if map.infinite {
+            return Err(TiledError::InfiniteMap);
+        }
+
+        let tilesets = map
+            .tilesets
+            .into_iter()
+            .map(convert_tileset)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut layers = Vec::new();
+        convert_layers(map.layers, &mut layers)?;
+
+        Ok(Self {
+            size: Vector2::new(map.width, map.height),
+            tile_size: Vector2::new(map.tilewidth, map.tileheight),
+            tilesets,
+            layers,
+            properties: convert_properties(map.properties),
+        })
+    }
+}
+
+fn convert_tileset(tileset: Tileset) -> Result<TilesetDef, TiledError> {
+    if let Some(source) = tileset.source {
+        return Err(TiledError::ExternalTileset(source));
+    }
+
+    let image = tileset
+        .image
+        .ok_or_else(|| TiledError::TilesetWithoutImage(tileset.name.clone()))?;
+
+    Ok(TilesetDef {
+        name: tileset.name,
+        first_gid: tileset.firstgid,
+        image,
+        image_size: Vector2::new(tileset.imagewidth, tileset.imageheight),
+        tile_size: Vector2::new(tileset.tilewidth, tileset.tileheight),
+        margin: tileset.margin,
+        spacing: tileset.spacing,
+        columns: tileset.columns,
+        tile_count: tileset.tilecount,
+        tile_properties: tileset
+            .tiles
+            .into_iter()
+            .map(|tile| (tile.id, convert_properties(tile.properties)))
+            .collect(),
+    })
+}
+
+fn convert_layers(layers: Vec<Layer>, output: &mut Vec<LayerDef>) -> Result<(), TiledError> {
+    for layer in layers {
+        match layer {
+            Layer::Tiles {
+                name,
+                width,
+                height,
+                data,
+                encoding,
+                visible,
+                opacity,
+                properties,
+            } => {
+                if let Some(encoding) = encoding.filter(|encoding| encoding != "csv") {
+                    return Err(TiledError::UnsupportedEncoding(name, encoding));
+                }
+
+                let data = match data {
+                    LayerData::Tiles(data) => data,
+                    LayerData::Encoded(_) => {
+                        return Err(TiledError::UnsupportedEncoding(name, "string".into()));
+                    }
+                };
+
+                let expected = match (width as usize).checked_mul(height as usize) {
+                    Some(expected) => expected,
+                    None => return Err(TiledError::LayerTooLarge(name, width, height)),
+                };
+                if data.len() != expected {
+                    return Err(TiledError::InvalidLayerSize(name, data.len(), expected));
+                }
+
+                output.push(LayerDef::Tiles(TileLayerDef {
+                    name,
+                    size: Vector2::new(width, height),
+                    tiles: data.into_iter().map(TileId).collect(),
+                    visible,
+                    opacity,
+                    properties: convert_properties(properties),
+                }));
+            }
+            Layer::Objects {
+                name,
+                objects,
+                visible,
+                properties,
+            } => {
+                output.push(LayerDef::Objects(ObjectLayerDef {
+                    name,
+                    objects: objects.into_iter().map(convert_object).collect(),
+                    visible,
+                    properties: convert_properties(properties),
+                }));
+            }
+            Layer::Group { layers } => {
+                convert_layers(layers, output)?;
+            }
+            Layer::Image {} => (),
+        }
+    }
+
+    Ok(())
+}
+
+fn convert_object(object: Object) -> ObjectDef {
+    let origin = Point2::new(object.x, object.y);
+    let points = |points: Vec<Position>| {
+        points
+            .into_iter()
+            .map(|p| origin + rotate(Vector2::new(p.x, p.y), object.rotation))
+            .collect()
+    };
+
+    // Tiled rotates objects clockwise around their top-left corner, given in
+    // degrees. Since our Y axis points down, this matches our angles.
+    let rotated_rect = || {
+        let size = Vector2::new(object.width, object.height);
+
+        RotatedRect {
+            center: origin + rotate(size / 2.0, object.rotation),
+            size,
+            angle: object.rotation.to_radians(),
+        }
+    };
+
+    let shape = if let Some(polygon) = object.polygon {
+        ObjectShape::Polygon(points(polygon))
+    } else if let Some(polyline) = object.polyline {
+        ObjectShape::Polyline(points(polyline))
+    } else if object.point {
+        ObjectShape::Point(origin)
+    } else if object.ellipse {
+        ObjectShape::Ellipse(rotated_rect())
+    } else if object.rotation == 0.0 {
+        ObjectShape::Rect(Rect::from_top_left(
+            origin,
+            Vector2::new(object.width, object.height),
+        ))
+    } else {
+        ObjectShape::RotatedRect(rotated_rect())
+    };
+
+    ObjectDef {
+        id: object.id,
+        name: object.name,
+        class: if object.class.is_empty() {
+            object.kind
+        } else {
+            object.class
+        },
+        shape,
+        visible: object.visible,
+        properties: convert_properties(object.properties),
+    }
+}
+
+fn convert_properties(properties: Vec<Property>) -> Properties {
+    properties
+        .into_iter()
+        .filter_map(|property| {
+            let value = match property.value {
+                serde_json::Value::Bool(value) => PropertyValue::Bool(value),
+                serde_json::Value::Number(value) => value
+                    .as_i64()
+                    .map(PropertyValue::Int)
+                    .or_else(|| value.as_f64().map(PropertyValue::Float))?,
+                serde_json::Value::String(value) => PropertyValue::String(value),
+                _ => return None,
+            };
+
+            Some((property.name, value))
+        })
+        .collect::<HashMap<_, _>>()
+}
+
+fn rotate(v: Vector2<f32>, degrees: f32) -> Vector2<f32> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+
+    Vector2::new(cos * v.x - sin * v.y, sin * v.x + cos * v.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Vector2};
+
+    use crate::tilemap::{LayerDef, ObjectShape, PropertyValue, TileId, TilemapDef};
+
+    use super::TiledError;
+
+    fn map(layers: &str) -> String {
+        format!(
+            r#"{{
+                "orientation": "orthogonal",
+                "width": 3,
+                "height": 2,
+                "tilewidth": 16,
+                "tileheight": 8,
+                "tilesets": [{{
+                    "firstgid": 1,
+                    "name": "ground",
+                    "image": "ground.png",
+                    "imagewidth": 64,
+                    "imageheight": 32,
+                    "tilewidth": 16,
+                    "tileheight": 8,
+                    "columns": 4,
+                    "tilecount": 16,
+                    "tiles": [{{
+                        "id": 2,
+                        "properties": [{{ "name": "solid", "type": "bool", "value": true }}]
+                    }}]
+                }}],
+                "layers": {}
+            }}"#,
+            layers
+        )
+    }
+
+    #[test]
+    fn csv_layers_and_objects() {
+        let json = map(r#"[
+            {
+                "type": "group",
+                "layers": [{
+                    "type": "tilelayer",
+                    "name": "ground",
+                    "width": 3,
+                    "height": 2,
+                    "data": [1, 0, 3, 0, 2, 0],
+                    "opacity": 0.5
+                }]
+            },
+            {
+                "type": "objectgroup",
+                "name": "spawns",
+                "objects": [{
+                    "id": 7,
+                    "name": "player",
+                    "type": "spawn",
+                    "x": 4,
+                    "y": 2,
+                    "point": true,
+                    "properties": [{ "name": "lives", "type": "int", "value": 3 }]
+                }]
+            }
+        ]"#);
+        let def = TilemapDef::from_tiled_json(&json).unwrap();
+
+        assert_eq!(def.size, Vector2::new(3, 2));
+        assert_eq!(def.tile_size, Vector2::new(16, 8));
+        assert_eq!(def.tilesets.len(), 1);
+        assert_eq!(def.tilesets[0].image, "ground.png");
+        assert_eq!(def.layers.len(), 2);
+
+        match &def.layers[0] {
+            LayerDef::Tiles(layer) => {
+                assert_eq!(layer.name, "ground");
+                assert_eq!(layer.opacity, 0.5);
+                assert_eq!(layer.get(Point2::new(2, 0)), TileId(3));
+                assert_eq!(layer.get(Point2::new(1, 1)), TileId(2));
+                assert_eq!(layer.get(Point2::new(3, 0)), TileId(0));
+            }
+            LayerDef::Objects(_) => panic!("expected a tile layer"),
+        }
+        match &def.layers[1] {
+            LayerDef::Objects(layer) => {
+                let object = &layer.objects[0];
+                assert_eq!(object.id, 7);
+                assert_eq!(object.class, "spawn");
+                assert!(
+                    matches!(object.shape, ObjectShape::Point(p) if p == Point2::new(4.0, 2.0))
+                );
+                assert_eq!(object.properties["lives"], PropertyValue::Int(3));
+            }
+            LayerDef::Tiles(_) => panic!("expected an object layer"),
+        }
+
+        assert!(def.is_solid(Point2::new(2, 0)));
+        assert!(!def.is_solid(Point2::new(0, 0)));
+    }
+
+    #[test]
+    fn invalid_layer_size() {
+        let json = map(r#"[{
+            "type": "tilelayer",
+            "name": "ground",
+            "width": 3,
+            "height": 2,
+            "data": [1, 2, 3]
+        }]"#);
+
+        assert!(matches!(
+            TilemapDef::from_tiled_json(&json),
+            Err(TiledError::InvalidLayerSize(name, 3, 6)) if name == "ground"
+        ));
+    }
+
+    #[test]
+    fn layer_too_large() {
+        let json = map(r#"[{
+            "type": "tilelayer",
+            "name": "ground",
+            "width": 4294967295,
+            "height": 4294967295,
+            "data": []
+        }]"#);
+
+        // On 32-bit targets the layer size overflows, elsewhere it mismatches.
+        assert!(matches!(
+            TilemapDef::from_tiled_json(&json),
+            Err(TiledError::LayerTooLarge(..)) | Err(TiledError::InvalidLayerSize(..))
+        ));
+    }
+
+    #[test]
+    fn unsupported_maps() {
+        let isometric = map("[]").replace("orthogonal", "isometric");
+        assert!(matches!(
+            TilemapDef::from_tiled_json(&isometric),
+            Err(TiledError::UnsupportedOrientation(orientation)) if orientation == "isometric"
+        ));
+
+        let base64 = map(r#"[{
+            "type": "tilelayer",
+            "name": "ground",
+            "width": 3,
+            "height": 2,
+            "encoding": "base64",
+            "data": "AAAA"
+        }]"#);
+        assert!(matches!(
+            TilemapDef::from_tiled_json(&base64),
+            Err(TiledError::UnsupportedEncoding(_, encoding)) if encoding == "base64"
+        ));
+
+        let external = map("[]").replace(r#""name": "ground""#, r#""source": "ground.tsx""#);
+        assert!(matches!(
+            TilemapDef::from_tiled_json(&external),
+            Err(TiledError::ExternalTileset(source)) if source == "ground.tsx"
+        ));
+    }
+}
@@ -1,12 +1,21 @@
+#[cfg(feature = "web")]
 use web_sys::HtmlCanvasElement;
 
+#[cfg(feature = "web")]
 use nalgebra::Vector2;
 
+#[cfg(feature = "web")]
 pub fn device_pixel_ratio() -> f64 {
     let window = web_sys::window().expect("Failed to obtain window");
     window.device_pixel_ratio()
 }
 
+#[cfg(not(feature = "web"))]
+pub fn device_pixel_ratio() -> f64 {
+    1.0
+}
+
+#[cfg(feature = "web")]
 pub fn logical_to_physical_size(logical_size: Vector2<u32>) -> Vector2<u32> {
     let scale_factor = device_pixel_ratio();
 
@@ -22,21 +31,25 @@ pub fn logical_to_physical_size(logical_size: Vector2<u32>) -> Vector2<u32> {
     )
 }
 
+#[cfg(feature = "web")]
 pub fn set_canvas_physical_size(canvas: &HtmlCanvasElement, physical_size: Vector2<u32>) {
     canvas.set_width(physical_size.x);
     canvas.set_height(physical_size.y);
 }
 
+#[cfg(feature = "web")]
 pub fn set_canvas_logical_size(canvas: &HtmlCanvasElement, logical_size: Vector2<u32>) {
     set_canvas_style_property(canvas, "width", &format!("{}px", logical_size.x));
     set_canvas_style_property(canvas, "height", &format!("{}px", logical_size.y));
 }
 
+#[cfg(feature = "web")]
 pub fn set_canvas_logical_size_fill(canvas: &HtmlCanvasElement) {
     set_canvas_style_property(canvas, "width", "100vw");
     set_canvas_style_property(canvas, "height", "100vh");
 }
 
+#[cfg(feature = "web")]
 pub fn set_canvas_style_property(canvas: &HtmlCanvasElement, property: &str, value: &str) {
     let style = canvas.style();
     style
@@ -44,6 +57,7 @@ pub fn set_canvas_style_property(canvas: &HtmlCanvasElement, property: &str, val
         .unwrap_or_else(|_| panic!("Failed to set {}", property));
 }
 
+#[cfg(feature = "web")]
 pub fn make_canvas_focusable(canvas: &HtmlCanvasElement) {
     canvas.set_attribute("tabIndex", "1").unwrap();
 }
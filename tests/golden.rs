@@ -0,0 +1,182 @@
+//! Renders geometry with `raster::Rasterizer` and compares the result against
+//! the PNG files in `tests/goldens`. Run with `MALEN_UPDATE_GOLDENS=1` to
+//! recreate them after intended changes.
+
+#![cfg(feature = "golden")]
+
+use std::{path::PathBuf, rc::Rc};
+
+use nalgebra::{Matrix3, Point2, Vector2};
+
+use malen::{
+    data::{ColorCircle, GeometryBuffer, Sprite, SpriteVertex, TriangleTag},
+    geom::{Circle, Rect, Screen},
+    gl::{self, Blend},
+    pass::{SpritePass, ViewMatrices},
+    plot::{Axis, LineGraph, Plot, PlotBatch, PlotStyle},
+    raster::{
+        golden::{assert_golden, GoldenTolerance},
+        Rasterizer, RgbaImage,
+    },
+    text::Font,
+    Color4,
+};
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/goldens")
+        .join(name)
+}
+
+/// Returns a rasterizer whose coordinates are in pixels, with the origin in
+/// the top-left corner.
+fn new_rasterizer(size: Vector2<u32>) -> Rasterizer {
+    let mut rasterizer = Rasterizer::new(size, Color4::new(0.0, 0.0, 0.0, 1.0));
+    rasterizer.set_matrices(&ViewMatrices {
+        projection: Screen::project_to_ndc(nalgebra::convert(size)),
+        view: Matrix3::identity(),
+    });
+
+    rasterizer
+}
+
+#[test]
+fn circles() {
+    let mut rasterizer = new_rasterizer(Vector2::new(64, 64));
+    rasterizer.set_blend(Some(Blend::default()));
+
+    let mut triangles = GeometryBuffer::<TriangleTag, _>::new();
+    for (center, radius, num_segments, color) in [
+        ((20.0, 20.0), 16.0, 32, Color4::new(1.0, 0.0, 0.0, 1.0)),
+        ((44.0, 24.0), 14.0, 6, Color4::new(0.0, 1.0, 0.0, 0.5)),
+        ((32.0, 44.0), 18.0, 16, Color4::new(0.0, 0.0, 1.0, 0.5)),
+    ] {
+        triangles.push(ColorCircle {
+            circle: Circle {
+                center: Point2::new(center.0, center.1),
+                radius,
+            },
+            depth: 0.0,
+            angle: 0.0,
+            num_segments,
+            color,
+        });
+    }
+    rasterizer.draw_colors(&triangles);
+
+    assert_golden(
+        &rasterizer.image(),
+        golden_path("circles.png"),
+        GoldenTolerance::default(),
+    );
+}
+
+#[test]
+fn sprites() {
+    // Four texels of different color, in the layout that is uploaded.
+    let texture = RgbaImage::from_data(
+        Vector2::new(2, 2),
+        vec![
+            255, 0, 0, 255, 0, 255, 0, 255, //
+            0, 0, 255, 255, 255, 255, 255, 255,
+        ],
+    );
+
+    let mut rasterizer = new_rasterizer(Vector2::new(64, 64));
+    rasterizer.set_blend(Some(Blend::default()));
+
+    let mut sprites = GeometryBuffer::<TriangleTag, SpriteVertex>::new();
+    sprites.push(Sprite {
+        rect: Rect::from_top_left(Point2::new(0.0, 0.0), Vector2::new(32.0, 32.0)),
+        depth: 0.0,
+        tex_rect: Rect::from_top_left(Point2::origin(), Vector2::new(2.0, 2.0)),
+        color: Color4::new(1.0, 1.0, 1.0, 1.0),
+    });
+    sprites.push(Sprite {
+        rect: Rect::from_top_left(Point2::new(36.0, 4.0), Vector2::new(24.0, 12.0)),
+        depth: 0.0,
+        tex_rect: Rect::from_top_left(Point2::new(1.0, 0.0), Vector2::new(1.0, 2.0)),
+        color: Color4::new(1.0, 1.0, 1.0, 1.0),
+    });
+    sprites.push(Sprite {
+        rect: Rect::from_top_left(Point2::new(16.0, 24.0), Vector2::new(40.0, 36.0)),
+        depth: 0.0,
+        tex_rect: Rect::from_top_left(Point2::origin(), Vector2::new(2.0, 2.0)),
+        color: Color4::new(1.0, 0.5, 0.5, 0.5),
+    });
+    rasterizer.draw_sprites(&texture, &sprites);
+
+    assert_golden(
+        &rasterizer.image(),
+        golden_path("sprites.png"),
+        GoldenTolerance::default(),
+    );
+}
+
+/// Renders the background, graphs and axes of a plot. Text is left out, since
+/// glyphs are rasterized into a GPU atlas.
+#[test]
+fn plot() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let sprite_pass = Rc::new(SpritePass::new(gl.clone()).unwrap());
+    let mut font = Font::new(
+        gl.clone(),
+        sprite_pass,
+        include_bytes!("../examples/playground/resources/RobotoMono-Regular.ttf"),
+        20.0,
+    )
+    .unwrap();
+
+    let mut batch = PlotBatch::new(gl).unwrap();
+    batch
+        .push(
+            &mut font,
+            Plot {
+                rect: Rect::from_top_left(Point2::new(8.0, 8.0), Vector2::new(224.0, 144.0)),
+                x_axis: Axis {
+                    label: "x".into(),
+                    range: None,
+                    tics: 2.0,
+                },
+                y_axis: Axis {
+                    label: "y".into(),
+                    range: Some((-1.0, 1.0)),
+                    tics: 0.5,
+                },
+                line_graphs: vec![
+                    LineGraph {
+                        caption: "sin".into(),
+                        color: Color4::new(1.0, 0.0, 0.0, 1.0),
+                        points: (0..=40)
+                            .map(|i| i as f32 * 0.2)
+                            .map(|x| (x, x.sin()))
+                            .collect(),
+                    },
+                    LineGraph {
+                        caption: "cos".into(),
+                        color: Color4::new(0.0, 0.0, 1.0, 1.0),
+                        points: (0..=40)
+                            .map(|i| i as f32 * 0.2)
+                            .map(|x| (x, x.cos()))
+                            .collect(),
+                    },
+                ],
+            },
+            PlotStyle {
+                axis_margin: Vector2::new(24.0, 16.0),
+                ..PlotStyle::default()
+            },
+        )
+        .unwrap();
+
+    let mut rasterizer = new_rasterizer(Vector2::new(240, 160));
+    rasterizer.set_blend(Some(Blend::default()));
+    rasterizer.draw_colors(batch.triangle_batch.buffer());
+    rasterizer.draw_colors(batch.line_batch.buffer());
+
+    assert_golden(
+        &rasterizer.image(),
+        golden_path("plot.png"),
+        GoldenTolerance::default(),
+    );
+}
@@ -0,0 +1,644 @@
+//! Runs the passes against `gl::Context::new_mock()` and checks the draw calls
+//! that they issue.
+
+use std::rc::Rc;
+
+use nalgebra::{Matrix3, Point2, Point3, Vector2};
+
+use malen::{
+    data::{
+        ColorRect, ColorTriangleBatch, Mesh, PackedSpriteBatch, PackedSpriteVertex, PackedSprites,
+        Sprite, SpriteBatch, SpriteVertex,
+    },
+    geom::Rect,
+    gl::{
+        self, DrawParams, Framebuffer, MockDrawCall, RenderCommand, Texture, TextureId,
+        TextureParams, TextureValueType, Uniform, VertexBuffer,
+    },
+    glow,
+    light::{
+        GlobalLightProps, Light, LightPipeline, LightPipelineParams, ObjectLightProps, OccluderRect,
+    },
+    pass::{BlurBuffer, BlurParams, BlurPass, ColorPass, SpritePass, ViewMatrices},
+    Color3, Color4,
+};
+
+fn new_texture(gl: &Rc<gl::Context>, size: Vector2<u32>) -> Texture {
+    Texture::new(gl.clone(), size, TextureParams::linear_rgbau8()).unwrap()
+}
+
+fn sprite(x: f32) -> Sprite {
+    Sprite {
+        rect: Rect::from_top_left(Point2::new(x, 0.0), Vector2::new(1.0, 1.0)),
+        depth: 0.0,
+        tex_rect: Rect::from_top_left(Point2::origin(), Vector2::new(8.0, 8.0)),
+        color: Color4::new(1.0, 1.0, 1.0, 1.0),
+    }
+}
+
+/// Returns the textures that a draw call renders into.
+fn targets(gl: &gl::Context, call: &MockDrawCall) -> Vec<TextureId> {
+    call.framebuffer
+        .map(|framebuffer| {
+            gl.mock()
+                .unwrap()
+                .framebuffer_attachments(framebuffer)
+                .into_iter()
+                .map(|(_, texture)| texture)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn sampled(call: &MockDrawCall) -> Vec<TextureId> {
+    call.textures.iter().map(|(_, texture)| *texture).collect()
+}
+
+#[test]
+fn sprite_pass_draws_batch() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let sprite_pass = SpritePass::new(gl.clone()).unwrap();
+    let texture = new_texture(&gl, Vector2::new(8, 8));
+    let matrices = Uniform::new(gl.clone(), ViewMatrices::default()).unwrap();
+
+    let mut batch = SpriteBatch::new(gl.clone()).unwrap();
+    for i in 0..3 {
+        batch.push(sprite(i as f32));
+    }
+
+    gl.mock().unwrap().take_draw_calls();
+    sprite_pass.draw(
+        &matrices,
+        &texture,
+        batch.draw_unit(),
+        &DrawParams::default(),
+    );
+
+    let calls = gl.mock().unwrap().take_draw_calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].mode, glow::TRIANGLES);
+    assert_eq!(calls[0].count, 3 * 6);
+    assert_eq!(calls[0].element_type, glow::UNSIGNED_SHORT);
+    assert_eq!(calls[0].framebuffer, None);
+    assert_eq!(sampled(&calls[0]), vec![texture.id()]);
+}
+
+#[test]
+fn sprite_pass_draws_packed_batch() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let sprite_pass = SpritePass::new(gl.clone()).unwrap();
+    let texture = new_texture(&gl, Vector2::new(4096, 16));
+    let matrices = Uniform::new(gl.clone(), ViewMatrices::default()).unwrap();
+
+    let mut batch = PackedSpriteBatch::new(gl.clone()).unwrap();
+    batch.push(PackedSprites {
+        geometry: Sprite {
+            tex_rect: Rect::from_top_left(Point2::new(4095.0, 8.0), Vector2::new(1.0, 8.0)),
+            ..sprite(0.0)
+        },
+        texture_size: texture.size(),
+    });
+
+    gl.mock().unwrap().take_draw_calls();
+    sprite_pass
+        .draw_packed(
+            &matrices,
+            &texture,
+            batch.draw_unit(),
+            &DrawParams::default(),
+        )
+        .unwrap();
+    assert_eq!(gl.mock().unwrap().take_draw_calls().len(), 1);
+
+    // Texture coordinates are normalized, so that texels of large textures
+    // can still be told apart.
+    let tex_coords: Vec<[u16; 2]> = batch
+        .buffer()
+        .vertices()
+        .iter()
+        .map(|vertex: &PackedSpriteVertex| vertex.tex_coords.0)
+        .collect();
+    let texel = |x: f32| (x * u16::MAX as f32).round() as u16;
+    assert!(tex_coords.contains(&[texel(4095.0 / 4096.0), texel(0.5)]));
+    assert!(tex_coords.contains(&[u16::MAX, u16::MAX]));
+}
+
+#[test]
+fn blur_pass_alternates_between_buffers() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let blur_pass = BlurPass::new(gl.clone(), BlurParams::default()).unwrap();
+    let mut blur_buffer = BlurBuffer::new(gl.clone()).unwrap();
+    let input = new_texture(&gl, Vector2::new(16, 16));
+    let output = Framebuffer::from_textures(vec![new_texture(&gl, Vector2::new(16, 16))]).unwrap();
+    let output_texture = output.textures()[0].id();
+
+    gl.mock().unwrap().take_draw_calls();
+    blur_pass
+        .blur(2, &input, 0, &mut blur_buffer, &output)
+        .unwrap();
+
+    // Each iteration blurs horizontally into the back buffer, and then
+    // vertically into the output.
+    let calls = gl.mock().unwrap().take_draw_calls();
+    assert_eq!(calls.len(), 4);
+
+    let back_texture = targets(&gl, &calls[0]);
+    assert_eq!(back_texture.len(), 1);
+    assert_ne!(back_texture, vec![output_texture]);
+
+    assert_eq!(sampled(&calls[0]), vec![input.id()]);
+    assert_eq!(targets(&gl, &calls[1]), vec![output_texture]);
+    assert_eq!(sampled(&calls[1]), back_texture);
+    assert_eq!(sampled(&calls[2]), vec![output_texture]);
+    assert_eq!(targets(&gl, &calls[2]), back_texture);
+    assert_eq!(targets(&gl, &calls[3]), vec![output_texture]);
+
+    for call in &calls {
+        assert_eq!(call.viewport, [0, 0, 16, 16]);
+    }
+}
+
+#[test]
+fn light_pipeline_runs_phases() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let color_pass = Rc::new(ColorPass::new(gl.clone()).unwrap());
+    let mut pipeline = LightPipeline::with_gl(
+        gl.clone(),
+        color_pass,
+        LightPipelineParams {
+            shadow_map_resolution: 64,
+            max_num_lights: 4,
+            ..LightPipelineParams::default()
+        },
+    )
+    .unwrap();
+
+    let matrices = Uniform::new(gl.clone(), ViewMatrices::default()).unwrap();
+    let object_light_props = Uniform::new(gl.clone(), ObjectLightProps::default()).unwrap();
+
+    let mut colors = ColorTriangleBatch::new(gl.clone()).unwrap();
+    colors.push(ColorRect {
+        rect: Rect::from_top_left(Point2::origin(), Vector2::new(10.0, 10.0)),
+        z: 0.0,
+        color: Color4::new(1.0, 0.0, 0.0, 1.0),
+    });
+
+    let mut occluders = pipeline.new_occluder_batch().unwrap();
+    occluders.push(OccluderRect {
+        rect: Rect::from_top_left(Point2::new(20.0, 20.0), Vector2::new(5.0, 5.0)),
+        height: 1.0,
+        ignore_light_index1: None,
+        ignore_light_index2: None,
+    });
+
+    let lights = [Light {
+        position: Point3::new(0.0, 0.0, 10.0),
+        radius: 100.0,
+        angle: 0.0,
+        angle_size: std::f32::consts::PI * 2.0,
+        start: 0.0,
+        back_glow: 0.0,
+        color: Color3::new(1.0, 1.0, 1.0),
+    }];
+
+    gl.mock().unwrap().take_draw_calls();
+
+    pipeline
+        .geometry_phase(&matrices)
+        .unwrap()
+        .draw_colors(
+            &object_light_props,
+            colors.draw_unit(),
+            &DrawParams::default(),
+        )
+        .shadow_map_phase(&lights)
+        .draw_occluders(&mut occluders)
+        .build_screen_light(GlobalLightProps::default())
+        .unwrap()
+        .compose();
+
+    let calls = gl.mock().unwrap().take_draw_calls();
+    assert_eq!(calls.len(), 4);
+
+    let (geometry, shadow_map, screen_light, compose) =
+        (&calls[0], &calls[1], &calls[2], &calls[3]);
+
+    assert!(targets(&gl, geometry).contains(&pipeline.screen_albedo().id()));
+    assert!(targets(&gl, geometry).contains(&pipeline.screen_normals().id()));
+    assert_eq!(geometry.count, 6);
+
+    assert_eq!(targets(&gl, shadow_map), vec![pipeline.shadow_map().id()]);
+    assert_eq!(shadow_map.instance_count, Some(lights.len() as i32));
+
+    assert_eq!(
+        targets(&gl, screen_light),
+        vec![pipeline.screen_light().id()]
+    );
+    assert!(sampled(screen_light).contains(&pipeline.shadow_map().id()));
+
+    assert_eq!(compose.framebuffer, None);
+    assert!(sampled(compose).contains(&pipeline.screen_albedo().id()));
+    assert!(sampled(compose).contains(&pipeline.screen_light().id()));
+}
+
+#[test]
+fn light_pipeline_keeps_screen_textures_per_viewport() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let color_pass = Rc::new(ColorPass::new(gl.clone()).unwrap());
+    let mut pipeline =
+        LightPipeline::with_gl(gl.clone(), color_pass, LightPipelineParams::default()).unwrap();
+    let matrices = Uniform::new(gl.clone(), ViewMatrices::default()).unwrap();
+
+    let viewports = [[0, 0, 40, 30], [40, 0, 20, 30]];
+    let mut render = |viewport: [i32; 4]| {
+        gl::with_viewport(&gl, viewport, || {
+            pipeline
+                .geometry_phase(&matrices)
+                .unwrap()
+                .shadow_map_phase(&[])
+                .build_screen_light(GlobalLightProps::default())
+                .unwrap()
+                .compose();
+        });
+
+        let albedo = pipeline.screen_albedo();
+        assert_eq!(
+            albedo.size(),
+            Vector2::new(viewport[2] as u32, viewport[3] as u32)
+        );
+        albedo.id()
+    };
+
+    let ids = viewports.map(&mut render);
+    for _ in 0..3 {
+        assert_eq!(viewports.map(&mut render), ids);
+    }
+}
+
+#[test]
+fn objects_are_recreated_after_context_loss() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let sprite_pass = SpritePass::new(gl.clone()).unwrap();
+    let texture = new_texture(&gl, Vector2::new(8, 8));
+    let matrices = Uniform::new(gl.clone(), ViewMatrices::default()).unwrap();
+
+    let mesh: Mesh<SpriteVertex> = Mesh::from_geometry(gl.clone(), sprite(0.0)).unwrap();
+    let mut batch = SpriteBatch::new(gl.clone()).unwrap();
+    batch.push(sprite(1.0));
+    batch.push(sprite(2.0));
+    batch.flush();
+
+    let streamed = VertexBuffer::<SpriteVertex>::new(gl.clone()).unwrap();
+    streamed.set(&[bytemuck::Zeroable::zeroed(); 4]);
+
+    let old_mesh_buffer = mesh.vertex_buffer().id();
+    let old_mesh_data = gl.mock().unwrap().buffer_data(old_mesh_buffer).unwrap();
+    let old_batch_buffer = batch.vertex_array().vertex_buffers().id();
+    let old_streamed = streamed.id();
+    let old_texture = texture.id();
+
+    gl.mark_lost();
+    gl.mark_restored();
+
+    // Static buffers are restored with their data.
+    let mesh_buffer = mesh.vertex_buffer().id();
+    assert_ne!(mesh_buffer, old_mesh_buffer);
+    assert_eq!(
+        gl.mock().unwrap().buffer_data(mesh_buffer),
+        Some(old_mesh_data)
+    );
+    assert_eq!(mesh.element_buffer().len(), 6);
+
+    // Streamed buffers are restored empty.
+    assert_eq!(streamed.len(), 0);
+    assert_ne!(streamed.id(), old_streamed);
+    assert_eq!(
+        gl.mock().unwrap().buffer_data(streamed.id()),
+        Some(Vec::new())
+    );
+
+    gl.mock().unwrap().take_draw_calls();
+    sprite_pass.draw(
+        &matrices,
+        &texture,
+        mesh.draw_unit(),
+        &DrawParams::default(),
+    );
+    sprite_pass.draw(
+        &matrices,
+        &texture,
+        batch.draw_unit(),
+        &DrawParams::default(),
+    );
+
+    // Batches upload their geometry again.
+    assert_ne!(batch.vertex_array().vertex_buffers().id(), old_batch_buffer);
+    assert_eq!(batch.vertex_array().vertex_buffers().len(), 2 * 4);
+
+    let calls = gl.mock().unwrap().take_draw_calls();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].count, 6);
+    assert_eq!(calls[1].count, 2 * 6);
+    for call in &calls {
+        assert_ne!(sampled(call), vec![old_texture]);
+        assert_eq!(sampled(call), vec![texture.id()]);
+    }
+}
+
+fn scaled_matrices(scale: f32) -> ViewMatrices {
+    ViewMatrices {
+        projection: Matrix3::identity() * scale,
+        view: Matrix3::identity(),
+    }
+}
+
+#[test]
+fn recorded_uniforms_are_replayed() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let sprite_pass = SpritePass::new(gl.clone()).unwrap();
+    let texture = new_texture(&gl, Vector2::new(8, 8));
+    let matrices = Uniform::new(gl.clone(), scaled_matrices(1.0)).unwrap();
+    let initial_data = gl.mock().unwrap().buffer_data(matrices.id()).unwrap();
+
+    let mut batch = SpriteBatch::new(gl.clone()).unwrap();
+    batch.push(sprite(0.0));
+
+    gl.mock().unwrap().take_draw_calls();
+    let (commands, ()) = gl::record(&gl, || {
+        for scale in [2.0, 3.0] {
+            matrices.set(scaled_matrices(scale));
+            sprite_pass.draw(
+                &matrices,
+                &texture,
+                batch.draw_unit(),
+                &DrawParams::default(),
+            );
+        }
+    });
+
+    // Nothing has been executed yet.
+    assert!(gl.mock().unwrap().take_draw_calls().is_empty());
+    assert_eq!(
+        gl.mock().unwrap().buffer_data(matrices.id()),
+        Some(initial_data)
+    );
+
+    let uploads: Vec<Vec<u8>> = commands
+        .commands()
+        .iter()
+        .filter_map(|command| match command {
+            RenderCommand::SetBufferData {
+                target: glow::UNIFORM_BUFFER,
+                buffer,
+                data,
+                ..
+            } if *buffer == matrices.id() => Some(data.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(uploads.len(), 2);
+    assert_ne!(uploads[0], uploads[1]);
+    assert_eq!(commands.num_draws(), 2);
+
+    commands.execute(&gl);
+
+    assert_eq!(gl.mock().unwrap().take_draw_calls().len(), 2);
+    assert_eq!(
+        gl.mock().unwrap().buffer_data(matrices.id()),
+        Some(uploads[1].clone())
+    );
+}
+
+#[test]
+fn recorded_frames_can_be_diffed_and_serialized() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let sprite_pass = SpritePass::new(gl.clone()).unwrap();
+    let texture = new_texture(&gl, Vector2::new(8, 8));
+    let matrices = Uniform::new(gl.clone(), scaled_matrices(1.0)).unwrap();
+
+    let mut batch = SpriteBatch::new(gl.clone()).unwrap();
+    batch.push(sprite(0.0));
+    batch.flush();
+
+    let mut record_frame = |scale: f32| {
+        gl::record(&gl, || {
+            matrices.set(scaled_matrices(scale));
+            gl::clear_color(&gl, Color4::new(0.0, 0.0, 0.0, 1.0));
+            sprite_pass.draw(
+                &matrices,
+                &texture,
+                batch.draw_unit(),
+                &DrawParams::default(),
+            );
+        })
+        .0
+    };
+
+    let first = record_frame(1.0);
+    assert_eq!(first.to_string(), record_frame(1.0).to_string());
+
+    // Only the upload of the changed uniform block differs.
+    let second = first.to_string();
+    let third = record_frame(2.0).to_string();
+    let changed: Vec<_> = second
+        .lines()
+        .zip(third.lines())
+        .filter(|(a, b)| a != b)
+        .collect();
+    assert_eq!(second.lines().count(), third.lines().count());
+    assert_eq!(changed.len(), 1);
+    assert!(changed[0].0.starts_with("SetBufferData"));
+
+    let json = serde_json::to_value(&first).unwrap();
+    let commands = json["commands"].as_array().unwrap();
+    assert_eq!(commands.len(), first.len());
+    assert!(commands
+        .iter()
+        .any(|command| command.get("SetBufferData").is_some()));
+    assert!(commands.iter().any(|command| command.get("Draw").is_some()));
+}
+
+#[test]
+fn redundant_state_changes_are_skipped() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let sprite_pass = SpritePass::new(gl.clone()).unwrap();
+    let texture = new_texture(&gl, Vector2::new(8, 8));
+    let matrices = Uniform::new(gl.clone(), ViewMatrices::default()).unwrap();
+
+    let mut batch = SpriteBatch::new(gl.clone()).unwrap();
+    batch.push(sprite(0.0));
+    batch.flush();
+
+    let mut draw = || {
+        sprite_pass.draw(
+            &matrices,
+            &texture,
+            batch.draw_unit(),
+            &DrawParams::default(),
+        );
+    };
+
+    draw();
+    gl.reset_state_cache_stats();
+    draw();
+
+    // The second draw binds the same objects as the first one.
+    let stats = gl.state_cache_stats();
+    for counter in [stats.program, stats.vertex_array, stats.texture] {
+        assert_eq!(counter.issued, 0);
+        assert!(counter.skipped > 0);
+    }
+    assert_eq!(gl.mock().unwrap().take_draw_calls().len(), 2);
+
+    gl.set_state_cache_enabled(false);
+    gl.reset_state_cache_stats();
+    draw();
+
+    let stats = gl.state_cache_stats();
+    assert_eq!(stats.skipped(), 0);
+    assert!(stats.program.issued > 0);
+    assert_eq!(gl.mock().unwrap().take_draw_calls().len(), 1);
+}
+
+#[test]
+fn frame_stats_are_reset_per_frame() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let sprite_pass = SpritePass::new(gl.clone()).unwrap();
+    let texture = new_texture(&gl, Vector2::new(8, 8));
+    let matrices = Uniform::new(gl.clone(), ViewMatrices::default()).unwrap();
+
+    let mut batch = SpriteBatch::new(gl.clone()).unwrap();
+    for i in 0..3 {
+        batch.push(sprite(i as f32));
+    }
+    batch.flush();
+
+    // Discard the uploads from setting things up.
+    gl.end_frame_stats();
+
+    matrices.set(scaled_matrices(2.0));
+    for _ in 0..2 {
+        sprite_pass.draw(
+            &matrices,
+            &texture,
+            batch.draw_unit(),
+            &DrawParams::default(),
+        );
+    }
+
+    let uniform_bytes = gl.mock().unwrap().buffer_data(matrices.id()).unwrap().len();
+    let stats = gl.frame_stats();
+    assert_eq!(stats.draw_calls, 2);
+    assert_eq!(stats.instanced_draw_calls, 0);
+    assert_eq!(stats.elements, 2 * 3 * 6);
+    assert_eq!(stats.buffer_uploads, 1);
+    assert_eq!(stats.buffer_bytes_uploaded, uniform_bytes);
+    assert_eq!(stats.texture_uploads, 0);
+
+    assert_eq!(gl.end_frame_stats(), stats);
+    assert_eq!(gl.last_frame_stats(), stats);
+    assert_eq!(gl.frame_stats(), Default::default());
+
+    // An empty frame counts nothing, but keeps the previous frame around
+    // until it completes.
+    assert_eq!(gl.last_frame_stats(), stats);
+    assert_eq!(gl.end_frame_stats(), Default::default());
+    assert_eq!(gl.last_frame_stats(), Default::default());
+}
+
+#[test]
+fn resource_totals_follow_object_lifetimes() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let initial = gl.resource_totals();
+
+    let framebuffer =
+        Framebuffer::from_textures(vec![new_texture(&gl, Vector2::new(8, 4))]).unwrap();
+    let texture = new_texture(&gl, Vector2::new(16, 16));
+    let vertices = [bytemuck::Zeroable::zeroed(); 4];
+    let buffer = VertexBuffer::<SpriteVertex>::new_static(gl.clone(), &vertices).unwrap();
+
+    let totals = gl.resource_totals();
+    assert_eq!(totals.num_textures, initial.num_textures + 2);
+    assert_eq!(
+        totals.texture_bytes,
+        initial.texture_bytes + (8 * 4 + 16 * 16) * 4
+    );
+    assert_eq!(totals.num_buffers, initial.num_buffers + 1);
+    assert_eq!(
+        totals.buffer_bytes,
+        initial.buffer_bytes + std::mem::size_of_val(&vertices)
+    );
+    assert_eq!(totals.num_framebuffers, initial.num_framebuffers + 1);
+
+    texture.set_label("atlas");
+    let old_texture = texture.id();
+
+    gl.mark_lost();
+    gl.mark_restored();
+
+    // Recreated objects keep their entries under the new ids.
+    let new_texture = texture.id();
+    assert_ne!(new_texture, old_texture);
+    assert_eq!(gl.resource(old_texture), None);
+    assert_eq!(
+        gl.resource(new_texture).unwrap().label.as_deref(),
+        Some("atlas")
+    );
+
+    // Objects are recreated when they are next used.
+    buffer.id();
+    framebuffer.id();
+    assert_eq!(gl.resource_totals(), totals);
+
+    drop(framebuffer);
+    assert_eq!(
+        gl.resource_totals().num_framebuffers,
+        initial.num_framebuffers
+    );
+    assert_eq!(gl.resource_totals().num_textures, initial.num_textures + 1);
+
+    drop(texture);
+    drop(buffer);
+    assert_eq!(gl.resource_totals(), initial);
+}
+
+#[test]
+fn read_pixels_extracts_components_and_keeps_binding() {
+    let gl = Rc::new(gl::Context::new_mock());
+    let size = Vector2::new(4, 2);
+    let new_attachment =
+        |value_type| Texture::new(gl.clone(), size, TextureParams::nearest(value_type)).unwrap();
+
+    let framebuffer = Framebuffer::from_textures(vec![
+        new_attachment(TextureValueType::RU8),
+        new_attachment(TextureValueType::RgF16),
+        new_attachment(TextureValueType::RgbaU32),
+    ])
+    .unwrap();
+    let other = Framebuffer::from_textures(vec![new_texture(&gl, size)]).unwrap();
+
+    gl.mock().unwrap().take_clears();
+
+    gl::with_framebuffer(&other, || {
+        let origin = Point2::origin();
+        assert_eq!(framebuffer.read_pixels::<u8>(0, origin, size).len(), 4 * 2);
+        assert_eq!(
+            framebuffer
+                .read_pixels::<f32>(1, Point2::new(1, 1), Vector2::new(3, 1))
+                .len(),
+            3 * 2
+        );
+        assert_eq!(
+            framebuffer.read_pixels::<u32>(2, origin, size).len(),
+            4 * 2 * 4
+        );
+        assert_eq!(framebuffer.read_pixel_row_f16(1, 0).len(), 4 * 2);
+
+        // Reading must not change the framebuffer that is rendered into.
+        gl::clear_color(&gl, Color4::new(0.0, 0.0, 0.0, 1.0));
+    });
+
+    let clears = gl.mock().unwrap().take_clears();
+    assert_eq!(clears.len(), 1);
+    assert_eq!(clears[0].framebuffer, Some(other.id()));
+}